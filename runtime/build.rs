@@ -0,0 +1,19 @@
+use std::{env, process::Command};
+
+fn main() {
+    // Expose the target triple being compiled for to the runtime.
+    println!("cargo:rustc-env=RIPTIDE_TARGET={}", env::var("TARGET").unwrap());
+
+    // Embed the current Git commit hash, if we are building from a Git
+    // checkout.
+    let commit = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+
+    if let Some(commit) = commit {
+        println!("cargo:rustc-env=RIPTIDE_GIT_COMMIT={}", commit.trim());
+    }
+}
@@ -0,0 +1,47 @@
+//! Example of exposing functions written in Rust to Riptide scripts.
+
+use riptide_runtime::{prelude::*, table, throw};
+
+/// Add up all of the given numbers.
+async fn sum(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut total = 0.0;
+
+    for arg in args.iter() {
+        match arg.as_number() {
+            Some(number) => total += number,
+            None => throw!("expected a number, got {}", arg.type_name()),
+        }
+    }
+
+    Ok(total.into())
+}
+
+/// Greet someone by name.
+async fn greet(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    match args.first() {
+        Some(name) => Ok(format!("hello, {}!", name).into()),
+        None => throw!("name required"),
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> Result<(), Exception> {
+    let mut fiber = riptide_runtime::init().await?;
+
+    // Define a single function as a global...
+    fiber.register_fn("sum", sum)?;
+
+    // ...or a whole module of functions that scripts can import.
+    fiber.register_table_module("host", table! {
+        "greet" => Value::foreign_fn(greet),
+    })?;
+
+    let result = fiber.execute(None, "
+        import 'host' for greet
+        greet (sum 1 2 3)
+    ").await?;
+
+    println!("{}", result);
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+//! Command aliases.
+//!
+//! An alias maps a command name to the text of a command to run in its place,
+//! such as `ll` to `ls -l`. When a named call does not refer to a function, the
+//! alias with that name is expanded by replacing the name with the aliased
+//! command, keeping any arguments given after it. Aliases are stored by name in
+//! a hidden global table.
+
+use crate::{
+    prelude::*,
+    syntax::{ast::*, parse, source::SourceFile},
+    table,
+    throw,
+    RipString,
+};
+use std::{cell::RefCell, collections::HashMap};
+
+/// This is the name of the hidden global variable that aliases are stored in.
+static ALIASES_GLOBAL: &str = "__aliases";
+
+/// The parsed commands of aliases by name, along with the text each was parsed
+/// from. This is shared by all fibers of a runtime, so that an alias is only
+/// parsed when it is defined rather than every time it is used.
+pub(crate) type Commands = RefCell<HashMap<RipString, (RipString, (String, Vec<CallArg>))>>;
+
+/// Get the table of all defined aliases, if any have been defined.
+pub(crate) fn table(fiber: &Fiber) -> Option<Table> {
+    fiber.globals().get(ALIASES_GLOBAL).as_table()
+}
+
+/// Get the table of all defined aliases, creating it if no aliases have been
+/// defined yet.
+pub(crate) fn table_or_insert(fiber: &Fiber) -> Table {
+    match table(fiber) {
+        Some(aliases) => aliases,
+        None => {
+            let aliases = table!();
+            fiber.globals().set(ALIASES_GLOBAL, aliases.clone());
+            aliases
+        }
+    }
+}
+
+/// Get the names of all defined aliases.
+pub(crate) fn names(fiber: &Fiber) -> Vec<RipString> {
+    match table(fiber) {
+        Some(aliases) => aliases.keys().collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Define an alias, checking that its text is a valid command.
+pub(crate) fn define(fiber: &Fiber, name: RipString, text: RipString) -> Result<(), Exception> {
+    let command = parse_command(&text)?;
+
+    fiber.alias_commands.borrow_mut().insert(name.clone(), (text.clone(), command));
+    table_or_insert(fiber).set(name, text);
+
+    Ok(())
+}
+
+/// Remove an alias, if an alias with the given name is defined.
+pub(crate) fn remove(fiber: &Fiber, name: RipString) {
+    fiber.alias_commands.borrow_mut().remove(&name);
+
+    if let Some(aliases) = table(fiber) {
+        aliases.set(name, Value::Nil);
+    }
+}
+
+/// Get the call that an alias expands to, if an alias with the given name is
+/// defined.
+pub(crate) fn get(fiber: &Fiber, name: &str) -> Result<Option<(String, Vec<CallArg>)>, Exception> {
+    let text = match table(fiber).map(|aliases| aliases.get(name)) {
+        Some(Value::String(text)) => text,
+        _ => return Ok(None),
+    };
+
+    let mut commands = fiber.alias_commands.borrow_mut();
+
+    if let Some((parsed_text, command)) = commands.get(name.as_bytes()) {
+        if *parsed_text == text {
+            return Ok(Some(command.clone()));
+        }
+    }
+
+    // The alias table was changed without using `alias`, such as by resetting
+    // the fiber, so the alias has to be parsed now.
+    let command = parse_command(&text)?;
+    commands.insert(RipString::from(name), (text, command.clone()));
+
+    Ok(Some(command))
+}
+
+/// Parse the text of an alias into the name of the command and the arguments
+/// it is called with.
+///
+/// The text must be a single command call, without pipes or other statements,
+/// so that the arguments of the aliased call can be appended to it.
+fn parse_command(text: &RipString) -> Result<(String, Vec<CallArg>), Exception> {
+    let block = match parse(SourceFile::virtual_from_bytes("<alias>", text.clone())) {
+        Ok(block) => block,
+        Err(e) => throw!("error parsing alias: {}", e),
+    };
+
+    match <[Statement; 1]>::try_from(block.statements) {
+        Ok([Statement::Pipeline(Pipeline { calls, .. })]) => match <[Call; 1]>::try_from(calls) {
+            Ok([Call::Named { function, args, .. }]) => Ok((function, args)),
+            _ => throw!("alias must be a single named command: {}", text),
+        },
+        _ => throw!("alias must be a single named command: {}", text),
+    }
+}
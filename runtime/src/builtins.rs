@@ -3,7 +3,14 @@
 //! Builtins are exposed via the special `builtins` module name.
 
 use crate::{
-    eval,
+    alias,
+    coerce,
+    completion,
+    controlflow::Resolve,
+    eval::{self, Resolution},
+    io::process,
+    jobs,
+    number,
     prelude::*,
     scope::Scope,
     string::RipString,
@@ -11,28 +18,170 @@ use crate::{
     throw,
 };
 use riptide_syntax::source::SourceFile;
-use std::convert::TryInto;
+use std::{cmp::Ordering, path::Path, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    task::JoinSet,
+};
 
 pub(crate) fn load_module() -> Result<Value, Exception> {
     Ok(table! {
+        "alias" => Value::ForeignFn(alias.into()),
+        "arg" => Value::ForeignFn(arg.into()),
+        "arg-count" => Value::ForeignFn(arg_count.into()),
         "backtrace" => Value::ForeignFn(backtrace.into()),
         "call" => Value::ForeignFn(call.into()),
         "cd" => Value::ForeignFn(cd.into()),
+        "complete" => Value::ForeignFn(complete.into()),
+        "defer" => Value::ForeignFn(defer.into()),
+        "enumerate" => Value::ForeignFn(enumerate.into()),
         "exit" => Value::ForeignFn(exit.into()),
+        "foreach" => Value::ForeignFn(foreach.into()),
+        "help" => Value::ForeignFn(help.into()),
+        "in-dir" => Value::ForeignFn(in_dir.into()),
         "include" => Value::ForeignFn(include.into()),
+        "is-block" => Value::ForeignFn(is_block.into()),
+        "is-boolean" => Value::ForeignFn(is_boolean.into()),
+        "is-list" => Value::ForeignFn(is_list.into()),
+        "is-nil" => Value::ForeignFn(is_nil.into()),
+        "is-number" => Value::ForeignFn(is_number.into()),
+        "is-string" => Value::ForeignFn(is_string.into()),
+        "is-table" => Value::ForeignFn(is_table.into()),
+        "jobs" => Value::ForeignFn(jobs_fn.into()),
         "load" => Value::ForeignFn(load.into()),
+        "max" => Value::ForeignFn(max.into()),
+        "merge" => Value::ForeignFn(merge.into()),
+        "min" => Value::ForeignFn(min.into()),
         "nil" => Value::ForeignFn(nil.into()),
         "nth" => Value::ForeignFn(nth.into()),
+        "opt" => Value::ForeignFn(opt.into()),
+        "number-format" => Value::ForeignFn(number_format.into()),
+        "parallel" => Value::ForeignFn(parallel.into()),
+        "range" => Value::ForeignFn(range.into()),
+        "read-all" => Value::ForeignFn(read_all.into()),
+        "read-line" => Value::ForeignFn(read_line.into()),
+        "repeat" => Value::ForeignFn(repeat.into()),
+        "reverse" => Value::ForeignFn(reverse.into()),
+        "sort" => Value::ForeignFn(sort.into()),
+        "strict" => Value::ForeignFn(strict.into()),
+        "table-get" => Value::ForeignFn(table_get.into()),
+        "table-set" => Value::ForeignFn(table_set.into()),
         "throw" => Value::ForeignFn(throw.into()),
+        "trace" => Value::ForeignFn(trace.into()),
         "try" => Value::ForeignFn(try_fn.into()),
         "typeof" => Value::ForeignFn(type_of.into()),
+        "unalias" => Value::ForeignFn(unalias.into()),
+        "version" => Value::ForeignFn(version.into()),
+        "wait" => Value::ForeignFn(wait.into()),
+        "which" => Value::ForeignFn(which.into()),
+        "with-timeout" => Value::ForeignFn(with_timeout.into()),
+        "zip" => Value::ForeignFn(zip.into()),
     }.into())
 }
 
+/// Defines an alias for a command, or lists aliases.
+///
+/// With a name and a command, calls to the name are replaced with the command,
+/// such as `alias ll "ls -l"`. With only a name, returns the command that name
+/// is an alias for. With no arguments, returns a table of all aliases.
+async fn alias(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let aliases = alias::table(fiber).unwrap_or_default();
+
+    match args.as_slice() {
+        // Return a copy, so that changing it does not change the aliases.
+        [] => Ok(aliases.keys()
+            .map(|name| (name.clone(), aliases.get(&name)))
+            .collect::<Table>()
+            .into()),
+        [name] => Ok(aliases.get(coerce::to_string(name))),
+        [name, command] => {
+            alias::define(fiber, coerce::to_string(name), coerce::to_string(command))?;
+
+            Ok(Value::Nil)
+        }
+        _ => throw!("too many arguments"),
+    }
+}
+
+/// Removes the aliases with the given names.
+async fn unalias(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    for name in args.iter() {
+        alias::remove(fiber, coerce::to_string(name));
+    }
+
+    Ok(Value::Nil)
+}
+
+/// Registers a function that completes the arguments of a command at the
+/// interactive prompt, or lists completion functions.
+///
+/// With a command name and a block, the block is called with the words typed
+/// so far whenever the arguments of that command are being completed, and
+/// should return a list of candidates for the last word. Passing `nil` instead
+/// of a block removes it. With only a name, returns the function registered
+/// for it, and with no arguments, returns a table of all of them.
+async fn complete(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    // Only registering a function creates the table, so that reading does not
+    // define a global.
+    let completions = completion::table(fiber).unwrap_or_default();
+
+    match args.as_slice() {
+        // Return a copy, so that changing it does not change the completions.
+        [] => Ok(completions.keys()
+            .map(|name| (name.clone(), completions.get(&name)))
+            .collect::<Table>()
+            .into()),
+        [name] => Ok(completions.get(coerce::to_string(name))),
+        [name, Value::Nil] => {
+            completions.set(coerce::to_string(name), Value::Nil);
+
+            Ok(Value::Nil)
+        }
+        [name, function @ (Value::Block(_) | Value::ForeignFn(_))] => {
+            completion::table_or_insert(fiber).set(coerce::to_string(name), function.clone());
+
+            Ok(Value::Nil)
+        }
+        [_, value] => throw!("completion function must be a block, got {}", value.type_name()),
+        _ => throw!("too many arguments"),
+    }
+}
+
+/// Reports what a command name refers to, resolving it in the same way as
+/// calling it would.
+///
+/// Returns a table with the `kind` of thing the name refers to, which is one of
+/// `builtin`, `function`, `value`, `alias`, or `command`. Aliases also have the
+/// aliased command as `alias`, and external commands have the `path` of the
+/// executable. Returns nil if the name does not refer to anything.
+async fn which(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let name = match args.first() {
+        Some(value) => coerce::to_string(value).to_string(),
+        None => throw!("command name required"),
+    };
+
+    Ok(match eval::resolve_name(fiber, &name, &[])? {
+        Resolution::Value(Value::ForeignFn(_)) => table! { "kind" => "builtin", }.into(),
+        Resolution::Value(Value::Block(_)) => table! { "kind" => "function", }.into(),
+        Resolution::Value(_) => table! { "kind" => "value", }.into(),
+        Resolution::Alias(..) => table! {
+            "kind" => "alias",
+            "alias" => alias::table(fiber).map(|aliases| aliases.get(name.as_str())).unwrap_or_default(),
+        }.into(),
+        Resolution::External => match process::find_executable(fiber, &name) {
+            Some(path) => table! {
+                "kind" => "command",
+                "path" => path,
+            }.into(),
+            None => Value::Nil,
+        },
+    })
+}
+
 /// Changes the current working directory of the current process.
 async fn cd(_fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     let dir = match args.first() {
-        Some(value) => value.to_string().into(),
+        Some(value) => coerce::to_string(value).as_os_str().into(),
         None => dirs::home_dir().unwrap()
     };
 
@@ -42,11 +191,39 @@ async fn cd(_fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     Ok(Value::Nil)
 }
 
+/// Invokes a block with the working directory set to the given directory.
+///
+/// Only the `@cwd` context variable is changed, and only for the duration of
+/// the block, so the working directory is restored even if the block throws.
+async fn in_dir(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (dir, closure) = match args.as_slice() {
+        [] => throw!("directory required"),
+        [dir, Value::Block(closure)] => (coerce::to_string(dir), closure.clone()),
+        [_] | [_, _] => throw!("block to invoke required"),
+        _ => throw!("too many arguments"),
+    };
+
+    // Relative paths are relative to the current working directory, not the
+    // process working directory.
+    let dir = Path::new(coerce::to_string(&fiber.current_dir()).as_os_str()).join(dir.as_os_str());
+
+    if !dir.is_dir() {
+        throw!("not a directory: {}", dir.display());
+    }
+
+    eval::invoke_closure(fiber, &closure, vec![], table!(), table! { "cwd" => dir }, false)
+        .await
+        .resolve()
+}
+
 /// Terminate the current process.
 async fn exit(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     let code = match args.first() {
-        Some(&Value::Number(number)) => number as i32,
-        _ => 0,
+        Some(value) => match coerce::to_number(value) {
+            Some(number) => number as i32,
+            None => throw!("exit code must be a number"),
+        },
+        None => 0,
     };
 
     fiber.exit(code);
@@ -55,11 +232,116 @@ async fn exit(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     Err(Exception::unrecoverable(code as f64))
 }
 
+/// Prints the documentation of a function, given either the function itself or
+/// its name. With no arguments, prints general help instead.
+async fn help(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let text = match args.first() {
+        None => format!(
+            "Riptide {}\n\nUse `help <name>` to show the documentation of a function.\n",
+            env!("CARGO_PKG_VERSION"),
+        ),
+        Some(value) => {
+            let function = match value {
+                Value::Block(_) => value.clone(),
+                name => fiber.get(coerce::to_string(name)),
+            };
+
+            match function {
+                Value::Block(closure) => match closure.doc() {
+                    Some(doc) => format!("{}\n", doc),
+                    None => throw!("no documentation for '{}'", value),
+                },
+                Value::Nil => throw!("no function named '{}'", value),
+                _ => throw!("no documentation for '{}'", value),
+            }
+        }
+    };
+
+    fiber.stdout().write_all(text.as_bytes()).await?;
+
+    Ok(Value::Nil)
+}
+
+/// Returns a table of information about the runtime version and build.
+async fn version(_: &mut Fiber, _: Vec<Value>) -> Result<Value, Exception> {
+    Ok(table! {
+        "version" => env!("CARGO_PKG_VERSION"),
+        "commit" => option_env!("RIPTIDE_GIT_COMMIT").map(Value::from).unwrap_or_default(),
+        "target" => env!("RIPTIDE_TARGET"),
+    }.into())
+}
+
+/// Enables or disables command tracing, returning whether it was previously
+/// enabled. With no arguments, returns whether tracing is enabled.
+async fn trace(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let enabled = fiber.trace();
+
+    if let Some(value) = args.first() {
+        fiber.set_trace(coerce::to_bool(value));
+    }
+
+    Ok(Value::from(enabled))
+}
+
+/// Enables or disables strict mode for the whole program if a value is given,
+/// and returns whether strict mode was enabled before.
+async fn strict(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let enabled = fiber.strict();
+
+    if let Some(value) = args.first() {
+        fiber.set_strict(coerce::to_bool(value));
+    }
+
+    Ok(Value::from(enabled))
+}
+
 /// Returns the name of the primitive type of the given arguments.
 async fn type_of(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     Ok(args.first().map(Value::type_name).map(Value::from).unwrap_or(Value::Nil))
 }
 
+/// Returns true if the given argument is a block.
+///
+/// Native functions are not blocks, even though they can be called like one.
+async fn is_block(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    Ok(is_type(&args, |value| matches!(value, Value::Block(_))))
+}
+
+/// Returns true if the given argument is a boolean.
+async fn is_boolean(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    Ok(is_type(&args, |value| matches!(value, Value::Boolean(_))))
+}
+
+/// Returns true if the given argument is a list.
+async fn is_list(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    Ok(is_type(&args, |value| matches!(value, Value::List(_))))
+}
+
+/// Returns true if the given argument is nil, or if no argument is given.
+async fn is_nil(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    Ok(is_type(&args, Value::is_nil))
+}
+
+/// Returns true if the given argument is a number. Strings that contain a
+/// number are not numbers.
+async fn is_number(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    Ok(is_type(&args, |value| matches!(value, Value::Number(_))))
+}
+
+/// Returns true if the given argument is a string.
+async fn is_string(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    Ok(is_type(&args, |value| matches!(value, Value::String(_))))
+}
+
+/// Returns true if the given argument is a table.
+async fn is_table(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    Ok(is_type(&args, |value| matches!(value, Value::Table(_))))
+}
+
+fn is_type(args: &[Value], predicate: impl Fn(&Value) -> bool) -> Value {
+    Value::from(predicate(args.first().unwrap_or(&Value::Nil)))
+}
+
 /// Parse a string as code, returning it as an executable closure.
 async fn load(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     let script: RipString = match args.first().and_then(Value::as_string) {
@@ -67,8 +349,7 @@ async fn load(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
         None => throw!("first argument must be a string"),
     };
 
-    let script: String = script.try_into().map_err(|e: std::string::FromUtf8Error| e.to_string())?;
-    let file = SourceFile::r#virtual("<dynamic>", script);
+    let file = SourceFile::virtual_from_bytes("<dynamic>", script);
 
     eval::compile(fiber, file).map(Value::from)
 }
@@ -79,7 +360,7 @@ async fn nth(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
         None => throw!("first argument must be a list"),
     };
 
-    let index = match args.get(1).and_then(Value::as_number) {
+    let index = match args.get(1).and_then(coerce::to_number) {
         Some(s) => s,
         None => throw!("index must be a number"),
     };
@@ -87,27 +368,522 @@ async fn nth(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     Ok(list.get(index as usize).cloned().unwrap_or(Value::Nil))
 }
 
+/// Format a number as a string with control over its decimal places and digit
+/// grouping.
+///
+/// The `--decimals` option sets a fixed number of decimal places to round to,
+/// and the `--separator` option sets a string to insert between each group of
+/// thousands.
+async fn number_format(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut decimals = None;
+    let mut separator = None;
+    let mut value = None;
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--decimals" {
+            decimals = match args.next().as_ref().and_then(coerce::to_number) {
+                Some(n) if n >= 0.0 => Some(n as usize),
+                _ => throw!("decimals must be a non-negative number"),
+            };
+        } else if arg == "--separator" {
+            separator = args.next().map(|value| coerce::to_string(&value).to_string());
+        } else if value.is_none() {
+            value = Some(arg);
+        } else {
+            throw!("too many arguments");
+        }
+    }
+
+    let number = match value.as_ref().and_then(coerce::to_number) {
+        Some(number) => number,
+        None => throw!("a number to format is required"),
+    };
+
+    Ok(number::format(number, decimals, separator.as_deref()).into())
+}
+
+/// The most numbers that a range can have. Every number is held in memory at
+/// once, so a larger range is more likely to be a mistake than intended.
+const MAX_RANGE_LEN: usize = 10_000_000;
+
+/// Returns a list of numbers from a start number up to, but not including, an
+/// end number.
+///
+/// An optional step sets the distance between numbers, which defaults to 1. If
+/// start is greater than end the numbers count down instead. Only the magnitude
+/// of the step is used, so the direction is always from start towards end.
+async fn range(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut numbers = [0.0, 0.0, 1.0];
+
+    for (i, number) in numbers.iter_mut().enumerate() {
+        match args.get(i) {
+            Some(value) => match coerce::to_number(value) {
+                Some(n) if n.is_finite() => *number = n,
+                _ => throw!("range arguments must be numbers, got {}", value),
+            },
+            None if i < 2 => throw!("start and end required"),
+            None => {}
+        }
+    }
+
+    let [start, end, step] = numbers;
+
+    if step == 0.0 {
+        throw!("step must not be zero");
+    }
+
+    let step = if start > end { -step.abs() } else { step.abs() };
+    let count = ((end - start) / step).ceil();
+
+    if count > MAX_RANGE_LEN as f64 {
+        throw!("range is too large, the limit is {} numbers", MAX_RANGE_LEN);
+    }
+
+    let count = count as usize;
+
+    Ok((0..count).map(|i| Value::from(start + i as f64 * step)).collect::<Vec<_>>().into())
+}
+
+/// Invokes a block a number of times, passing it the number of the iteration
+/// counting from zero.
+///
+/// Returns nil, unless the `--collect` flag is given, in which case a list of
+/// the values returned by each invocation is returned instead. The language has
+/// no `break` or `continue`, so returning from the block stands in for
+/// `continue` by ending the current iteration, and throwing an exception is the
+/// only way to stop any further iterations.
+async fn repeat(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut collect = false;
+    let mut positional = Vec::new();
+
+    for arg in args {
+        if arg == "--collect" {
+            collect = true;
+        } else {
+            positional.push(arg);
+        }
+    }
+
+    let (count, block) = match positional.as_slice() {
+        [count, block] => (count, block),
+        _ => throw!("count and block required"),
+    };
+
+    let count = match coerce::to_number(count) {
+        Some(n) if n >= 0.0 && n.fract() == 0.0 => n as usize,
+        _ => throw!("repeat count must be a whole number that is not negative, got {}", count),
+    };
+
+    let mut results = Vec::new();
+
+    for i in 0..count {
+        let value = fiber.invoke(block, &[Value::from(i as f64)]).await?;
+
+        if collect {
+            results.push(value);
+        }
+    }
+
+    Ok(if collect { Value::List(results) } else { Value::Nil })
+}
+
+/// Calls a block once for each item in a list, passing the item as the first
+/// argument.
+///
+/// A string is iterated over by character rather than by byte, so it must be
+/// valid UTF-8.
+async fn foreach(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (items, block) = match args.as_slice() {
+        [Value::List(items), block] => (items.clone(), block),
+        [Value::String(string), block] => match string.as_utf8() {
+            Some(string) => (string.chars().map(|c| Value::from(c.to_string())).collect(), block),
+            None => throw!("cannot iterate over characters of a string that is not valid UTF-8"),
+        },
+        [value, _] => throw!("cannot iterate over a value of type {}", value.type_name()),
+        _ => throw!("list and block required"),
+    };
+
+    for item in items {
+        fiber.invoke(block, &[item]).await?;
+    }
+
+    Ok(Value::Nil)
+}
+
+/// Reads a line from standard input, without the line terminator.
+///
+/// If a prompt is given, it is written to standard error first. Returns nil
+/// once the end of input has been reached.
+async fn read_line(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    if let Some(prompt) = args.first() {
+        fiber.stderr().write_all(coerce::to_string(prompt).as_bytes()).await?;
+        fiber.stderr().flush().await?;
+    }
+
+    let mut line = Vec::new();
+    let mut byte = [0];
+
+    // Read one byte at a time, so that nothing past the end of the line is
+    // consumed and left unavailable to whatever reads standard input next.
+    loop {
+        if fiber.stdin().read(&mut byte).await? == 0 {
+            if line.is_empty() {
+                return Ok(Value::Nil);
+            }
+
+            break;
+        }
+
+        if byte[0] == b'\n' {
+            break;
+        }
+
+        line.push(byte[0]);
+    }
+
+    if line.ends_with(b"\r") {
+        line.pop();
+    }
+
+    Ok(RipString::from(line).into())
+}
+
+/// Reads all of standard input until the end, returning it as a single string.
+async fn read_all(fiber: &mut Fiber, _: Vec<Value>) -> Result<Value, Exception> {
+    let mut bytes = Vec::new();
+    fiber.stdin().read_to_end(&mut bytes).await?;
+
+    Ok(RipString::from(bytes).into())
+}
+
+/// Returns the smallest of the given numbers.
+///
+/// The numbers may be given either as separate arguments or as a single list.
+/// If `--by` is given followed by a block, the block is called with each item
+/// and the item with the smallest result is returned instead.
+async fn min(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    extreme(fiber, args, Ordering::Less).await
+}
+
+/// Returns the largest of the given numbers.
+///
+/// Accepts the same arguments as `min`.
+async fn max(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    extreme(fiber, args, Ordering::Greater).await
+}
+
+/// Find the first item whose key is ordered before all others, in the order
+/// given by `wanted`.
+async fn extreme(fiber: &mut Fiber, args: Vec<Value>, wanted: Ordering) -> Result<Value, Exception> {
+    let mut key_fn = None;
+    let mut items = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--by" {
+            key_fn = match args.next() {
+                Some(block) => Some(block),
+                None => throw!("--by requires a block"),
+            };
+        } else {
+            items.push(arg);
+        }
+    }
+
+    if let [Value::List(list)] = items.as_slice() {
+        items = list.clone();
+    }
+
+    let mut best: Option<(Value, f64)> = None;
+
+    for item in items {
+        let key = match &key_fn {
+            Some(key_fn) => fiber.invoke(key_fn, &[item.clone()]).await?,
+            None => item.clone(),
+        };
+
+        // Only actual numbers are compared; strings are not coerced.
+        let key = match key {
+            Value::Number(n) if n.is_nan() => throw!("cannot compare NaN"),
+            Value::Number(n) => n,
+            key => throw!("expected a number, got {}", key.type_name()),
+        };
+
+        match &best {
+            Some((_, best_key)) if key.partial_cmp(best_key) != Some(wanted) => {}
+            _ => best = Some((item, key)),
+        }
+    }
+
+    match best {
+        Some((item, _)) => Ok(item),
+        None => throw!("at least one number required"),
+    }
+}
+
+/// Returns a list of `[index item]` pairs for each item in a list, counting
+/// from zero.
+async fn enumerate(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let list = match args.first().and_then(Value::as_list) {
+        Some(s) => s,
+        None => throw!("first argument must be a list"),
+    };
+
+    Ok(list.iter()
+        .enumerate()
+        .map(|(i, item)| Value::List(vec![Value::from(i as f64), item.clone()]))
+        .collect())
+}
+
+/// Returns a list of lists, where the nth list contains the nth item of each
+/// of the given lists.
+///
+/// The result is as long as the shortest list given, and any extra items in
+/// longer lists are ignored.
+async fn zip(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut lists = Vec::new();
+
+    for arg in args.iter() {
+        match arg.as_list() {
+            Some(list) => lists.push(list),
+            None => throw!("zip arguments must be lists, got {}", arg.type_name()),
+        }
+    }
+
+    let len = lists.iter().map(|list| list.len()).min().unwrap_or(0);
+
+    Ok((0..len)
+        .map(|i| Value::List(lists.iter().map(|list| list[i].clone()).collect()))
+        .collect())
+}
+
+/// Returns a copy of a list with its items in reverse order.
+async fn reverse(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut list = match args.first().and_then(Value::as_list) {
+        Some(s) => s.to_vec(),
+        None => throw!("first argument must be a list"),
+    };
+
+    list.reverse();
+
+    Ok(list.into())
+}
+
+/// Returns a sorted copy of a list.
+///
+/// By default numbers are sorted numerically and strings lexicographically. If
+/// a comparator block is given, it is invoked with two items and should return
+/// a negative number, zero, or a positive number if the first item is less
+/// than, equal to, or greater than the second. The sort is stable.
+async fn sort(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut list = match args.first().and_then(Value::as_list) {
+        Some(s) => s.to_vec(),
+        None => throw!("first argument must be a list"),
+    };
+
+    let comparator = args.get(1);
+    let mut buffer = list.clone();
+
+    // Bottom-up merge sort, since the comparator must be awaited and so cannot
+    // be used with the standard library sort.
+    let mut width = 1;
+
+    while width < list.len() {
+        for start in (0..list.len()).step_by(width * 2) {
+            let mid = (start + width).min(list.len());
+            let end = (start + width * 2).min(list.len());
+            let (mut i, mut j) = (start, mid);
+
+            for slot in &mut buffer[start..end] {
+                let take_left = j >= end || i < mid && compare(fiber, comparator, &list[i], &list[j]).await? != Ordering::Greater;
+
+                if take_left {
+                    *slot = list[i].clone();
+                    i += 1;
+                } else {
+                    *slot = list[j].clone();
+                    j += 1;
+                }
+            }
+        }
+
+        std::mem::swap(&mut list, &mut buffer);
+        width *= 2;
+    }
+
+    Ok(list.into())
+}
+
+async fn compare(fiber: &mut Fiber, comparator: Option<&Value>, a: &Value, b: &Value) -> Result<Ordering, Exception> {
+    if let Some(comparator) = comparator {
+        let result = fiber.invoke(comparator, &[a.clone(), b.clone()]).await?;
+
+        return match coerce::to_number(&result) {
+            Some(n) if n < 0.0 => Ok(Ordering::Less),
+            Some(n) if n > 0.0 => Ok(Ordering::Greater),
+            Some(_) => Ok(Ordering::Equal),
+            None => throw!("comparator must return a number, got {}", result.type_name()),
+        };
+    }
+
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => Ok(a.partial_cmp(b).unwrap_or(Ordering::Equal)),
+        (Value::String(a), Value::String(b)) => Ok(a.cmp(b)),
+        _ => throw!("cannot compare {} with {}", a.type_name(), b.type_name()),
+    }
+}
+
+/// Returns a list of all background jobs.
+async fn jobs_fn(fiber: &mut Fiber, _: Vec<Value>) -> Result<Value, Exception> {
+    Ok(jobs::list(fiber).into())
+}
+
+/// Wait for a background job or spawned process to complete and return its
+/// exit status. The job may be given as a job table, a process ID, or a job ID.
+/// If no job is given, waits for all jobs.
+async fn wait(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    match args.first() {
+        Some(value) => {
+            let job = jobs::find(fiber, value)?;
+            jobs::wait(&job).await
+        }
+        None => {
+            for job in jobs::list(fiber) {
+                jobs::wait(&job).await?;
+            }
+
+            Ok(Value::Nil)
+        }
+    }
+}
+
+/// Get the value of a key in a table. The key may be any hashable value.
+async fn table_get(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let table = match args.first().and_then(Value::as_table) {
+        Some(table) => table,
+        None => throw!("first argument must be a table"),
+    };
+
+    match args.get(1) {
+        Some(key) => table.get_value(key),
+        None => throw!("key required"),
+    }
+}
+
+/// Set the value of a key in a table, returning the old value. The key may be
+/// any hashable value.
+async fn table_set(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let table = match args.first().and_then(Value::as_table) {
+        Some(table) => table,
+        None => throw!("first argument must be a table"),
+    };
+
+    match args.get(1) {
+        Some(key) => table.set_value(key.clone(), args.get(2).cloned().unwrap_or_default()),
+        None => throw!("key required"),
+    }
+}
+
+/// Merge two or more tables into a new table, with keys in later tables
+/// overriding earlier ones.
+///
+/// If the `--deep` flag is given, nested tables present in both are merged
+/// recursively instead of being replaced. The given tables are not modified,
+/// but any nested table that is not merged is shared with the result rather
+/// than copied.
+async fn merge(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut deep = false;
+    let mut tables = Vec::new();
+
+    for arg in args {
+        if let Some(table) = arg.as_table() {
+            tables.push(table);
+        } else if arg == "--deep" {
+            deep = true;
+        } else {
+            throw!("cannot merge a {}, expected a table", arg.type_name());
+        }
+    }
+
+    if tables.len() < 2 {
+        throw!("at least two tables are required");
+    }
+
+    let merged = Table::new();
+
+    for table in tables {
+        merge_into(&merged, &table, deep)?;
+    }
+
+    Ok(merged.into())
+}
+
+fn merge_into(dest: &Table, src: &Table, deep: bool) -> Result<(), Exception> {
+    for key in src.all_keys() {
+        let value = src.get_value(&key)?;
+        let value = match (dest.get_value(&key)?.as_table(), value.as_table()) {
+            (Some(old), Some(new)) if deep => {
+                let merged = Table::new();
+                merge_into(&merged, &old, true)?;
+                merge_into(&merged, &new, true)?;
+                merged.into()
+            }
+            _ => value,
+        };
+
+        dest.set_value(key, value)?;
+    }
+
+    Ok(())
+}
+
 /// Function that always returns Nil.
 async fn nil(_: &mut Fiber, _: Vec<Value>) -> Result<Value, Exception> {
     Ok(Value::Nil)
 }
 
 /// Throw an exception.
+///
+/// With one argument, the argument is the exception message. With two, the
+/// first is a tag naming the type of the exception, such as `io-error`, and
+/// the second is the message.
 async fn throw(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
-    match args.first() {
-        Some(value) => Err(Exception::from(value.clone())),
-        None => Err(Exception::from(Value::Nil)),
+    match args.as_slice() {
+        [] => Err(Exception::from(Value::Nil)),
+        [message] => Err(Exception::from(message.clone())),
+        [tag, message] => Err(Exception::from(message.clone()).with_tag(coerce::to_string(tag))),
+        _ => throw!("too many arguments"),
     }
 }
 
 /// Handle exceptions.
+///
+/// The error block is invoked with the exception message, followed by its tag
+/// if it has one. If the `--type` option is given, only exceptions with that tag are
+/// caught, and any others are thrown again.
 async fn try_fn(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
-    let try_block = match args.first() {
+    let mut tag = None;
+    let mut positional = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--type" {
+            match args.next() {
+                Some(value) => tag = Some(coerce::to_string(&value)),
+                None => throw!("--type requires a tag"),
+            }
+        } else {
+            positional.push(arg);
+        }
+    }
+
+    let try_block = match positional.first() {
         Some(value) => value,
         None => throw!("block to invoke required"),
     };
 
-    let error_continuation = match args.get(1) {
+    let error_continuation = match positional.get(1) {
         Some(value) => value,
         None => throw!("error block required"),
     };
@@ -115,8 +891,15 @@ async fn try_fn(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception>
     match fiber.invoke(try_block, &[]).await {
         Ok(value) => Ok(value),
         Err(exception) => {
+            if tag.is_some() && exception.tag() != tag.as_ref() {
+                return Err(exception);
+            }
+
             // Invoke the catch block.
-            let result = fiber.invoke(error_continuation, &[exception.message().clone()]).await;
+            let mut catch_args = vec![exception.message().clone()];
+            catch_args.extend(exception.tag().cloned().map(Value::from));
+
+            let result = fiber.invoke(error_continuation, &catch_args).await;
 
             // If the exception is unrecoverable, re-throw it anyway.
             if exception.is_unrecoverable() {
@@ -128,6 +911,98 @@ async fn try_fn(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception>
     }
 }
 
+/// Defers a block to run when the block that called `defer` exits, whether it
+/// returns normally or throws an exception.
+async fn defer(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let block = match args.first() {
+        Some(block @ (Value::Block(_) | Value::ForeignFn(_))) => block.clone(),
+        _ => throw!("block to defer required"),
+    };
+
+    caller_scope(fiber, "defer")?.deferred.borrow_mut().push(block);
+
+    Ok(Value::Nil)
+}
+
+/// Get the scope of the block that called a builtin.
+fn caller_scope<'f>(fiber: &'f Fiber, name: &str) -> Result<&'f Scope, Exception> {
+    // The top of the stack is the scope of the builtin itself, so the scope of
+    // the caller is just below it.
+    match fiber.stack.iter().rev().nth(1) {
+        Some(scope) => Ok(scope),
+        None => throw!("{} must be called from within a block", name),
+    }
+}
+
+/// Get the arguments that the block calling a builtin was invoked with.
+fn caller_args(fiber: &Fiber, name: &str) -> Result<Vec<Value>, Exception> {
+    Ok(caller_scope(fiber, name)?.get("args").as_list().map(<[Value]>::to_vec).unwrap_or_default())
+}
+
+/// Returns the positional argument at an index that the calling block was
+/// invoked with, counting from zero.
+///
+/// If the block was not given that many arguments, the default given after the
+/// index is returned instead, or nil if there is none.
+async fn arg(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let index = match args.first().and_then(coerce::to_number) {
+        Some(index) if index >= 0.0 && index.fract() == 0.0 => index as usize,
+        _ => throw!("index must be a whole number that is not negative"),
+    };
+
+    let default = args.get(1).cloned().unwrap_or_default();
+
+    Ok(caller_args(fiber, "arg")?.get(index).cloned().unwrap_or(default))
+}
+
+/// Returns the number of arguments that the calling block was invoked with.
+async fn arg_count(fiber: &mut Fiber, _: Vec<Value>) -> Result<Value, Exception> {
+    Ok(Value::from(caller_args(fiber, "arg-count")?.len() as f64))
+}
+
+/// Returns the value of a named argument that the calling block was invoked
+/// with, such as `opt level` for `--level warn`.
+///
+/// Named arguments given in a call are found in the `opts` table of the block.
+/// Otherwise, the positional arguments are searched for the flag, followed by
+/// its value; a flag not followed by a value is true. If the argument was not
+/// given, the default given after the name is returned instead, or nil if
+/// there is none.
+async fn opt(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let name = match args.first() {
+        Some(name) => coerce::to_string(name),
+        None => throw!("option name required"),
+    };
+
+    let default = args.get(1).cloned().unwrap_or_default();
+    let name = name.as_bytes().strip_prefix(b"--").unwrap_or(name.as_bytes());
+    let scope = caller_scope(fiber, "opt")?;
+
+    if let Some(opts) = scope.get("opts").as_table() {
+        match opts.get(name) {
+            Value::Nil => {}
+            value => return Ok(value),
+        }
+    }
+
+    let mut flag = b"--".to_vec();
+    flag.extend_from_slice(name);
+
+    let given = caller_args(fiber, "opt")?;
+    let mut given = given.iter();
+
+    while let Some(arg) = given.next() {
+        if arg.as_string().is_some_and(|arg| arg.as_bytes() == flag) {
+            return Ok(match given.next() {
+                Some(value) if !value.as_string().is_some_and(|value| value.as_bytes().starts_with(b"--")) => value.clone(),
+                _ => Value::TRUE,
+            });
+        }
+    }
+
+    Ok(default)
+}
+
 async fn call(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     if let Some(function) = args.first() {
         let args = match args.get(1) {
@@ -141,6 +1016,66 @@ async fn call(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     }
 }
 
+/// Invokes blocks concurrently, each in a fiber of its own, and returns a list
+/// of their results in the same order as the blocks.
+///
+/// If any block throws an exception, the blocks still running are cancelled and
+/// the exception is thrown.
+async fn parallel(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    // The forked fibers write to the same streams as this one, so write out
+    // anything buffered so far first.
+    fiber.io.flush().await?;
+
+    let mut results = vec![Value::Nil; args.len()];
+    let mut futures = JoinSet::new();
+
+    for (i, block) in args.into_iter().enumerate() {
+        let mut fiber = fiber.fork();
+
+        futures.spawn_local(async move {
+            (i, fiber.invoke(&block, &[]).await)
+        });
+    }
+
+    // Dropping the set when returning early aborts the remaining blocks.
+    while let Some(result) = futures.join_next().await {
+        match result {
+            Ok((i, Ok(value))) => results[i] = value,
+            Ok((_, Err(exception))) => return Err(exception),
+            Err(e) => throw!("{}", e),
+        }
+    }
+
+    Ok(Value::List(results))
+}
+
+/// Invokes a block, throwing an exception tagged `timeout` if it does not
+/// finish within a number of seconds.
+///
+/// Once the time runs out the block is cancelled, which also kills any
+/// processes that it is waiting on.
+async fn with_timeout(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (seconds, block) = match args.as_slice() {
+        [seconds, block] => (seconds, block),
+        _ => throw!("timeout and block required"),
+    };
+
+    let seconds = match coerce::to_number(seconds) {
+        Some(seconds) if seconds >= 0.0 => seconds,
+        _ => throw!("timeout must be a number of seconds that is not negative, got {}", seconds),
+    };
+
+    let duration = match Duration::try_from_secs_f64(seconds) {
+        Ok(duration) => duration,
+        Err(e) => throw!("invalid timeout: {}", e),
+    };
+
+    match tokio::time::timeout(duration, fiber.invoke(block, &[])).await {
+        Ok(result) => result,
+        Err(_) => Err(Exception::from(format!("timed out after {} seconds", seconds)).with_tag("timeout")),
+    }
+}
+
 async fn include(_: &mut Fiber, _: Vec<Value>) -> Result<Value, Exception> {
     throw!("not implemented");
 }
@@ -17,6 +17,10 @@ pub struct Closure {
     /// anonymous, names are optionally derived from the first binding to the
     /// closure in source code.
     pub(crate) name: Option<String>,
+
+    /// Documentation for the closure, if it was defined as a subroutine with a
+    /// doc string.
+    pub(crate) doc: Option<String>,
 }
 
 impl Closure {
@@ -25,12 +29,18 @@ impl Closure {
         self.name.as_deref()
     }
 
+    /// Get the documentation of the closure.
+    pub fn doc(&self) -> Option<&str> {
+        self.doc.as_deref()
+    }
+
     /// Return a copy of this closure with the given name assigned.
     pub(crate) fn with_name(&self, name: String) -> Self {
         Self {
             block: self.block.clone(),
             scope: self.scope.clone(),
             name: Some(name),
+            doc: self.doc.clone(),
         }
     }
 }
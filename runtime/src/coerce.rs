@@ -0,0 +1,167 @@
+//! Rules for converting values from one type to another.
+//!
+//! Riptide is loosely typed, and functions that expect a value of a particular
+//! type should accept other types that have an obvious equivalent. Rather than
+//! each function inventing its own rules, all such conversions should go
+//! through the functions in this module so that they behave the same
+//! everywhere.
+
+use crate::{string::RipString, value::Value};
+
+/// Convert a value to a number.
+///
+/// - Numbers are returned as-is.
+/// - Booleans are converted to `1` or `0`.
+/// - Strings are parsed as a decimal number, ignoring surrounding whitespace.
+///
+/// All other values, and strings that are not a valid number, have no numeric
+/// equivalent and return `None`.
+pub fn to_number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => Some(*number),
+        Value::Boolean(b) => Some(if *b { 1.0 } else { 0.0 }),
+        Value::String(s) => s.as_utf8()?.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Convert a value to a string.
+///
+/// Strings are returned as-is. All other values are converted using their
+/// display representation, which is also how values are converted when passed
+/// as arguments to external commands.
+pub fn to_string(value: &Value) -> RipString {
+    match value {
+        Value::String(s) => s.clone(),
+        value => value.to_string().into(),
+    }
+}
+
+/// Convert a value to a boolean, according to whether it is "truthy".
+///
+/// The following values are considered false:
+///
+/// - `nil`
+/// - `false`
+/// - The empty string, `"0"`, and `"false"` (in any case).
+/// - The empty list.
+///
+/// All other values are considered true, including every number.
+pub fn to_bool(value: &Value) -> bool {
+    match value {
+        Value::Nil => false,
+        Value::Boolean(b) => *b,
+        Value::String(s) => !(s.as_bytes().is_empty() || s == "0" || s.to_lowercase() == "false"),
+        Value::List(items) => !items.is_empty(),
+        _ => true,
+    }
+}
+
+/// Convert a value to a list.
+///
+/// Lists are returned as-is, and `nil` becomes the empty list. Any other value
+/// becomes a list containing just that value.
+pub fn to_list(value: &Value) -> Vec<Value> {
+    match value {
+        Value::Nil => Vec::new(),
+        Value::List(items) => items.clone(),
+        value => vec![value.clone()],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::table;
+
+    fn values() -> Vec<(&'static str, Value)> {
+        vec![
+            ("nil", Value::Nil),
+            ("true", Value::TRUE),
+            ("false", Value::FALSE),
+            ("zero", Value::from(0.0)),
+            ("number", Value::from(2.5)),
+            ("nan", Value::from(f64::NAN)),
+            ("empty string", Value::from("")),
+            ("zero string", Value::from("0")),
+            ("false string", Value::from("FALSE")),
+            ("numeric string", Value::from(" 42 ")),
+            ("string", Value::from("hello")),
+            ("empty list", Value::List(Vec::new())),
+            ("list", Value::from(vec![Value::from("a"), Value::from(1.0)])),
+            ("table", Value::from(table!())),
+        ]
+    }
+
+    fn check<T: PartialEq + std::fmt::Debug>(f: impl Fn(&Value) -> T, expected: &[T]) {
+        for ((name, value), expected) in values().iter().zip(expected) {
+            assert_eq!(&f(value), expected, "coercing {}", name);
+        }
+    }
+
+    #[test]
+    fn to_number_matrix() {
+        check(|v| to_number(v).map(|n| n.to_string()), &[
+            None,
+            Some("1".into()),
+            Some("0".into()),
+            Some("0".into()),
+            Some("2.5".into()),
+            Some("NaN".into()),
+            None,
+            Some("0".into()),
+            None,
+            Some("42".into()),
+            None,
+            None,
+            None,
+            None,
+        ]);
+    }
+
+    #[test]
+    fn to_string_matrix() {
+        check(|v| to_string(v).to_string(), &[
+            "nil".into(),
+            "true".into(),
+            "false".into(),
+            "0".into(),
+            "2.5".into(),
+            "NaN".into(),
+            "".into(),
+            "0".into(),
+            "FALSE".into(),
+            " 42 ".into(),
+            "hello".into(),
+            "[]".into(),
+            "[a,1]".into(),
+        ]);
+
+        assert!(to_string(&Value::from(table!())).to_string().starts_with("<table@"));
+    }
+
+    #[test]
+    fn to_bool_matrix() {
+        check(to_bool, &[
+            false,
+            true,
+            false,
+            true,
+            true,
+            true,
+            false,
+            false,
+            false,
+            true,
+            true,
+            false,
+            true,
+            true,
+        ]);
+    }
+
+    #[test]
+    fn to_list_matrix() {
+        check(|v| to_list(v).len(), &[0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 2, 1]);
+    }
+}
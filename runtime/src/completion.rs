@@ -0,0 +1,40 @@
+//! Custom completions for the arguments of commands.
+//!
+//! Scripts register a block that completes the arguments of a command with the
+//! `complete` builtin, such as `complete git { ... }`. The runtime only stores
+//! them by command name in a hidden global table, and it is up to an
+//! interactive shell to call them while a command is being typed.
+
+use crate::{prelude::*, table};
+
+/// This is the name of the hidden global variable that completion functions
+/// are stored in.
+static COMPLETIONS_GLOBAL: &str = "__completions";
+
+/// Get the table of all registered completion functions, if any have been
+/// registered.
+pub(crate) fn table(fiber: &Fiber) -> Option<Table> {
+    fiber.globals().get(COMPLETIONS_GLOBAL).as_table()
+}
+
+/// Get the table of all registered completion functions, creating it if
+/// nothing has been registered yet.
+pub(crate) fn table_or_insert(fiber: &Fiber) -> Table {
+    match table(fiber) {
+        Some(completions) => completions,
+        None => {
+            let completions = table!();
+            fiber.globals().set(COMPLETIONS_GLOBAL, completions.clone());
+            completions
+        }
+    }
+}
+
+/// Get the function registered to complete the arguments of a command, if
+/// there is one.
+pub fn get(fiber: &Fiber, command: &str) -> Option<Value> {
+    match table(fiber)?.get(command) {
+        Value::Nil => None,
+        function => Some(function),
+    }
+}
@@ -1,11 +1,14 @@
 //! This module contains the core logic of the interpreter.
 
 use crate::{
+    alias,
     closure::Closure,
+    coerce,
     controlflow::{break_return, throw_cf, BreakAction, ControlFlow},
     exceptions::Exception,
     fiber::Fiber,
     foreign::ForeignFn,
+    io::process,
     scope::Scope,
     string::RipString,
     table,
@@ -16,7 +19,7 @@ use crate::{
 use gc::Gc;
 use riptide_syntax::{ast::*, parse, source::*};
 use std::ops::ControlFlow::Continue;
-use tokio::task::JoinSet;
+use tokio::{io::AsyncWriteExt, task::JoinSet};
 
 /// Compile the given source code as a closure.
 pub(crate) fn compile(
@@ -42,18 +45,83 @@ fn compile_block(fiber: &mut Fiber, block: Block) -> Closure {
         block,
         scope: fiber.current_scope().cloned(),
         name: None,
+        doc: None,
     }
 }
 
+/// A single evaluated argument to a function call.
+enum ArgValue {
+    /// A positional argument.
+    Positional(Value),
+
+    /// A named argument given using `--name value` syntax, or a flag given
+    /// using `--name` syntax without a value.
+    Named(RipString, Option<Value>),
+}
+
+impl ArgValue {
+    /// Flatten a list of arguments into positional arguments only. Named
+    /// arguments are expanded back into their original `--name value` form.
+    fn flatten(args: Vec<ArgValue>) -> Vec<Value> {
+        let mut values = Vec::with_capacity(args.len());
+
+        for arg in args {
+            match arg {
+                ArgValue::Positional(value) => values.push(value),
+                ArgValue::Named(name, None) => values.push(flag_name(&name)),
+                ArgValue::Named(name, Some(value)) => {
+                    values.push(flag_name(&name));
+                    values.push(value);
+                }
+            }
+        }
+
+        values
+    }
+}
+
+fn flag_name(name: &RipString) -> Value {
+    let mut flag = b"--".to_vec();
+    flag.extend_from_slice(name.as_bytes());
+    Value::from(RipString::from(flag))
+}
+
 /// Invoke the given value as a function with the given arguments.
 pub(crate) async fn invoke(
     fiber: &mut Fiber,
     value: &Value,
     args: Vec<Value>,
+) -> ControlFlow<Value> {
+    invoke_with_named_args(fiber, value, args.into_iter().map(ArgValue::Positional).collect()).await
+}
+
+/// Invoke the given value as a function with positional and named arguments.
+///
+/// Closures receive named arguments in an implicit `opts` table. Native
+/// functions do not understand named arguments, so they are passed through as
+/// positional arguments instead.
+async fn invoke_with_named_args(
+    fiber: &mut Fiber,
+    value: &Value,
+    args: Vec<ArgValue>,
 ) -> ControlFlow<Value> {
     match value {
-        Value::Block(closure) => invoke_closure(fiber, closure, args, table!(), table!(), false).await,
-        Value::ForeignFn(function) => invoke_native(fiber, function, args).await,
+        Value::Block(closure) => {
+            let opts = Table::default();
+            let mut positional = Vec::with_capacity(args.len());
+
+            for arg in args {
+                match arg {
+                    ArgValue::Positional(value) => positional.push(value),
+                    ArgValue::Named(name, value) => {
+                        opts.set(name, value.unwrap_or(Value::TRUE));
+                    }
+                }
+            }
+
+            invoke_closure(fiber, closure, positional, table! { "opts" => opts, }, table!(), false).await
+        }
+        Value::ForeignFn(function) => invoke_native(fiber, function, ArgValue::flatten(args)).await,
         value => throw_cf!("cannot invoke '{:?}' as a function", value),
     }
 }
@@ -72,18 +140,62 @@ pub(crate) async fn invoke_closure(
         bindings,
         cvars,
         parent: closure.scope.clone(),
+        deferred: Default::default(),
     };
 
     // Before attempting to bind args to named params, first define the implicit
     // `args` variable.
     scope.set("args", args.clone());
 
+    // Push the scope onto the stack.
+    let scope = Gc::new(scope);
+    fiber.stack.push(scope.clone());
+
+    // Pop the scope off of the stack before returning. We use a scope guard to
+    // do this to ensure that the stack is popped even if the current task
+    // panics or is cancelled.
+    let mut fiber = scopeguard::guard(fiber, |fiber| {
+        fiber.stack.pop();
+    });
+
+    let result = evaluate_closure_body(*fiber, closure, &scope, args, bubble_up_return).await;
+
+    // Deferred blocks are async, so they cannot be run by the scope guard, and
+    // are skipped if the task is cancelled. Most scopes defer nothing, so skip
+    // allocating the boxed future for them.
+    if scope.deferred.borrow().is_empty() {
+        return result;
+    }
+
+    run_deferred(*fiber, &scope, result).await
+}
+
+/// Bind arguments to the params of a closure, then evaluate its statements in
+/// the given scope.
+async fn evaluate_closure_body(
+    fiber: &mut Fiber,
+    closure: &Closure,
+    scope: &Scope,
+    args: Vec<Value>,
+    bubble_up_return: bool,
+) -> ControlFlow<Value> {
     let mut args = args.into_iter();
 
-    // Bind arguments to any named params.
+    // Bind arguments to any named params. Default values are only evaluated if
+    // an argument is missing, and are evaluated inside the new scope so that
+    // they may refer to earlier params.
     if let Some(named_params) = closure.block.named_params.as_ref() {
         for named_param in named_params.iter() {
-            scope.set(named_param.as_bytes(), args.next().unwrap_or_default());
+            let value = match (args.next(), named_param.default.as_ref()) {
+                (Some(value), _) => value,
+                (None, Some(default)) => match evaluate_expr(fiber, default.clone()).await {
+                    Continue(value) => value,
+                    ControlFlow::Break(action) => return unwind(fiber, action, bubble_up_return),
+                },
+                (None, None) => Value::Nil,
+            };
+
+            scope.set(named_param.name.as_bytes(), value);
         }
     }
 
@@ -92,43 +204,63 @@ pub(crate) async fn invoke_closure(
         scope.set(vararg_param.as_bytes(), args.collect::<Value>());
     }
 
-    // Push the scope onto the stack.
-    fiber.stack.push(Gc::new(scope));
-
-    // Pop the scope off of the stack before returning. We use a scope guard to
-    // do this to ensure that the stack is popped even if the current task
-    // panics or is cancelled.
-    let mut fiber = scopeguard::guard(fiber, |fiber| {
-        fiber.stack.pop();
-    });
-
     let mut last_return_value = Value::Nil;
 
     // Evaluate each statement in order.
     for statement in closure.block.statements.clone().into_iter() {
-        match evaluate_statement(*fiber, statement).await {
+        match evaluate_statement(fiber, statement).await {
             Continue(return_value) => last_return_value = return_value,
+            ControlFlow::Break(action) => return unwind(fiber, action, bubble_up_return),
+        }
+    }
 
-            // Stop block execution and return the given value.
-            ControlFlow::Break(BreakAction::Return(value)) => if bubble_up_return {
-                break_return!(value);
-            } else {
-                return Continue(value);
-            },
+    Continue(last_return_value)
+}
 
-            // Exception thrown; our scope guard from earlier will ensure that
-            // the stack is unwound.
-            ControlFlow::Break(BreakAction::Throw(mut exception)) => {
-                if exception.backtrace.is_empty() {
-                    exception.backtrace = fiber.backtrace().cloned().collect();
-                }
+/// Run any blocks deferred in a scope that is about to exit, most recently
+/// deferred first.
+///
+/// An exception thrown by a deferred block is only logged if the scope is
+/// already exiting because of an exception, so that the original exception is
+/// not lost. Otherwise, the exception is thrown once all deferred blocks have
+/// run.
+#[async_recursion::async_recursion(?Send)]
+async fn run_deferred(fiber: &mut Fiber, scope: &Scope, mut result: ControlFlow<Value>) -> ControlFlow<Value> {
+    let deferred = std::mem::take(&mut *scope.deferred.borrow_mut());
 
-                return ControlFlow::Break(BreakAction::Throw(exception));
+    for block in deferred.into_iter().rev() {
+        if let ControlFlow::Break(BreakAction::Throw(exception)) = invoke(fiber, &block, Vec::new()).await {
+            if matches!(result, ControlFlow::Break(BreakAction::Throw(_))) {
+                log::warn!("exception thrown by deferred block was ignored: {}", exception);
+            } else {
+                result = ControlFlow::Break(BreakAction::Throw(exception));
             }
         }
     }
 
-    Continue(last_return_value)
+    result
+}
+
+/// Handle a break in normal control flow inside of a closure body.
+fn unwind(fiber: &Fiber, action: BreakAction, bubble_up_return: bool) -> ControlFlow<Value> {
+    match action {
+        // Stop block execution and return the given value.
+        BreakAction::Return(value) => if bubble_up_return {
+            break_return!(value);
+        } else {
+            Continue(value)
+        },
+
+        // Exception thrown; the caller's scope guard will ensure that the
+        // stack is unwound.
+        BreakAction::Throw(mut exception) => {
+            if exception.backtrace.is_empty() {
+                exception.backtrace = fiber.backtrace().cloned().collect();
+            }
+
+            ControlFlow::Break(BreakAction::Throw(exception))
+        }
+    }
 }
 
 /// Invoke a native function.
@@ -143,6 +275,7 @@ async fn invoke_native(
         bindings: Default::default(),
         cvars: Default::default(),
         parent: None,
+        deferred: Default::default(),
     }));
 
     // Pop the scope off of the stack before returning. We use a scope guard to
@@ -152,7 +285,9 @@ async fn invoke_native(
         fiber.stack.pop();
     });
 
-    result_to_control_flow(function.call(*fiber, args).await.map_err(|mut e| {
+    // Native functions may come from third parties, so a panic in one is
+    // turned into an exception rather than tearing down the whole runtime.
+    result_to_control_flow(function.call_catch_unwind(*fiber, args).await.map_err(|mut e| {
         if e.backtrace.is_empty() {
             e.backtrace = fiber.backtrace().cloned().collect();
         }
@@ -167,17 +302,19 @@ async fn evaluate_statement(fiber: &mut Fiber, statement: Statement) -> ControlF
             evaluate_import_statement(fiber, statement).await?;
             Continue(Default::default())
         }
-        Statement::Return(None) => break_return!(),
-        Statement::Return(Some(expr)) => {
+        Statement::Return(ReturnStatement { value: None, .. }) => break_return!(),
+        Statement::Return(ReturnStatement { value: Some(expr), .. }) => {
             let value = evaluate_expr(fiber, expr).await?;
             break_return!(value)
         }
         Statement::Pipeline(pipeline) => evaluate_pipeline(fiber, pipeline).await,
-        Statement::Assignment(AssignmentStatement { target, value }) => {
+        Statement::Condition(condition) => evaluate_condition(fiber, condition).await,
+        Statement::Background(Background(pipeline)) => result_to_control_flow(crate::jobs::spawn(fiber, pipeline).await),
+        Statement::Assignment(AssignmentStatement { target, value, .. }) => {
             match target {
                 AssignmentTarget::MemberAccess(member_access) => {
-                    if let Some(table) = evaluate_expr(fiber, *member_access.0).await?.as_table() {
-                        table.set(member_access.1, evaluate_expr(fiber, value).await?);
+                    if let Some(table) = evaluate_expr(fiber, *member_access.target).await?.as_table() {
+                        table.set(member_access.name, evaluate_expr(fiber, value).await?);
                     } else {
                         throw_cf!("cannot assign to a non-table")
                     }
@@ -189,11 +326,11 @@ async fn evaluate_statement(fiber: &mut Fiber, statement: Statement) -> ControlF
                     // doesn't already have one.
                     if let Value::Block(closure) = &value {
                         if closure.name().is_none() {
-                            value = Value::Block(closure.with_name(variable.clone()));
+                            value = Value::Block(closure.with_name(variable.name.clone()));
                         }
                     }
 
-                    fiber.set(variable, value);
+                    fiber.set(variable.name, value);
                 }
             }
 
@@ -229,41 +366,113 @@ async fn evaluate_import_statement(
     Continue(())
 }
 
-async fn evaluate_pipeline(fiber: &mut Fiber, pipeline: Pipeline) -> ControlFlow<Value> {
-    match pipeline.0.len() {
+/// Run the pipelines of a chain joined by `&&` and `||`, evaluating to the
+/// result of the last pipeline that was run.
+async fn evaluate_condition(fiber: &mut Fiber, condition: Condition) -> ControlFlow<Value> {
+    let mut rest = condition.rest.into_iter().peekable();
+    let (mut value, mut success) = evaluate_condition_operand(fiber, condition.first, true).await?;
+
+    while let Some((operator, pipeline)) = rest.next() {
+        if success == (operator == ConditionOperator::And) {
+            let handled = rest.peek().is_some();
+            (value, success) = evaluate_condition_operand(fiber, pipeline, handled).await?;
+        }
+    }
+
+    Continue(value)
+}
+
+/// Run a pipeline in a chain joined by `&&` and `||`, and check whether it
+/// succeeded.
+///
+/// A pipeline that results in nil succeeds unless it ran an external command
+/// that exited with a nonzero status, and any other result succeeds if it is
+/// truthy. If the failure of the pipeline is handled by an operator after it,
+/// strict mode does not apply to the commands it runs.
+async fn evaluate_condition_operand(fiber: &mut Fiber, pipeline: Pipeline, handled: bool) -> ControlFlow<(Value, bool)> {
+    // Clear the status first, so that we can tell whether this pipeline ran any
+    // commands.
+    let previous_status = fiber.globals().get(process::LAST_STATUS);
+    fiber.globals().set(process::LAST_STATUS, Value::Nil);
+
+    let in_condition = fiber.in_condition;
+    let mut fiber = scopeguard::guard(fiber, |fiber| {
+        fiber.in_condition = in_condition;
+    });
+
+    fiber.in_condition = in_condition || handled;
+
+    let result = evaluate_pipeline(*fiber, pipeline).await;
+    let status = fiber.globals().get(process::LAST_STATUS);
+
+    if status.is_nil() {
+        fiber.globals().set(process::LAST_STATUS, previous_status);
+    }
+
+    let value = result?;
+
+    let success = match &value {
+        Value::Nil => status.as_number().map_or(true, |status| status == 0.0),
+        value => value.is_truthy(),
+    };
+
+    Continue((value, success))
+}
+
+pub(crate) async fn evaluate_pipeline(fiber: &mut Fiber, pipeline: Pipeline) -> ControlFlow<Value> {
+    match pipeline.calls.len() {
         // If there's only one call in the pipeline, we don't need to fork and
         // can just execute the function by itself.
-        1 => evaluate_call(fiber, pipeline.0.into_iter().next().unwrap()).await,
+        1 => evaluate_call(fiber, pipeline.calls.into_iter().next().unwrap()).await,
 
         // Fork the current fiber once for each step in the pipeline, wire up
         // pipes between them for their I/O context, and then execute each call
         // in the pipeline in their respective fibers concurrently.
+        //
+        // The exit status of each step is collected in order into the
+        // `$PIPESTATUS` global once all of them have finished.
         count => {
             let mut values = Vec::with_capacity(count);
+            let mut statuses = vec![Value::Nil; count];
             let mut futures = JoinSet::new();
 
+            // The forked fibers write to the same streams as this one, so write
+            // out anything buffered so far first.
+            if let Err(e) = fiber.io.flush().await {
+                return ControlFlow::Break(BreakAction::Throw(e.into()));
+            }
+
             let mut ios = match fiber.io.try_clone().and_then(|io| io.split_n(count)) {
                 Ok(io) => io.into_iter(),
                 Err(e) => return ControlFlow::Break(BreakAction::Throw(e.into())),
             };
 
-            for call in pipeline.0 {
+            for (index, call) in pipeline.calls.into_iter().enumerate() {
                 let mut fiber = fiber.fork();
                 fiber.io = ios.next().unwrap();
 
                 futures.spawn_local(async move {
-                    evaluate_call(&mut fiber, call).await
+                    let result = evaluate_call(&mut fiber, call).await;
+                    (index, fiber.status.clone(), result)
                 });
             }
 
             while let Some(result) = futures.join_next().await {
                 match result {
-                    Ok(Continue(value)) => values.push(value),
-                    Ok(ControlFlow::Break(action)) => return ControlFlow::Break(action),
+                    Ok((index, status, Continue(value))) => {
+                        statuses[index] = status;
+                        values.push(value);
+                    }
+                    Ok((_, _, ControlFlow::Break(action))) => return ControlFlow::Break(action),
                     Err(e) => throw_cf!("{}", e),
                 }
             }
 
+            // A pipeline nested in one of the steps of another counts as a
+            // command run by that step, with the status of its last step.
+            fiber.status = statuses[count - 1].clone();
+            fiber.globals().set(process::PIPE_STATUS, Value::List(statuses));
+
             Continue(Value::List(values))
         }
     }
@@ -272,38 +481,186 @@ async fn evaluate_pipeline(fiber: &mut Fiber, pipeline: Pipeline) -> ControlFlow
 #[async_recursion::async_recursion(?Send)]
 async fn evaluate_call(fiber: &mut Fiber, call: Call) -> ControlFlow<Value> {
     match call {
-        Call::Named { function, args } => {
-            let name = function;
-            let function = fiber.get(&name);
+        Call::Named { function, args, .. } => evaluate_named_call(fiber, function, args, &mut Vec::new()).await,
+        // A match or try expression on its own runs its blocks, rather than
+        // calling the value they evaluate to.
+        Call::Unnamed { function, args, .. } if args.is_empty() && matches!(*function, Expr::Match(_) | Expr::Try(_)) => {
+            evaluate_expr(fiber, *function).await
+        }
+        Call::Unnamed { function, args, .. } => {
+            let function = evaluate_expr(fiber, *function).await?;
             let arg_values = evaluate_call_args(fiber, args).await?;
 
-            if !function.is_nil() {
-                invoke(fiber, &function, arg_values).await
-            } else {
-                result_to_control_flow(crate::io::process::command(fiber, &name, &arg_values).await)
+            trace_call(fiber, function.to_string().as_bytes(), &arg_values).await;
+
+            invoke_with_named_args(fiber, &function, arg_values).await
+        }
+    }
+}
+
+/// Call a function by name, falling back to an alias and then to an external
+/// command if no function has the name.
+///
+/// The names of aliases already expanded are tracked, so that an alias that
+/// refers to itself, such as `ls` for `ls --color`, runs the external command
+/// of the same name rather than expanding forever.
+#[async_recursion::async_recursion(?Send)]
+async fn evaluate_named_call(
+    fiber: &mut Fiber,
+    name: String,
+    args: Vec<CallArg>,
+    expanded: &mut Vec<String>,
+) -> ControlFlow<Value> {
+    let resolution = result_to_control_flow(resolve_name(fiber, &name, expanded))?;
+
+    if let Resolution::Alias(alias_name, mut alias_args) = resolution {
+        expanded.push(name);
+        alias_args.extend(args);
+
+        return evaluate_named_call(fiber, alias_name, alias_args, expanded).await;
+    }
+
+    let arg_values = evaluate_call_args(fiber, args).await?;
+
+    trace_call(fiber, name.as_bytes(), &arg_values).await;
+
+    if let Resolution::Value(function) = resolution {
+        invoke_with_named_args(fiber, &function, arg_values).await
+    } else {
+        let arg_values = ArgValue::flatten(arg_values);
+
+        // Give the user's handler a chance to deal with commands that do not
+        // exist before running into the usual error.
+        let handler = fiber.globals().get(process::COMMAND_NOT_FOUND);
+
+        if !handler.is_nil() && process::find_executable(fiber, &name).is_none() {
+            let mut handler_args = Vec::with_capacity(arg_values.len() + 1);
+            handler_args.push(Value::from(name.clone()));
+            handler_args.extend(arg_values.iter().cloned());
+
+            let value = invoke(fiber, &handler, handler_args).await?;
+
+            if !value.is_nil() {
+                return Continue(value);
             }
         }
-        Call::Unnamed { function, args } => {
-            let function = evaluate_expr(fiber, *function).await?;
-            let arg_values = evaluate_call_args(fiber, args).await?;
 
-            invoke(fiber, &function, arg_values).await
+        result_to_control_flow(process::command(fiber, &name, &arg_values).await)
+    }
+}
+
+/// What a command name refers to when it is called by name.
+pub(crate) enum Resolution {
+    /// A variable in scope, which is invoked as a function.
+    Value(Value),
+
+    /// An alias, with the name and arguments of the command it expands to.
+    Alias(String, Vec<CallArg>),
+
+    /// Anything else is run as an external command.
+    External,
+}
+
+/// Resolve a command name in the same order that a named call does: first a
+/// variable in scope, then an alias, then an external command.
+///
+/// Aliases with a name in `expanded` are skipped.
+pub(crate) fn resolve_name(fiber: &Fiber, name: &str, expanded: &[String]) -> Result<Resolution, Exception> {
+    let value = fiber.get(name);
+
+    if !value.is_nil() {
+        return Ok(Resolution::Value(value));
+    }
+
+    if !expanded.iter().any(|expanded| expanded == name) {
+        if let Some((alias_name, alias_args)) = alias::get(fiber, name)? {
+            return Ok(Resolution::Alias(alias_name, alias_args));
+        }
+    }
+
+    Ok(Resolution::External)
+}
+
+/// Write a call and its evaluated arguments to standard error if tracing is
+/// enabled, in the same form as a command line.
+async fn trace_call(fiber: &mut Fiber, name: &[u8], args: &[ArgValue]) {
+    if !fiber.trace() {
+        return;
+    }
+
+    let mut line = b"+ ".to_vec();
+    line.extend_from_slice(name);
+
+    for arg in args {
+        let (flag, value) = match arg {
+            ArgValue::Positional(value) => (None, Some(value)),
+            ArgValue::Named(name, value) => (Some(name), value.as_ref()),
+        };
+
+        if let Some(flag) = flag {
+            line.extend_from_slice(b" --");
+            line.extend_from_slice(flag.as_bytes());
+        }
+
+        if let Some(value) = value {
+            line.push(b' ');
+            line.extend_from_slice(coerce::to_string(value).as_bytes());
         }
     }
+
+    line.push(b'\n');
+
+    let stderr = fiber.stderr();
+
+    let result = match stderr.write_all(&line).await {
+        Ok(()) => stderr.flush().await,
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = result {
+        log::warn!("failed to write trace: {}", e);
+    }
 }
 
-async fn evaluate_call_args(fiber: &mut Fiber, args: Vec<CallArg>) -> ControlFlow<Vec<Value>> {
+async fn evaluate_call_args(fiber: &mut Fiber, args: Vec<CallArg>) -> ControlFlow<Vec<ArgValue>> {
     let mut arg_values = Vec::with_capacity(args.len());
 
     for arg in args {
         match arg {
-            CallArg::Expr(expr) => arg_values.push(evaluate_expr(fiber, expr).await?),
+            CallArg::Expr(expr) => arg_values.push(ArgValue::Positional(evaluate_expr(fiber, expr).await?)),
+            CallArg::Named(name, expr) => {
+                // A named argument without a value is a flag.
+                let value = match expr {
+                    Some(expr) => Some(evaluate_expr(fiber, expr).await?),
+                    None => None,
+                };
+
+                arg_values.push(ArgValue::Named(name.into(), value));
+            }
             CallArg::Splat(expr) => {
                 let splat_items = evaluate_expr(fiber, expr).await?;
 
                 if let Some(items) = splat_items.as_list() {
                     for item in items {
-                        arg_values.push(item.clone());
+                        arg_values.push(ArgValue::Positional(item.clone()));
+                    }
+                } else if let Some(table) = splat_items.as_table() {
+                    // Expanding a table passes its entries as named args,
+                    // which allows `opts` to be forwarded to another function.
+                    for key in table.all_keys() {
+                        let key = match key.as_string() {
+                            Some(key) => key.clone(),
+                            None => throw_cf!("cannot expand a {} key as a named argument", key.type_name()),
+                        };
+
+                        // Flags are stored in `opts` as true, so pass them on
+                        // as flags again.
+                        let value = match table.get(&key) {
+                            Value::Boolean(true) => None,
+                            value => Some(value),
+                        };
+
+                        arg_values.push(ArgValue::Named(key, value));
                     }
                 } else if !splat_items.is_nil() {
                     throw_cf!(
@@ -321,18 +678,23 @@ async fn evaluate_call_args(fiber: &mut Fiber, args: Vec<CallArg>) -> ControlFlo
 #[async_recursion::async_recursion(?Send)]
 async fn evaluate_expr(fiber: &mut Fiber, expr: Expr) -> ControlFlow<Value> {
     match expr {
-        Expr::Number(number) => Continue(Value::Number(number)),
-        Expr::String(string) => Continue(Value::from(string)),
-        Expr::Regex(RegexLiteral(src)) => Continue(Value::Regex(src)),
+        Expr::Number(number) => Continue(Value::Number(number.value)),
+        Expr::String(string) => Continue(Value::from(RipString::from(&*raw_bytes(&string.value)))),
+        Expr::Regex(regex) => Continue(Value::Regex(regex.regex)),
+        Expr::Tilde(tilde) => Continue(expand_tilde(&tilde.text)),
         Expr::CvarReference(cvar) => evaluate_cvar(fiber, cvar).await,
         Expr::CvarScope(cvar_scope) => evaluate_cvar_scope(fiber, cvar_scope).await,
+        Expr::Match(match_expr) => evaluate_match(fiber, match_expr).await,
+        Expr::Try(try_expr) => evaluate_try(fiber, try_expr).await,
         Expr::Substitution(substitution) => evaluate_substitution(fiber, substitution).await,
         Expr::Table(literal) => evaluate_table_literal(fiber, literal).await,
         Expr::List(list) => evaluate_list_literal(fiber, list).await,
         Expr::InterpolatedString(string) => evaluate_interpolated_string(fiber, string).await,
-        Expr::MemberAccess(MemberAccess(lhs, rhs)) => {
-            evaluate_member_access(fiber, *lhs, rhs).await
+        Expr::MemberAccess(MemberAccess { target, name, .. }) => {
+            evaluate_member_access(fiber, *target, name).await
         }
+        Expr::Subscript(subscript) => evaluate_subscript(fiber, subscript).await,
+        Expr::Coalesce(coalesce) => evaluate_coalesce(fiber, coalesce).await,
         Expr::Block(block) => evaluate_block(fiber, block),
         Expr::Subroutine(subroutine) => evaluate_subroutine(fiber, subroutine),
         Expr::Pipeline(pipeline) => evaluate_pipeline(fiber, pipeline).await,
@@ -345,7 +707,8 @@ fn evaluate_block(fiber: &mut Fiber, block: Block) -> ControlFlow<Value> {
 
 fn evaluate_subroutine(fiber: &mut Fiber, subroutine: Subroutine) -> ControlFlow<Value> {
     let variable_name = RipString::from(subroutine.name.as_str());
-    let closure = compile_block(fiber, subroutine.block).with_name(subroutine.name);
+    let mut closure = compile_block(fiber, subroutine.block).with_name(subroutine.name);
+    closure.doc = subroutine.doc;
     let value = Value::Block(closure);
 
     fiber.set(variable_name, value.clone());
@@ -357,18 +720,118 @@ async fn evaluate_member_access(fiber: &mut Fiber, lhs: Expr, rhs: String) -> Co
     Continue(evaluate_expr(fiber, lhs).await?.get(rhs))
 }
 
+/// Index a list by position or a table by key.
+///
+/// List positions start at zero, and negative positions count backwards from
+/// the end of the list. A position outside of the list evaluates to nil, like
+/// a key that is not in a table.
+async fn evaluate_subscript(fiber: &mut Fiber, subscript: Subscript) -> ControlFlow<Value> {
+    let target = evaluate_expr(fiber, *subscript.target).await?;
+    let index = evaluate_expr(fiber, *subscript.index).await?;
+
+    match target {
+        Value::List(items) => {
+            let position = match coerce::to_number(&index) {
+                Some(position) if position.fract() == 0.0 => position,
+                _ => throw_cf!("list index must be a whole number, not '{}'", index),
+            };
+
+            let position = if position < 0.0 {
+                items.len() as f64 + position
+            } else {
+                position
+            };
+
+            if position >= 0.0 {
+                if let Some(item) = items.get(position as usize) {
+                    return Continue(item.clone());
+                }
+            }
+
+            Continue(Value::Nil)
+        }
+        Value::Table(table) => result_to_control_flow(table.get_value(&index)),
+        Value::Nil => Continue(Value::Nil),
+        target => throw_cf!("cannot index a {} value", target.type_name()),
+    }
+}
+
+/// Evaluate the left-hand side, and only if it is nil, the right-hand side.
+async fn evaluate_coalesce(fiber: &mut Fiber, coalesce: Coalesce) -> ControlFlow<Value> {
+    match evaluate_expr(fiber, *coalesce.lhs).await? {
+        Value::Nil => evaluate_expr(fiber, *coalesce.rhs).await,
+        value => Continue(value),
+    }
+}
+
 async fn evaluate_cvar(fiber: &mut Fiber, cvar: CvarReference) -> ControlFlow<Value> {
-    Continue(fiber.get_cvar(cvar.0))
+    Continue(fiber.get_cvar(cvar.name))
 }
 
 async fn evaluate_cvar_scope(fiber: &mut Fiber, cvar_scope: CvarScope) -> ControlFlow<Value> {
     let closure = compile_block(fiber, cvar_scope.scope);
 
     let cvars = table! {
-        cvar_scope.name.0 => evaluate_expr(fiber, *cvar_scope.value).await?,
+        cvar_scope.name.name => evaluate_expr(fiber, *cvar_scope.value).await?,
     };
 
-    invoke_closure(fiber, &closure, vec![], cvars, table!(), false).await
+    invoke_closure(fiber, &closure, vec![], table!(), cvars, false).await
+}
+
+/// Run the block of the first arm whose pattern matches the subject, or
+/// evaluate to nil if no arm matches.
+async fn evaluate_match(fiber: &mut Fiber, match_expr: Match) -> ControlFlow<Value> {
+    let subject = evaluate_expr(fiber, *match_expr.subject).await?;
+
+    for arm in match_expr.arms {
+        let matched = match arm.pattern {
+            MatchPattern::Wildcard => true,
+            MatchPattern::Value(pattern) => evaluate_expr(fiber, *pattern).await? == subject,
+        };
+
+        if matched {
+            let closure = compile_block(fiber, arm.body);
+
+            return invoke_closure(fiber, &closure, vec![], table!(), table!(), false).await;
+        }
+    }
+
+    Continue(Value::Nil)
+}
+
+/// Evaluate a try expression.
+///
+/// If the body throws an exception, the message is bound to the variable of the
+/// catch clause and the result of the catch block becomes the value of the
+/// expression. The finally block always runs last, and an exception it throws
+/// replaces the outcome of the rest of the expression.
+async fn evaluate_try(fiber: &mut Fiber, try_expr: Try) -> ControlFlow<Value> {
+    let closure = compile_block(fiber, *try_expr.body);
+    let mut result = invoke_closure(fiber, &closure, vec![], table!(), table!(), false).await;
+
+    if let (ControlFlow::Break(BreakAction::Throw(exception)), Some(catch)) = (&result, try_expr.catch) {
+        let bindings = table!();
+
+        if let Some(name) = catch.name {
+            bindings.set(name, exception.message().clone());
+        }
+
+        let unrecoverable = exception.is_unrecoverable();
+        let closure = compile_block(fiber, catch.body);
+        let catch_result = invoke_closure(fiber, &closure, vec![], bindings, table!(), false).await;
+
+        // Unrecoverable exceptions are thrown again, even once caught.
+        if !unrecoverable {
+            result = catch_result;
+        }
+    }
+
+    if let Some(finally) = try_expr.finally {
+        let closure = compile_block(fiber, *finally);
+        invoke_closure(fiber, &closure, vec![], table!(), table!(), false).await?;
+    }
+
+    result
 }
 
 async fn evaluate_substitution(
@@ -376,7 +839,7 @@ async fn evaluate_substitution(
     substitution: Substitution,
 ) -> ControlFlow<Value> {
     match substitution {
-        Substitution::Variable(name) => Continue(fiber.get(name)),
+        Substitution::Variable(variable) => Continue(fiber.get(variable.name)),
         Substitution::Pipeline(pipeline) => evaluate_pipeline(fiber, pipeline).await,
         _ => unimplemented!(),
     }
@@ -385,7 +848,7 @@ async fn evaluate_substitution(
 async fn evaluate_table_literal(fiber: &mut Fiber, literal: TableLiteral) -> ControlFlow<Value> {
     let table = Table::default();
 
-    for entry in literal.0 {
+    for entry in literal.entries {
         let key = evaluate_expr(fiber, entry.key).await?;
         let value = evaluate_expr(fiber, entry.value).await?;
 
@@ -398,7 +861,7 @@ async fn evaluate_table_literal(fiber: &mut Fiber, literal: TableLiteral) -> Con
 async fn evaluate_list_literal(fiber: &mut Fiber, list: ListLiteral) -> ControlFlow<Value> {
     let mut values = Vec::new();
 
-    for expr in list.0 {
+    for expr in list.items {
         values.push(evaluate_expr(fiber, expr).await?);
     }
 
@@ -409,21 +872,65 @@ async fn evaluate_interpolated_string(
     fiber: &mut Fiber,
     string: InterpolatedString,
 ) -> ControlFlow<Value> {
-    let mut rendered = String::new();
-
-    for part in string.0.into_iter() {
-        rendered.push_str(
-            match part {
-                InterpolatedStringPart::String(part) => part,
-                InterpolatedStringPart::Substitution(sub) => {
-                    evaluate_substitution(fiber, sub).await?.to_string()
-                }
+    // Build the string from bytes, since both literal parts and substituted
+    // values may not be valid UTF-8.
+    let mut rendered = Vec::new();
+
+    for part in string.parts.into_iter() {
+        match part {
+            InterpolatedStringPart::String(part) => rendered.extend_from_slice(&raw_bytes(&part)),
+            InterpolatedStringPart::Substitution(sub) => {
+                let value = evaluate_interpolated_substitution(fiber, sub).await?;
+                rendered.extend_from_slice(coerce::to_string(&value).as_bytes());
             }
-            .as_str(),
-        );
+        }
+    }
+
+    Continue(Value::from(RipString::from(rendered)))
+}
+
+/// Evaluate a substitution inside an interpolated string.
+///
+/// Variable substitutions such as `$HOME` or `${HOME}` that do not name a
+/// variable in scope fall back to the environment variable of the same name in
+/// the global `env` table. Variables in scope always take precedence over
+/// environment variables.
+async fn evaluate_interpolated_substitution(
+    fiber: &mut Fiber,
+    substitution: Substitution,
+) -> ControlFlow<Value> {
+    let name = match substitution {
+        Substitution::Variable(VariableReference { name, .. }) => name,
+        Substitution::Format(FormatSubstitution { name, flags: None, .. }) => name,
+        substitution => return evaluate_substitution(fiber, substitution).await,
+    };
+
+    match fiber.get(&name) {
+        Value::Nil => Continue(fiber.globals().get("env").get(&name)),
+        value => Continue(value),
     }
+}
+
+/// Expand the leading `~` or `~user` of an unquoted string to the home
+/// directory of the current user or the named user.
+///
+/// If the user does not exist, the string is returned unchanged.
+fn expand_tilde(text: &str) -> Value {
+    let (prefix, rest) = text.split_at(text.find('/').unwrap_or(text.len()));
+
+    let home = match &prefix[1..] {
+        "" => dirs::home_dir(),
+        user => nix::unistd::User::from_name(user).ok().flatten().map(|user| user.dir),
+    };
 
-    Continue(Value::from(rendered))
+    match home {
+        Some(home) => {
+            let mut bytes = Vec::from(RipString::from(home.into_os_string()));
+            bytes.extend_from_slice(&raw_bytes(rest));
+            Value::from(RipString::from(bytes))
+        }
+        None => Value::from(RipString::from(&*raw_bytes(text))),
+    }
 }
 
 fn result_to_control_flow<T>(result: Result<T, Exception>) -> ControlFlow<T> {
@@ -1,8 +1,9 @@
 use super::{
     scope::Scope,
+    string::RipString,
     value::Value,
 };
-use std::{fmt, io};
+use std::{error::Error, fmt, io};
 
 /// An exception thrown at runtime.
 ///
@@ -13,6 +14,10 @@ pub struct Exception {
     /// The exception message.
     pub(crate) message: Value,
 
+    /// An optional tag naming the type of the exception, so that handlers can
+    /// pick which exceptions to catch.
+    pub(crate) tag: Option<RipString>,
+
     /// The cause of this exception, if any.
     pub(crate) cause: Option<Box<Exception>>,
 
@@ -31,6 +36,7 @@ impl Exception {
     pub fn new<M: Into<Value>>(message: M) -> Self {
         Self {
             message: message.into(),
+            tag: None,
             cause: None,
             backtrace: vec![],
             unrecoverable: false,
@@ -44,6 +50,7 @@ impl Exception {
     pub fn with_cause<M: Into<Value>>(message: M, cause: Exception) -> Self {
         Self {
             message: message.into(),
+            tag: None,
             cause: Some(Box::new(cause)),
             backtrace: vec![],
             unrecoverable: false,
@@ -54,18 +61,31 @@ impl Exception {
     pub(crate) fn unrecoverable(message: impl Into<Value>) -> Self {
         Self {
             message: message.into(),
+            tag: None,
             cause: None,
             backtrace: vec![],
             unrecoverable: true,
         }
     }
 
+    /// Set the type tag of the exception.
+    pub fn with_tag(mut self, tag: impl Into<RipString>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
     /// Get the exception message.
     #[inline]
     pub fn message(&self) -> &Value {
         &self.message
     }
 
+    /// Get the type tag of the exception, if present.
+    #[inline]
+    pub fn tag(&self) -> Option<&RipString> {
+        self.tag.as_ref()
+    }
+
     /// Get the cause of the exception, if present.
     #[inline]
     pub fn cause(&self) -> Option<&Exception> {
@@ -108,6 +128,12 @@ impl From<io::Error> for Exception {
     }
 }
 
+impl Error for Exception {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause.as_deref().map(|cause| cause as _)
+    }
+}
+
 impl fmt::Debug for Exception {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         fmt::Display::fmt(self, f)
@@ -1,7 +1,9 @@
 use crate::{
+    alias,
     controlflow::Resolve,
     eval,
     exceptions::Exception,
+    foreign::ForeignFn,
     io::{IoContext, Input, Output},
     modules::{ModuleIndex, NativeModule},
     scope::Scope,
@@ -9,6 +11,7 @@ use crate::{
     syntax::source::SourceFile,
     table,
     table::Table,
+    throw,
     value::Value,
 };
 use gc::Gc;
@@ -18,6 +21,13 @@ use std::{rc::Rc, sync::atomic::{AtomicUsize, Ordering}};
 /// stored in.
 static EXIT_CODE_GLOBAL: &str = "__exit_code";
 
+/// This is the name of the hidden global variable that holds whether command
+/// tracing is enabled.
+static TRACE_GLOBAL: &str = "__trace";
+
+/// This is the name of the context variable that enables strict mode.
+pub(crate) static STRICT_CVAR: &str = "strict";
+
 fn next_pid() -> usize {
     static NEXT_PID: AtomicUsize = AtomicUsize::new(1);
 
@@ -34,6 +44,9 @@ pub struct Fiber {
 
     module_index: Rc<ModuleIndex>,
 
+    /// Parsed commands of defined aliases, shared with all forks.
+    pub(crate) alias_commands: Rc<alias::Commands>,
+
     /// Table where global values are stored that are not on the stack.
     globals: Table,
 
@@ -41,11 +54,29 @@ pub struct Fiber {
     /// context variables that have not been set by any scope.
     cvar_globals: Table,
 
+    /// Copies of the globals and context variable defaults to restore when the
+    /// fiber is reset.
+    snapshot: (Table, Table),
+
     /// Call stack of functions being executed by this fiber.
     pub(crate) stack: Vec<Gc<Scope>>,
 
     /// Standard I/O streams for this fiber.
     pub(crate) io: IoContext,
+
+    /// Whether this fiber is running a background job. Processes started by a
+    /// background fiber are never put in the foreground.
+    pub(crate) background: bool,
+
+    /// Whether this fiber is running a pipeline whose failure is handled by a
+    /// following `&&` or `||` operator. Strict mode does not apply to commands
+    /// run by such a pipeline.
+    pub(crate) in_condition: bool,
+
+    /// Exit status of the last external command run by this fiber, or nil if
+    /// it has not run any. Unlike `$?`, this is not shared with other fibers,
+    /// so it tells the stages of a pipeline apart.
+    pub(crate) status: Value,
 }
 
 impl Fiber {
@@ -54,10 +85,15 @@ impl Fiber {
         let fiber = Self {
             pid: next_pid(),
             module_index: Rc::new(ModuleIndex::default()),
+            alias_commands: Default::default(),
             globals: Default::default(),
             cvar_globals: Default::default(),
+            snapshot: Default::default(),
             stack: Vec::new(),
             io: io_cx,
+            background: false,
+            in_condition: false,
+            status: Value::Nil,
         };
 
         log::debug!("root fiber {} created", fiber.pid);
@@ -74,7 +110,15 @@ impl Fiber {
         fiber
     }
 
+    /// Get the identifier of this fiber.
+    pub fn pid(&self) -> usize {
+        self.pid
+    }
+
     /// Get the table that holds all global variables.
+    ///
+    /// Tables are shared by reference, so globals can be defined or changed
+    /// through the returned table.
     pub fn globals(&self) -> &Table {
         &self.globals
     }
@@ -94,15 +138,25 @@ impl Fiber {
         self.io.stderr()
     }
 
+    /// Get the I/O context that holds this fiber's standard streams.
+    pub fn io(&mut self) -> &mut IoContext {
+        &mut self.io
+    }
+
     /// Create a new fiber with the exact same stack and context as this one.
     pub fn fork(&self) -> Self {
         let fork = Self {
             pid: next_pid(),
             module_index: self.module_index.clone(),
+            alias_commands: self.alias_commands.clone(),
             globals: self.globals.clone(),
             cvar_globals: self.cvar_globals.clone(),
+            snapshot: self.snapshot.clone(),
             stack: self.stack.clone(),
             io: self.io.try_clone().unwrap(),
+            background: self.background,
+            in_condition: self.in_condition,
+            status: Value::Nil,
         };
 
         log::debug!("fiber {} forked from fiber {}", fork.pid, self.pid);
@@ -110,6 +164,35 @@ impl Fiber {
         fork
     }
 
+    /// Record the current globals and context variable defaults as the state
+    /// to return to when the fiber is [reset](Fiber::reset).
+    ///
+    /// A snapshot is taken at the end of [`init`](crate::init), and can be
+    /// taken again after any further setup, such as loading the standard
+    /// library.
+    pub fn snapshot(&mut self) {
+        self.snapshot = (self.globals.shallow_clone(), self.cvar_globals.shallow_clone());
+    }
+
+    /// Reset the fiber to the state it was in when the last snapshot was
+    /// taken, so that it can run another script without the cost of
+    /// initializing a new runtime.
+    ///
+    /// The call stack is cleared, and globals and context variable defaults
+    /// are restored to the values they had in the snapshot. Only the tables
+    /// of globals themselves are restored, so changes made inside a table held
+    /// by a global, such as `env`, are kept.
+    ///
+    /// Modules that have already been loaded stay cached across resets, and
+    /// are not loaded again the next time they are imported.
+    pub fn reset(&mut self) {
+        self.stack.clear();
+        self.globals.replace_entries(&self.snapshot.0);
+        self.cvar_globals.replace_entries(&self.snapshot.1);
+
+        log::debug!("fiber {} reset", self.pid);
+    }
+
     /// Get the fiber's current working directory.
     pub fn current_dir(&self) -> Value {
         // The working dir is just implemented as the `@cwd` context variable.
@@ -141,6 +224,37 @@ impl Fiber {
         }
     }
 
+    /// Check if command tracing is enabled. The setting is global and shared
+    /// by all related fibers.
+    ///
+    /// When tracing is enabled, each command is written to standard error
+    /// along with its arguments just before it is called. Every command in a
+    /// pipeline is written separately, since they each evaluate their own
+    /// arguments.
+    pub fn trace(&self) -> bool {
+        self.globals.get(TRACE_GLOBAL).is_truthy()
+    }
+
+    /// Enable or disable command tracing.
+    pub fn set_trace(&self, enabled: bool) {
+        self.globals.set(TRACE_GLOBAL, enabled);
+    }
+
+    /// Check if strict mode is enabled for the whole program.
+    ///
+    /// In strict mode, an external command that exits with a nonzero status
+    /// throws an exception. Strict mode is the default value of the `@strict`
+    /// context variable, so it can still be turned off for part of a program
+    /// by binding the variable in a scope.
+    pub fn strict(&self) -> bool {
+        self.cvar_globals.get(STRICT_CVAR).is_truthy()
+    }
+
+    /// Enable or disable strict mode for the whole program.
+    pub fn set_strict(&self, enabled: bool) {
+        self.cvar_globals.set(STRICT_CVAR, enabled);
+    }
+
     /// Execute the given script within this runtime.
     ///
     /// The script will be executed inside the context of the module with the given name. If no module name is given, an
@@ -236,6 +350,43 @@ impl Fiber {
         self.module_index.register_native_module(name, module);
     }
 
+    /// Register a native function as a global with the given name, making it
+    /// callable by scripts executed in this runtime.
+    ///
+    /// Returns an exception if a global with the same name is already defined.
+    pub fn register_fn(
+        &self,
+        name: impl Into<RipString>,
+        function: impl Into<ForeignFn>,
+    ) -> Result<(), Exception> {
+        let name = name.into();
+
+        if self.globals.get(&name) != Value::Nil {
+            throw!("global '{}' is already defined", name);
+        }
+
+        self.globals.set(name, Value::ForeignFn(function.into()));
+
+        Ok(())
+    }
+
+    /// Register a native module whose exports are the entries of the given
+    /// table, making it available to scripts with `import`.
+    ///
+    /// Returns an exception if a native module with the same name is already
+    /// registered.
+    pub fn register_table_module(&self, name: impl Into<String>, exports: Table) -> Result<(), Exception> {
+        let name = name.into();
+
+        if self.module_index.has_native_module(&name) {
+            throw!("module '{}' is already registered", name);
+        }
+
+        self.module_index.register_native_module(name, move || Ok(Value::from(exports.clone())));
+
+        Ok(())
+    }
+
     pub(crate) async fn load_module(&mut self, name: &str) -> Result<Value, Exception> {
         self.module_index.clone().load(self, name).await
     }
@@ -1,8 +1,18 @@
 use crate::prelude::*;
-use std::{future::Future, pin::Pin, rc::Rc};
+use std::{
+    any::Any,
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
 
 type LocalBoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
 
+/// A boxed future returned by a native function closure.
+pub type ForeignFuture<'a> = LocalBoxFuture<'a, Result<Value, Exception>>;
+
 /// A native function that can be invoked by scripts through a runtime as well
 /// as in native code.
 #[derive(Clone)]
@@ -13,9 +23,49 @@ impl ForeignFn {
         Self(Rc::new(f))
     }
 
+    /// Create a native function from a closure that returns a boxed future.
+    ///
+    /// Unlike an `async fn`, a closure can capture host state such as an `Rc`
+    /// handle. Since the returned future may outlive the call, clone any
+    /// captured state into the future rather than borrowing it:
+    ///
+    /// ```
+    /// use riptide_runtime::ForeignFn;
+    /// use std::{cell::Cell, rc::Rc};
+    ///
+    /// let calls = Rc::new(Cell::new(0u32));
+    ///
+    /// let function = ForeignFn::from_closure(move |_, _| {
+    ///     let calls = calls.clone();
+    ///
+    ///     Box::pin(async move {
+    ///         calls.set(calls.get() + 1);
+    ///         Ok(calls.get().into())
+    ///     })
+    /// });
+    /// ```
+    pub fn from_closure<F>(f: F) -> Self
+    where
+        F: for<'a> Fn(&'a mut Fiber, Vec<Value>) -> ForeignFuture<'a> + 'static,
+    {
+        Self::new(f)
+    }
+
     pub async fn call(&self, runtime: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
         self.0.call(runtime, args).await
     }
+
+    /// Call the function, converting a panic inside the function into an
+    /// exception instead of unwinding into the caller.
+    ///
+    /// Panics that abort the process, such as a panic while already
+    /// panicking, cannot be caught.
+    pub(crate) async fn call_catch_unwind(&self, runtime: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+        match CatchUnwind(self.0.call(runtime, args)).await {
+            Ok(result) => result,
+            Err(payload) => Err(Exception::from(format!("native function panicked: {}", panic_message(&*payload)))),
+        }
+    }
 }
 
 impl<F: private::ForeignFnTrait> From<F> for ForeignFn {
@@ -24,6 +74,37 @@ impl<F: private::ForeignFnTrait> From<F> for ForeignFn {
     }
 }
 
+/// A future that catches any panic that occurs while polling the inner future.
+///
+/// This is safe to use with a future that is not unwind safe only as long as it
+/// is not polled again after a panic, which this future does not do.
+struct CatchUnwind<F>(F);
+
+impl<F: Future + Unpin> Future for CatchUnwind<F> {
+    type Output = Result<F::Output, Box<dyn Any + Send>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = &mut self.0;
+
+        match panic::catch_unwind(AssertUnwindSafe(|| Pin::new(inner).poll(cx))) {
+            Ok(Poll::Pending) => Poll::Pending,
+            Ok(Poll::Ready(output)) => Poll::Ready(Ok(output)),
+            Err(payload) => Poll::Ready(Err(payload)),
+        }
+    }
+}
+
+/// Get the message a panic was raised with, if it has one.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "unknown panic"
+    }
+}
+
 mod private {
     use super::*;
 
@@ -0,0 +1,169 @@
+use super::{unix, Output};
+use std::{
+    io,
+    os::unix::io::{AsRawFd, RawFd},
+    pin::Pin,
+    process::Stdio,
+    task::{ready, Context, Poll},
+};
+use tokio::io::AsyncWrite;
+
+/// The maximum number of bytes held in a buffer before it is written out.
+const CAPACITY: usize = 8 * 1024;
+
+/// Controls when data written to an output is passed on to the underlying file
+/// descriptor.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BufferMode {
+    /// Every write is passed on immediately.
+    Unbuffered,
+
+    /// Writes are buffered until a newline is written, the buffer is full, or
+    /// the output is flushed. Suited to output that is read by a person as it
+    /// is written, such as a terminal.
+    Line,
+
+    /// Writes are buffered until the buffer is full or the output is flushed.
+    /// Suited to output read by another program, such as a pipe or file.
+    Block,
+}
+
+impl BufferMode {
+    /// Get the buffering mode that suits a file descriptor: line buffering if it
+    /// is a terminal, and block buffering otherwise.
+    pub fn for_fd(fd: RawFd) -> Self {
+        match nix::unistd::isatty(fd) {
+            Ok(true) => BufferMode::Line,
+            _ => BufferMode::Block,
+        }
+    }
+}
+
+/// An output that buffers writes according to a [`BufferMode`].
+///
+/// Anything still buffered when the output is dropped is written out with a
+/// blocking write, since there is no way to wait for an asynchronous one.
+pub struct BufferedOutput {
+    inner: Box<dyn Output>,
+    mode: BufferMode,
+    buffer: Vec<u8>,
+}
+
+impl BufferedOutput {
+    pub fn new(inner: Box<dyn Output>, mode: BufferMode) -> Self {
+        Self {
+            inner,
+            mode,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Get the buffering mode of this output.
+    pub fn mode(&self) -> BufferMode {
+        self.mode
+    }
+
+    /// Change the buffering mode of this output. Data that is already buffered
+    /// is written out on the next write or flush.
+    pub fn set_mode(&mut self, mode: BufferMode) {
+        self.mode = mode;
+    }
+
+    pub(crate) fn try_clone_buffered(&self) -> io::Result<Self> {
+        Ok(Self::new(self.inner.try_clone()?, self.mode))
+    }
+
+    /// Write out everything in the buffer to the underlying output.
+    fn poll_drain(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        while !self.buffer.is_empty() {
+            match ready!(Pin::new(&mut self.inner).poll_write(cx, &self.buffer))? {
+                0 => return Poll::Ready(Err(io::ErrorKind::WriteZero.into())),
+                n => {
+                    self.buffer.drain(..n);
+                }
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for BufferedOutput {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+
+        // Anything written earlier must be written out first, if the current
+        // mode would not have buffered it or there is no room left.
+        let drain_first = match this.mode {
+            BufferMode::Unbuffered => true,
+            BufferMode::Line => this.buffer.contains(&b'\n') || this.buffer.len() + buf.len() > CAPACITY,
+            BufferMode::Block => this.buffer.len() + buf.len() > CAPACITY,
+        };
+
+        if drain_first {
+            ready!(this.poll_drain(cx))?;
+        }
+
+        if this.mode == BufferMode::Unbuffered || buf.len() >= CAPACITY {
+            return Pin::new(&mut this.inner).poll_write(cx, buf);
+        }
+
+        this.buffer.extend_from_slice(buf);
+
+        // Try to write out a completed line right away. If the output is not
+        // ready, the line will be written before any further writes instead.
+        if this.mode == BufferMode::Line && buf.contains(&b'\n') {
+            if let Poll::Ready(Err(e)) = this.poll_drain(cx) {
+                return Poll::Ready(Err(e));
+            }
+        }
+
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        ready!(this.poll_drain(cx))?;
+
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        ready!(this.poll_drain(cx))?;
+
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+impl AsRawFd for BufferedOutput {
+    fn as_raw_fd(&self) -> RawFd {
+        self.inner.as_raw_fd()
+    }
+}
+
+impl Output for BufferedOutput {
+    fn try_clone(&self) -> io::Result<Box<dyn Output>> {
+        Ok(Box::new(self.try_clone_buffered()?))
+    }
+
+    fn create_stdio(&self) -> io::Result<Stdio> {
+        self.inner.create_stdio()
+    }
+
+    fn set_nonblocking(&mut self, nonblocking: bool) -> io::Result<()> {
+        self.inner.set_nonblocking(nonblocking)
+    }
+}
+
+impl Drop for BufferedOutput {
+    fn drop(&mut self) {
+        if !self.buffer.is_empty() {
+            if let Err(e) = unix::write_all_blocking(self.inner.as_raw_fd(), &self.buffer) {
+                log::warn!("failed to write buffered output: {}", e);
+            }
+        }
+    }
+}
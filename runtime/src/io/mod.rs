@@ -12,27 +12,39 @@ use std::{
 };
 use tokio::{
     fs::File,
-    io::{AsyncRead, AsyncWrite, Stderr, Stdin, Stdout},
+    io::{AsyncRead, AsyncWrite, AsyncWriteExt, Stderr, Stdin, Stdout},
 };
 use tokio_pipe::{pipe, PipeRead, PipeWrite};
 
+mod buffered;
 pub mod process;
 mod unix;
 
+pub use buffered::{BufferMode, BufferedOutput};
+
 /// An I/O context encapsulates the management of standard streams independently
 /// of the current process, which allows more than one I/O context to coexist
 /// inside the same process. This is essential in order to implement I/O aware
 /// fibers.
+///
+/// Standard output and standard error are buffered according to their
+/// [`BufferMode`]. Pipes created between contexts are never buffered.
 pub struct IoContext {
     stdin: Box<dyn Input>,
-    stdout: Box<dyn Output>,
-    stderr: Box<dyn Output>,
+    stdout: BufferedOutput,
+    stderr: BufferedOutput,
 }
 
 impl IoContext {
     /// Create a new context inherited from the standard streams of the current
     /// OS process.
+    ///
+    /// Standard output is line buffered if it is a terminal and block buffered
+    /// otherwise, while standard error is unbuffered.
     pub fn from_process() -> io::Result<Self> {
+        let stdout = unix::dup::<_, PipeWrite>(tokio::io::stdout())?;
+        let stdout_mode = BufferMode::for_fd(stdout.as_raw_fd());
+
         Ok(Self {
             stdin: Box::new({
                 let mut stdin = unix::dup::<_, PipeRead>(tokio::io::stdin())?;
@@ -41,8 +53,11 @@ impl IoContext {
 
                 stdin
             }),
-            stdout: Box::new(unix::dup::<_, PipeWrite>(tokio::io::stdout())?),
-            stderr: Box::new(unix::dup::<_, PipeWrite>(tokio::io::stderr())?),
+            stdout: BufferedOutput::new(Box::new(stdout), stdout_mode),
+            stderr: BufferedOutput::new(
+                Box::new(unix::dup::<_, PipeWrite>(tokio::io::stderr())?),
+                BufferMode::Unbuffered,
+            ),
             // stdin: Box::new(tokio::io::stdin()),
             // stdout: Box::new(tokio::io::stdout()),
             // stderr: Box::new(tokio::io::stderr()),
@@ -54,18 +69,57 @@ impl IoContext {
     }
 
     pub fn stdout(&mut self) -> &mut dyn Output {
-        &mut *self.stdout
+        &mut self.stdout
+    }
+
+    /// Close standard input, replacing it with an empty input.
+    ///
+    /// This lets the writer of a pipe connected to standard input know that
+    /// nothing more will be read from it, once no other readers remain.
+    pub async fn close_stdin(&mut self) -> io::Result<()> {
+        self.stdin = Box::new(File::open("/dev/null").await?);
+        Ok(())
     }
 
     pub fn stderr(&mut self) -> &mut dyn Output {
-        &mut *self.stderr
+        &mut self.stderr
+    }
+
+    /// Get the buffering mode of standard output.
+    pub fn stdout_buffer_mode(&self) -> BufferMode {
+        self.stdout.mode()
+    }
+
+    /// Set the buffering mode of standard output.
+    pub fn set_stdout_buffer_mode(&mut self, mode: BufferMode) {
+        self.stdout.set_mode(mode);
+    }
+
+    /// Get the buffering mode of standard error.
+    pub fn stderr_buffer_mode(&self) -> BufferMode {
+        self.stderr.mode()
+    }
+
+    /// Set the buffering mode of standard error.
+    pub fn set_stderr_buffer_mode(&mut self, mode: BufferMode) {
+        self.stderr.set_mode(mode);
+    }
+
+    /// Write out any buffered data in standard output and standard error.
+    ///
+    /// This must be done before handing the file descriptors to anything else
+    /// that writes to them, such as another fiber or a child process, so that
+    /// output appears in the order it was written.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.stdout.flush().await?;
+        self.stderr.flush().await
     }
 
     pub fn try_clone(&self) -> io::Result<Self> {
         Ok(Self {
             stdin: self.stdin.try_clone()?,
-            stdout: self.stdout.try_clone()?,
-            stderr: self.stderr.try_clone()?,
+            stdout: self.stdout.try_clone_buffered()?,
+            stderr: self.stderr.try_clone_buffered()?,
         })
     }
 
@@ -77,8 +131,8 @@ impl IoContext {
         Ok((
             Self {
                 stdin: self.stdin,
-                stdout: Box::new(stdout),
-                stderr: self.stderr.try_clone()?,
+                stdout: BufferedOutput::new(Box::new(stdout), BufferMode::Unbuffered),
+                stderr: self.stderr.try_clone_buffered()?,
             },
             Self {
                 stdin: Box::new(stdin),
@@ -176,6 +230,11 @@ pub trait Output: AsyncWrite + AsRawFd + Unpin + Send {
     fn set_nonblocking(&mut self, nonblocking: bool) -> io::Result<()> {
         unix::set_nonblocking(&mut self.as_raw_fd(), nonblocking)
     }
+
+    /// Check if this output is a terminal.
+    fn is_terminal(&self) -> bool {
+        nix::unistd::isatty(self.as_raw_fd()).unwrap_or(false)
+    }
 }
 
 impl AsRawFd for Box<dyn Output> {
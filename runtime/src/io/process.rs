@@ -1,18 +1,62 @@
 //! Functions for working with processes.
 
-use crate::prelude::*;
-use nix::unistd;
+use crate::{fiber::STRICT_CVAR, prelude::*, throw};
+use nix::{
+    fcntl::{fcntl, FcntlArg},
+    libc::{self, c_int},
+    sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal},
+    unistd::{self, Pid},
+};
 use std::{
+    convert::Infallible,
+    env,
     ffi::{CString, OsStr},
     future::Future,
-    io::ErrorKind,
-    process,
+    io::{self, ErrorKind},
+    os::unix::{
+        ffi::OsStrExt,
+        fs::PermissionsExt,
+        io::{AsRawFd, RawFd},
+        process::ExitStatusExt,
+    },
+    path::{Path, PathBuf},
+    process::{self, ExitStatus},
+    sync::{
+        atomic::{AtomicI32, Ordering},
+        Mutex,
+    },
+};
+use tokio::{
+    process::{Child, Command},
+    signal::unix::{Signal as SignalStream, SignalKind},
 };
-use tokio::process::Command;
+
+/// Name of the global variable holding the exit status of the last command.
+pub const LAST_STATUS: &str = "?";
+
+/// Name of the global variable holding the exit status of each stage of the
+/// last pipeline with more than one stage.
+pub const PIPE_STATUS: &str = "PIPESTATUS";
+
+/// Name of the global variable holding the handler for commands that do not
+/// exist.
+pub const COMMAND_NOT_FOUND: &str = "command-not-found";
 
 /// Executes a shell command in the foreground, waiting for it to complete.
 ///
-/// Returns the process exit code.
+/// The exit status of the command is stored in the `$?` global. If the process
+/// exited normally this is its exit code, and if it was terminated by a signal
+/// this is the negated signal number.
+///
+/// A nonzero status is not treated as an error unless the `@strict` context
+/// variable is truthy, in which case an exception is thrown. Commands whose
+/// failure is handled by a `&&` or `||` operator never throw.
+///
+/// Unless the fiber is running a background job, the process is run in the
+/// foreground: it is put in its own process group, which is given the terminal
+/// while it runs, and any interrupt and stop signals we receive are forwarded
+/// to it. There is no job control to resume a stopped process with later, so
+/// if the process is stopped, such as with Ctrl-Z, it is continued right away.
 ///
 /// Cancellation is fully supported. Dropping the returned future will send a
 /// signal to the child process to terminate.
@@ -34,29 +78,294 @@ pub async fn command(
     // description, so we must make sure to restore this when we're done.
     fiber.stdin().set_nonblocking(false)?;
 
-    let exit_status = Command::new(command)
+    // The child writes directly to our output file descriptors, so anything we
+    // have buffered must be written before it starts.
+    fiber.io.flush().await?;
+
+    let mut cmd = Command::new(command.as_ref());
+
+    cmd
         .args(
             args.iter()
-                .map(|value| crate::string::RipString::from(value.clone())),
+                .map(crate::coerce::to_string),
         )
         .current_dir(fiber.current_dir().to_string())
         .stdin(fiber.stdin().create_stdio()?)
         .stdout(fiber.stdout().create_stdio()?)
         .stderr(fiber.stderr().create_stdio()?)
-        .kill_on_drop(true)
-        .status()
-        .await
-        .map_err(|e| match e.kind() {
-            ErrorKind::NotFound => Exception::from("no such command or file"),
-            _ => e.to_string().into(),
-        })?;
-
-    if exit_status.success() {
-        Ok(Value::Nil)
+        .kill_on_drop(true);
+
+    // Foreground processes are put in a process group that is given the
+    // terminal while they run, so that Ctrl-C and Ctrl-Z go to them and not to
+    // us. A process stopped by Ctrl-Z is continued right away, since there is
+    // no job control to resume it with later. Keep the guard alive until the
+    // process exits.
+    let foreground = if fiber.background {
+        None
+    } else {
+        Some(Foreground::enter(&mut cmd))
+    };
+
+    // Listen for the process being stopped before it has a chance to be.
+    let mut child_signals = if foreground.is_some() {
+        Some(tokio::signal::unix::signal(SignalKind::child())?)
     } else {
-        Err(Exception::from(Value::from(
-            exit_status.code().unwrap_or(0) as f64,
-        )))
+        None
+    };
+
+    let mut child = cmd.spawn().map_err(|e| match e.kind() {
+        ErrorKind::NotFound => not_found(&fiber, command.as_ref()),
+        _ => e.to_string().into(),
+    })?;
+
+    if let (Some(foreground), Some(pid)) = (foreground.as_ref(), child.id()) {
+        foreground.started(Pid::from_raw(pid as i32));
+    }
+
+    let exit_status = match (child.id(), child_signals.as_mut()) {
+        (Some(pid), Some(child_signals)) => wait_foreground(&mut child, Pid::from_raw(pid as i32), child_signals).await?,
+        _ => child.wait().await?,
+    };
+
+    drop(foreground);
+
+    let status = match (exit_status.code(), exit_status.signal()) {
+        (Some(code), _) => code,
+        (None, Some(signal)) => -signal,
+        (None, None) => unreachable!("process exited without a code or signal"),
+    };
+
+    fiber.status = Value::from(status as f64);
+    fiber.globals().set(LAST_STATUS, fiber.status.clone());
+
+    if status != 0 && !fiber.in_condition && fiber.get_cvar(STRICT_CVAR).is_truthy() {
+        let command = command.as_ref().to_string_lossy();
+
+        match exit_status.signal() {
+            Some(signal) => throw!("command '{}' was terminated by signal {}", command, signal),
+            None => throw!("command '{}' exited with status {}", command, status),
+        }
+    }
+
+    Ok(Value::Nil)
+}
+
+/// Wait for a foreground process to exit, continuing it whenever it is stopped.
+///
+/// Nothing would ever continue a stopped foreground process otherwise, and we
+/// would wait for it forever.
+async fn wait_foreground(child: &mut Child, pid: Pid, child_signals: &mut SignalStream) -> io::Result<ExitStatus> {
+    loop {
+        tokio::select! {
+            status = child.wait() => return status,
+            _ = child_signals.recv() => {
+                if take_stopped(pid) {
+                    log::debug!("continuing stopped foreground process {}", pid);
+
+                    if let Err(e) = signal::kill(pid, Signal::SIGCONT) {
+                        log::warn!("failed to continue process {}: {}", pid, e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Check whether a child process has stopped since this was last checked.
+///
+/// Only stops are reported, so the exit status of the process is left for
+/// [`Child::wait`] to collect.
+fn take_stopped(pid: Pid) -> bool {
+    let mut info: libc::siginfo_t = unsafe { std::mem::zeroed() };
+
+    let result = unsafe {
+        libc::waitid(libc::P_PID, pid.as_raw() as libc::id_t, &mut info, libc::WSTOPPED | libc::WNOHANG)
+    };
+
+    result == 0 && info.si_code == libc::CLD_STOPPED
+}
+
+/// Find the executable that an external command name refers to.
+///
+/// Names that contain a slash are paths relative to the working directory of
+/// the fiber, and any other name is searched for in the directories listed in
+/// `PATH`.
+pub fn find_executable(fiber: &Fiber, name: &str) -> Option<PathBuf> {
+    fn is_executable(path: &Path) -> bool {
+        match path.metadata() {
+            Ok(metadata) => metadata.is_file() && metadata.permissions().mode() & 0o111 != 0,
+            Err(_) => false,
+        }
+    }
+
+    if name.contains('/') {
+        let path = Path::new(&fiber.current_dir().to_string()).join(name);
+
+        return Some(path).filter(|path| is_executable(path));
+    }
+
+    env::split_paths(&env::var_os("PATH")?)
+        .map(|dir| dir.join(name))
+        .find(|path| is_executable(path))
+}
+
+/// The process group that foreground processes are running in, or zero if
+/// there are none. This is read by a signal handler, so it is kept apart from
+/// the rest of the group's state.
+static FOREGROUND_PGID: AtomicI32 = AtomicI32::new(0);
+
+/// State of the foreground process group, if there is one.
+static FOREGROUND_GROUP: Mutex<Option<ForegroundGroup>> = Mutex::new(None);
+
+struct ForegroundGroup {
+    /// Number of foreground processes that have not yet exited.
+    members: usize,
+
+    /// The terminal that the group has been given, if we had it to give.
+    terminal: Option<RawFd>,
+
+    /// Signal actions to restore once the group is done.
+    signal_actions: Vec<(Signal, SigAction)>,
+}
+
+/// Membership of a process in the foreground process group.
+///
+/// Foreground processes started at the same time, such as the commands of a
+/// pipeline, share a single group just like a job would in other shells. While
+/// the group exists, interrupt and stop signals that we receive are forwarded
+/// to it instead of being handled here. Once the last member is dropped, the
+/// terminal and signal actions are restored.
+struct Foreground(());
+
+impl Foreground {
+    /// Join the foreground group, creating it if needed, and configure the
+    /// given command to start in it.
+    fn enter(command: &mut Command) -> Self {
+        let mut group = FOREGROUND_GROUP.lock().unwrap();
+
+        let group = group.get_or_insert_with(|| {
+            // SIGTTOU must be ignored before the terminal is handed over, or
+            // taking it back later would stop us.
+            let signal_actions = forward_signals();
+
+            ForegroundGroup {
+                members: 0,
+                terminal: controlling_terminal(),
+                signal_actions,
+            }
+        });
+
+        group.members += 1;
+
+        let terminal = group.terminal;
+        command.process_group(FOREGROUND_PGID.load(Ordering::SeqCst));
+
+        // Take the terminal from within the child as well, so that it cannot
+        // read from the terminal before we have given it to the group. Only
+        // async-signal-safe calls can be made here.
+        unsafe {
+            command.pre_exec(move || {
+                if let Some(terminal) = terminal {
+                    unistd::tcsetpgrp(terminal, unistd::getpgrp())?;
+                }
+
+                let default = SigAction::new(SigHandler::SigDfl, SaFlags::empty(), SigSet::empty());
+                signal::sigaction(Signal::SIGTTOU, &default)?;
+
+                Ok(())
+            });
+        }
+
+        Self(())
+    }
+
+    /// Record that a member of the group has started with the given PID.
+    fn started(&self, pid: Pid) {
+        let group = FOREGROUND_GROUP.lock().unwrap();
+
+        // The first process to start is the leader of a new group.
+        if FOREGROUND_PGID.load(Ordering::SeqCst) == 0 {
+            FOREGROUND_PGID.store(pid.as_raw(), Ordering::SeqCst);
+
+            if let Some(terminal) = group.as_ref().and_then(|group| group.terminal) {
+                if let Err(e) = unistd::tcsetpgrp(terminal, pid) {
+                    log::warn!("failed to give terminal to process group {}: {}", pid, e);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for Foreground {
+    fn drop(&mut self) {
+        let mut guard = FOREGROUND_GROUP.lock().unwrap();
+
+        let group = match guard.as_mut() {
+            Some(group) => group,
+            None => return,
+        };
+
+        group.members -= 1;
+
+        if group.members > 0 {
+            return;
+        }
+
+        let group = guard.take().unwrap();
+
+        if let Some(terminal) = group.terminal {
+            if let Err(e) = unistd::tcsetpgrp(terminal, unistd::getpgrp()) {
+                log::warn!("failed to take back terminal: {}", e);
+            }
+        }
+
+        for (signal, action) in group.signal_actions {
+            if let Err(e) = unsafe { signal::sigaction(signal, &action) } {
+                log::warn!("failed to restore action for {}: {}", signal, e);
+            }
+        }
+
+        FOREGROUND_PGID.store(0, Ordering::SeqCst);
+    }
+}
+
+/// Forward interrupt and stop signals to the foreground group, and ignore
+/// SIGTTOU so that we can change the terminal's process group. Returns the
+/// previous signal actions.
+fn forward_signals() -> Vec<(Signal, SigAction)> {
+    let forward = SigAction::new(SigHandler::Handler(forward_signal), SaFlags::SA_RESTART, SigSet::empty());
+    let ignore = SigAction::new(SigHandler::SigIgn, SaFlags::empty(), SigSet::empty());
+
+    [(Signal::SIGINT, &forward), (Signal::SIGTSTP, &forward), (Signal::SIGTTOU, &ignore)]
+        .into_iter()
+        .filter_map(|(signal, action)| match unsafe { signal::sigaction(signal, action) } {
+            Ok(previous) => Some((signal, previous)),
+            Err(e) => {
+                log::warn!("failed to set action for {}: {}", signal, e);
+                None
+            }
+        })
+        .collect()
+}
+
+extern "C" fn forward_signal(signal: c_int) {
+    let pgid = FOREGROUND_PGID.load(Ordering::SeqCst);
+
+    if pgid > 0 {
+        unsafe {
+            libc::killpg(pgid, signal);
+        }
+    }
+}
+
+/// Get the terminal of the current process, if it has one and we are in the
+/// foreground of it.
+fn controlling_terminal() -> Option<RawFd> {
+    let terminal = [0, 1, 2].into_iter().find(|&fd| unistd::isatty(fd).unwrap_or(false))?;
+
+    match unistd::tcgetpgrp(terminal) {
+        Ok(pgid) if pgid == unistd::getpgrp() => Some(terminal),
+        _ => None,
     }
 }
 
@@ -85,19 +394,102 @@ pub async fn spawn<F: Future<Output = ()>>(future: F) -> Result<i32, String> {
 }
 
 /// Replace the current process with an external command.
-pub fn exec(command: &str, args: &[&str]) -> Result<(), String> {
-    let command_c = CString::new(command).unwrap();
+///
+/// The command inherits the standard I/O streams and working directory of the
+/// given fiber. Anything buffered for output is written first, since it would
+/// otherwise be lost along with the rest of the process.
+///
+/// On success this never returns. If the command could not be executed, the
+/// process is left as it was and an exception is returned instead.
+pub async fn exec(
+    fiber: &mut Fiber,
+    command: impl AsRef<OsStr>,
+    args: &[Value],
+) -> Result<Infallible, Exception> {
+    let command = command.as_ref();
+
+    let to_cstring = |bytes: &[u8]| {
+        CString::new(bytes).map_err(|_| Exception::from("arguments cannot contain null bytes"))
+    };
 
-    let mut args_c = Vec::new();
+    let command_c = to_cstring(command.as_bytes())?;
+    let mut args_c = vec![command_c.clone()];
     for arg in args {
-        args_c.push(CString::new(*arg).unwrap());
+        args_c.push(to_cstring(crate::coerce::to_string(arg).as_bytes())?);
     }
 
-    match unistd::execvp(
-        &command_c,
-        &args_c.iter().map(|s| s.as_c_str()).collect::<Vec<_>>(),
-    ) {
-        Ok(_) => Ok(()),
-        Err(e) => Err(e.to_string()),
+    let cwd = crate::coerce::to_string(&fiber.current_dir());
+
+    fiber.io.flush().await?;
+
+    // The command runs in the working directory of the fiber rather than our
+    // own. Changing directory is not undone by a failed exec, so change back
+    // on any error.
+    let saved_cwd = unistd::getcwd().map_err(io::Error::from)?;
+
+    if let Err(e) = unistd::chdir(cwd.as_bytes()) {
+        throw!("failed to change directory to '{}': {}", cwd, e);
+    }
+
+    let _saved_cwd = scopeguard::guard(saved_cwd, |dir| {
+        if let Err(e) = unistd::chdir(&dir) {
+            log::warn!("failed to restore working directory: {}", e);
+        }
+    });
+
+    // The fiber's streams are not necessarily the process's standard streams,
+    // such as when exec is part of a pipeline, so move them into place. Keep
+    // copies of the originals in case the command cannot be executed.
+    let fds = [
+        fiber.stdin().as_raw_fd(),
+        fiber.stdout().as_raw_fd(),
+        fiber.stderr().as_raw_fd(),
+    ];
+    let saved = [dup_cloexec(0)?, dup_cloexec(1)?, dup_cloexec(2)?];
+
+    let result = (|| -> io::Result<Infallible> {
+        for (target, fd) in fds.into_iter().enumerate() {
+            if fd != target as RawFd {
+                unistd::dup2(fd, target as RawFd)?;
+            }
+        }
+
+        // Processes expect standard input to begin life as blocking.
+        let mut stdin: RawFd = 0;
+        super::unix::set_nonblocking(&mut stdin, false)?;
+
+        Ok(unistd::execvp(&command_c, &args_c)?)
+    })();
+
+    let error = match result {
+        Ok(never) => match never {},
+        Err(e) => e,
+    };
+
+    for (target, fd) in saved.into_iter().enumerate() {
+        unistd::dup2(fd, target as RawFd).map_err(io::Error::from)?;
+        unistd::close(fd).map_err(io::Error::from)?;
     }
+
+    fiber.stdin().set_nonblocking(true)?;
+
+    match error.kind() {
+        ErrorKind::NotFound => Err(not_found(fiber, command)),
+        _ => throw!("failed to execute '{}': {}", command.to_string_lossy(), error),
+    }
+}
+
+/// Create the exception for a command that does not exist, suggesting a
+/// similarly named function if there is one.
+fn not_found(fiber: &Fiber, command: &OsStr) -> Exception {
+    match crate::suggest::command(fiber, &command.to_string_lossy()) {
+        Some(suggestion) => Exception::from(format!("no such command or file, did you mean '{}'?", suggestion)),
+        None => Exception::from("no such command or file"),
+    }
+}
+
+/// Duplicate a file descriptor that is closed when a new program is executed,
+/// so that it is not leaked into it.
+fn dup_cloexec(fd: RawFd) -> io::Result<RawFd> {
+    fcntl(fd, FcntlArg::F_DUPFD_CLOEXEC(0)).map_err(io::Error::from)
 }
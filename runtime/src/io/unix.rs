@@ -1,7 +1,9 @@
 use nix::fcntl::{fcntl, FcntlArg, OFlag};
 use std::{
-    io,
-    os::unix::io::{AsRawFd, FromRawFd},
+    fs::File,
+    io::{self, Write},
+    mem::ManuallyDrop,
+    os::unix::io::{AsRawFd, FromRawFd, RawFd},
 };
 
 pub(super) fn dup<T: AsRawFd, U: FromRawFd>(fd: T) -> io::Result<U> {
@@ -22,3 +24,25 @@ pub(super) fn set_nonblocking(file: &mut impl AsRawFd, nonblocking: bool) -> io:
         .map_err(io::Error::from)
         .map(|_| ())
 }
+
+/// Write all of a buffer to a file descriptor, blocking until it is written
+/// even if the file descriptor is in non-blocking mode.
+pub(super) fn write_all_blocking(mut fd: RawFd, buf: &[u8]) -> io::Result<()> {
+    let flags = fcntl(fd, FcntlArg::F_GETFL)
+        .map(OFlag::from_bits_truncate)
+        .map_err(io::Error::from)?;
+    let nonblocking = flags.contains(OFlag::O_NONBLOCK);
+
+    if nonblocking {
+        set_nonblocking(&mut fd, false)?;
+    }
+
+    // The file descriptor is borrowed, so make sure it is not closed.
+    let result = ManuallyDrop::new(unsafe { File::from_raw_fd(fd) }).write_all(buf);
+
+    if nonblocking {
+        set_nonblocking(&mut fd, true)?;
+    }
+
+    result
+}
@@ -0,0 +1,203 @@
+//! Management of background jobs.
+//!
+//! A background job is either a pipeline that is run in a detached fiber while
+//! the current fiber continues executing, or a child process spawned by the
+//! runtime. Each job is represented by a table with the following keys, which
+//! is updated in place as the job changes state:
+//!
+//! - `id`: A number identifying the job, starting from 1.
+//! - `fiber`: The ID of the fiber running the job, if it is a pipeline.
+//! - `pid`: The process ID of the job, if it is a child process.
+//! - `status`: Either `running` or `done`.
+//! - `exit-code`: The exit status of the job once it is done. For a pipeline
+//!   this is the value of `$?` after it completes, or 1 if it threw an
+//!   exception. For a process this follows the same convention as `$?`.
+
+use crate::{
+    coerce,
+    controlflow::BreakAction,
+    eval,
+    io::process,
+    prelude::*,
+    table,
+    throw,
+};
+use nix::{
+    sys::wait::{waitpid, WaitPidFlag, WaitStatus},
+    unistd::Pid,
+};
+use riptide_syntax::ast::Pipeline;
+use std::{ops::ControlFlow, panic};
+use tokio::{
+    signal::unix::{signal, SignalKind},
+    sync::Notify,
+};
+
+/// This is the name of the hidden global variable that the list of jobs is
+/// stored in.
+static JOBS_GLOBAL: &str = "__jobs";
+
+/// Notified whenever a job running in a fiber finishes.
+static FIBER_JOB_DONE: Notify = Notify::const_new();
+
+/// Run a pipeline in the background as a new job, returning the job table.
+///
+/// Jobs run as local tasks, so an exception is thrown if this is not called
+/// from within a [`tokio::task::LocalSet`].
+pub(crate) async fn spawn(fiber: &mut Fiber, pipeline: Pipeline) -> Result<Value, Exception> {
+    // Tokio offers no way to check for a local task set besides trying to
+    // spawn into one, which panics if there is none.
+    if panic::catch_unwind(|| tokio::task::spawn_local(async {})).is_err() {
+        throw!("background jobs are not supported outside of a local task set");
+    }
+
+    fiber.io.flush().await?;
+
+    let mut child_fiber = fiber.fork();
+    child_fiber.background = true;
+
+    let job = add(fiber, table! {
+        "fiber" => child_fiber.pid() as f64,
+        "status" => "running",
+    });
+    let id = job.get("id");
+
+    log::debug!("started job {} in fiber {}", id, child_fiber.pid());
+
+    tokio::task::spawn_local({
+        let job = job.clone();
+
+        async move {
+            let exit_code = match eval::evaluate_pipeline(&mut child_fiber, pipeline).await {
+                ControlFlow::Break(BreakAction::Throw(e)) => {
+                    log::error!("job {}: {}", id, e);
+                    1.0
+                }
+                _ => child_fiber.globals().get(process::LAST_STATUS).as_number().unwrap_or(0.0),
+            };
+
+            log::debug!("job {} finished with status {}", id, exit_code);
+
+            job.set("status", "done");
+            job.set("exit-code", exit_code);
+            FIBER_JOB_DONE.notify_waiters();
+        }
+    });
+
+    Ok(job.into())
+}
+
+/// Track a child process as a job so that it can be waited on, returning the
+/// job table.
+///
+/// The process must be a direct child of the current process, and must not be
+/// waited on by anything else.
+pub fn track_process(fiber: &Fiber, pid: i32) -> Value {
+    let job = add(fiber, table! {
+        "pid" => pid as f64,
+        "status" => "running",
+    });
+
+    log::debug!("tracking process {} as job {}", pid, job.get("id"));
+
+    job.into()
+}
+
+fn add(fiber: &Fiber, job: Table) -> Table {
+    let mut jobs = list(fiber);
+
+    job.set("id", jobs.len() as f64 + 1.0);
+    jobs.push(job.clone().into());
+    fiber.globals().set(JOBS_GLOBAL, jobs);
+
+    job
+}
+
+/// Get a list of all jobs started by this runtime.
+///
+/// Child processes are only reaped when waited on, so a process is listed as
+/// running until then even if it has already exited.
+pub(crate) fn list(fiber: &Fiber) -> Vec<Value> {
+    fiber.globals().get(JOBS_GLOBAL).as_list().map(<[Value]>::to_vec).unwrap_or_default()
+}
+
+/// Find a job by its job table, process ID, or job ID.
+pub(crate) fn find(fiber: &Fiber, value: &Value) -> Result<Value, Exception> {
+    let jobs = list(fiber);
+
+    if let Value::Table(_) = value {
+        if let Some(job) = jobs.into_iter().find(|job| job == value) {
+            return Ok(job);
+        }
+    } else if let Some(number) = coerce::to_number(value) {
+        // Process IDs take priority over job IDs.
+        let job = jobs.iter().find(|job| job.get("pid") == number)
+            .or_else(|| jobs.iter().find(|job| job.get("id") == number));
+
+        if let Some(job) = job {
+            return Ok(job.clone());
+        }
+    }
+
+    throw!("no such job or process: {}", value)
+}
+
+/// Wait for a job to complete and return its exit status.
+///
+/// If the job is already done, its exit status is returned immediately.
+pub(crate) async fn wait(job: &Value) -> Result<Value, Exception> {
+    // Processes are forked directly rather than spawned as a Tokio child, so
+    // listen for `SIGCHLD` the same way Tokio does to know when to check again.
+    let mut child_signals = signal(SignalKind::child())?;
+
+    loop {
+        // Register for notifications before checking, so that a job finishing
+        // in between is not missed.
+        let fiber_job_done = FIBER_JOB_DONE.notified();
+        tokio::pin!(fiber_job_done);
+        fiber_job_done.as_mut().enable();
+
+        if update(job) {
+            return Ok(job.get("exit-code"));
+        }
+
+        tokio::select! {
+            _ = fiber_job_done => {}
+            _ = child_signals.recv() => {}
+        }
+    }
+}
+
+/// Check if a job is done, updating its table if a child process has exited
+/// since the last check.
+fn update(job: &Value) -> bool {
+    if job.get("status") == "done" {
+        return true;
+    }
+
+    // Jobs running in a fiber update themselves.
+    let pid = match job.get("pid").as_number() {
+        Some(pid) => Pid::from_raw(pid as i32),
+        None => return false,
+    };
+
+    let exit_code = match waitpid(pid, Some(WaitPidFlag::WNOHANG)) {
+        Ok(WaitStatus::Exited(_, code)) => Value::from(code as f64),
+        Ok(WaitStatus::Signaled(_, signal, _)) => Value::from(-(signal as i32) as f64),
+        Ok(_) => return false,
+
+        // The process was already reaped by someone else, so its exit status
+        // is unknown.
+        Err(e) => {
+            log::warn!("failed to get status of process {}: {}", pid, e);
+            Value::Nil
+        }
+    };
+
+    if let Some(table) = job.as_table() {
+        table.set("status", "done");
+        table.set("exit-code", exit_code);
+    }
+
+    true
+}
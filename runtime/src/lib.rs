@@ -1,24 +1,31 @@
 use std::{env, time::Instant};
 
+mod alias;
 mod builtins;
 mod closure;
+pub mod coerce;
+pub mod completion;
 mod controlflow;
 mod eval;
 mod exceptions;
 mod fiber;
 mod foreign;
 pub mod io;
+pub mod jobs;
 mod macros;
 mod modules;
+pub mod number;
 mod scope;
 mod string;
+mod suggest;
 mod table;
 mod value;
 
 pub use crate::{
     exceptions::Exception,
     fiber::Fiber,
-    foreign::ForeignFn,
+    foreign::{ForeignFn, ForeignFuture},
+    string::RipString,
     table::Table,
     value::Value,
 };
@@ -59,6 +66,9 @@ pub async fn init() -> Result<Fiber, Exception> {
     // Run the first bootstrap script
     fiber.execute(None, include_str!("init.rt")).await?;
 
+    // Resetting the fiber returns it to this point.
+    fiber.snapshot();
+
     log::debug!("runtime took {:?} to initialize", start_time.elapsed());
 
     Ok(fiber)
@@ -10,25 +10,14 @@ macro_rules! throw {
 /// closure that takes care of lifetime fiddly bits for you.
 #[macro_export]
 macro_rules! foreign_fn {
-    ($(clone $clone:ident),* |$fiber:pat, $args:ident| $block:expr) => {{
-        type LocalBoxFuture<'a, T> = ::std::pin::Pin<Box<dyn ::std::future::Future<Output = T> + 'a>>;
-
-        fn constrain<F>(f: F) -> F
-        where
-            F: for<'a> Fn(&'a mut $crate::Fiber, Vec<$crate::Value>) -> LocalBoxFuture<'a, Result<$crate::Value, $crate::Exception>>,
-        {
-            f
-        }
-
-        let closure = constrain(move |$fiber, $args| {
+    ($(clone $clone:ident),* |$fiber:pat, $args:ident| $block:expr) => {
+        $crate::ForeignFn::from_closure(move |$fiber, $args| {
             $(
                 let $clone = $clone.clone();
             )*
             Box::pin(async move {
                 $block
             })
-        });
-
-        $crate::ForeignFn::from(closure)
-    }};
+        })
+    };
 }
@@ -12,6 +12,9 @@ use std::{
     path::*,
 };
 
+/// Name prefix shared by all modules provided by the standard library.
+const STDLIB_PREFIX: &str = "std/";
+
 /// Base trait that allows you to implement a module in entirely native code.
 pub trait NativeModule {
     fn init(&self) -> Result<Value, Exception>;
@@ -72,9 +75,26 @@ impl ModuleIndex {
             }
         }
 
+        // Give a more helpful error if a standard library module is requested
+        // but the standard library was never loaded into this runtime.
+        if name.starts_with(STDLIB_PREFIX) && !self.has_stdlib() {
+            throw!("module '{}' not found (the standard library is not loaded)", name)
+        }
+
         throw!("module '{}' not found", name)
     }
 
+    /// Check if any standard library modules have been registered.
+    fn has_stdlib(&self) -> bool {
+        self.native_modules.borrow().keys().any(|name| name.starts_with(STDLIB_PREFIX))
+    }
+
+    /// Check if a module implemented in native code has been registered with
+    /// the given name.
+    pub(crate) fn has_native_module(&self, name: &str) -> bool {
+        self.native_modules.borrow().contains_key(name)
+    }
+
     /// Register a module implemented in native code.
     pub(crate) fn register_native_module<N, M>(&self, name: N, module: M)
     where
@@ -0,0 +1,77 @@
+//! Formatting of numbers for display.
+
+/// Format a number the way it is displayed by default.
+///
+/// Whole numbers are written without a fractional part, such as `1000000`
+/// rather than `1000000.0`. Other numbers are written with the fewest digits
+/// that represent the number exactly, so `0.1 + 0.2` is `0.30000000000000004`.
+pub fn display(number: f64) -> String {
+    number.to_string()
+}
+
+/// Format a number with a fixed number of decimal places and a separator
+/// between each group of thousands.
+///
+/// If `decimals` is `None` the number is written with as many decimal places
+/// as it is by [`display`]. If `separator` is `None` the digits are not
+/// grouped. Infinite and `NaN` values are written as they are by [`display`].
+pub fn format(number: f64, decimals: Option<usize>, separator: Option<&str>) -> String {
+    if !number.is_finite() {
+        return display(number);
+    }
+
+    let formatted = match decimals {
+        Some(decimals) => format!("{:.*}", decimals, number),
+        None => display(number),
+    };
+
+    let separator = match separator {
+        Some(separator) if !separator.is_empty() => separator,
+        _ => return formatted,
+    };
+
+    let (sign, unsigned) = match formatted.strip_prefix('-') {
+        Some(unsigned) => ("-", unsigned),
+        None => ("", formatted.as_str()),
+    };
+
+    let (integer, fraction) = match unsigned.find('.') {
+        Some(point) => unsigned.split_at(point),
+        None => (unsigned, ""),
+    };
+
+    let mut grouped = String::from(sign);
+
+    for (i, digit) in integer.chars().enumerate() {
+        if i > 0 && (integer.len() - i) % 3 == 0 {
+            grouped.push_str(separator);
+        }
+
+        grouped.push(digit);
+    }
+
+    grouped.push_str(fraction);
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_whole_numbers_without_fraction() {
+        assert_eq!(display(1000000.0), "1000000");
+        assert_eq!(display(-3.0), "-3");
+        assert_eq!(display(0.1 + 0.2), "0.30000000000000004");
+    }
+
+    #[test]
+    fn format_decimals_and_separators() {
+        assert_eq!(format(0.1 + 0.2, Some(2), None), "0.30");
+        assert_eq!(format(1234567.891, Some(1), Some(",")), "1,234,567.9");
+        assert_eq!(format(-1234.0, None, Some("_")), "-1_234");
+        assert_eq!(format(999.0, Some(0), Some(",")), "999");
+        assert_eq!(format(1e6, None, Some(" ")), "1 000 000");
+        assert_eq!(format(f64::INFINITY, Some(2), Some(",")), "inf");
+    }
+}
@@ -1,5 +1,5 @@
 use super::{string::RipString, table::Table, value::Value};
-use gc::Gc;
+use gc::{Gc, GcCell};
 
 /// A function evaluation scope.
 ///
@@ -23,6 +23,9 @@ pub(crate) struct Scope {
 
     /// The lexical parent scope to this one.
     pub(crate) parent: Option<Gc<Scope>>,
+
+    /// Blocks to run when the scope exits, in the order they were deferred.
+    pub(crate) deferred: GcCell<Vec<Value>>,
 }
 
 impl Scope {
@@ -0,0 +1,103 @@
+//! Suggestions for names that could not be found, usually because of a typo.
+//!
+//! Finding a suggestion means comparing against every name in scope, so this is
+//! only ever done once something has already gone wrong.
+
+use crate::{
+    alias,
+    fiber::Fiber,
+    string::RipString,
+    table::Table,
+    value::Value,
+};
+
+/// Find the name of the command closest to the given name, out of all the
+/// functions and aliases that could have been called in its place.
+pub(crate) fn command(fiber: &Fiber, name: &str) -> Option<RipString> {
+    // Paths are not looked up by name, so there is nothing to compare.
+    if name.contains('/') {
+        return None;
+    }
+
+    let mut candidates = Vec::new();
+    let mut scope = fiber.stack.last().cloned();
+
+    while let Some(current) = scope {
+        push_functions(&mut candidates, &current.bindings);
+        scope = current.parent.clone();
+    }
+
+    push_functions(&mut candidates, fiber.globals());
+    candidates.extend(alias::names(fiber));
+
+    closest(name.as_bytes(), candidates)
+}
+
+fn push_functions(candidates: &mut Vec<RipString>, table: &Table) {
+    for key in table.keys() {
+        if matches!(table.get(&key), Value::Block(_) | Value::ForeignFn(_)) {
+            candidates.push(key);
+        }
+    }
+}
+
+/// Pick the candidate with the smallest edit distance to a name, if any are
+/// close enough to be a likely typo. Ties are broken alphabetically so that
+/// the suggestion does not depend on table order.
+fn closest(name: &[u8], candidates: impl IntoIterator<Item = RipString>) -> Option<RipString> {
+    // Allow about one mistake for every three characters.
+    let threshold = (name.len() / 3).clamp(1, 3);
+
+    candidates
+        .into_iter()
+        .filter(|candidate| !candidate.as_bytes().starts_with(b"__") && candidate.as_bytes() != name)
+        .map(|candidate| (distance(name, candidate.as_bytes()), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by(|(a, a_name), (b, b_name)| a.cmp(b).then_with(|| a_name.as_bytes().cmp(b_name.as_bytes())))
+        .map(|(_, candidate)| candidate)
+}
+
+/// Compute the Levenshtein distance between two byte strings.
+fn distance(a: &[u8], b: &[u8]) -> usize {
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, &a_byte) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, &b_byte) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a_byte != b_byte);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_counts_edits() {
+        assert_eq!(distance(b"", b"abc"), 3);
+        assert_eq!(distance(b"echo", b"echo"), 0);
+        assert_eq!(distance(b"ehco", b"echo"), 2);
+        assert_eq!(distance(b"kitten", b"sitting"), 3);
+    }
+
+    #[test]
+    fn closest_prefers_smallest_distance() {
+        let candidates = ["print", "println", "printf"].map(RipString::from);
+
+        assert_eq!(closest(b"prinln", candidates.clone()).unwrap(), "println");
+        assert_eq!(closest(b"prnt", candidates).unwrap(), "print");
+    }
+
+    #[test]
+    fn closest_ignores_distant_and_hidden_names() {
+        assert_eq!(closest(b"ls", [RipString::from("which")]), None);
+        assert_eq!(closest(b"_aliases", [RipString::from("__aliases")]), None);
+    }
+}
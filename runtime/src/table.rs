@@ -1,9 +1,11 @@
+use super::exceptions::Exception;
 use super::string::RipString;
-use super::value::Value;
-use gc::{Gc, GcCell};
+use super::value::{self, Value};
+use gc::{custom_trace, Finalize, Gc, GcCell, Trace};
+use indexmap::{Equivalent, IndexMap};
 use std::{
-    collections::BTreeMap,
     fmt,
+    hash::{Hash, Hasher},
     iter::FromIterator,
 };
 
@@ -31,7 +33,9 @@ macro_rules! table {
 
 /// Implementation of a "table". Tables are used like a map or object.
 ///
-/// Only string keys are allowed.
+/// Keys may be any hashable value (see [`Value::is_hashable`]), though most
+/// tables only use string keys. Keys are kept in the order they were first
+/// inserted, so iterating over a table is deterministic.
 #[derive(Clone, gc::Finalize, gc::Trace)]
 pub struct Table {
     /// Tables are stored by reference instead of by value. We use reference
@@ -40,7 +44,85 @@ pub struct Table {
     ///
     /// Unlike all other value types, tables are internally mutable, so we are
     /// using a cell here to implement that.
-    inner: Gc<GcCell<BTreeMap<RipString, Value>>>,
+    inner: Gc<GcCell<Entries>>,
+}
+
+/// The entries of a table in insertion order.
+#[derive(Default)]
+struct Entries(IndexMap<Key, Value>);
+
+impl Finalize for Entries {}
+
+unsafe impl Trace for Entries {
+    custom_trace!(this, {
+        for (key, value) in this.0.iter() {
+            mark(&key.0);
+            mark(value);
+        }
+    });
+}
+
+/// A table key. Must only be constructed from a hashable value.
+#[derive(Clone)]
+struct Key(Value);
+
+impl Hash for Key {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl PartialEq for Key {
+    fn eq(&self, rhs: &Key) -> bool {
+        self.0 == rhs.0
+    }
+}
+
+// Hashable values are always equal to themselves.
+impl Eq for Key {}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Borrowed form of a key, for lookups without cloning the key.
+struct KeyRef<'a>(&'a Value);
+
+impl Hash for KeyRef<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.hash(state);
+    }
+}
+
+impl Equivalent<Key> for KeyRef<'_> {
+    fn equivalent(&self, key: &Key) -> bool {
+        *self.0 == key.0
+    }
+}
+
+/// Borrowed form of a string key, for lookups without allocating a string.
+struct StrKey<'a>(&'a [u8]);
+
+impl Hash for StrKey<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        value::hash_string(self.0, state);
+    }
+}
+
+impl Equivalent<Key> for StrKey<'_> {
+    fn equivalent(&self, key: &Key) -> bool {
+        key.0 == *self.0
+    }
+}
+
+fn check_hashable(key: &Value) -> Result<(), Exception> {
+    if key.is_hashable() {
+        Ok(())
+    } else {
+        Err(Exception::from(format!("cannot use a {} as a table key", key.type_name())))
+    }
 }
 
 impl Default for Table {
@@ -61,37 +143,87 @@ impl Table {
         &*self.inner as *const _ as usize
     }
 
-    /// Get the value indexed by a key.
+    /// Get the value indexed by a string key.
     ///
     /// If the key does not exist, `Nil` is returned.
     pub fn get(&self, key: impl AsRef<[u8]>) -> Value {
-        self.inner.borrow().get(key.as_ref()).cloned().unwrap_or(Value::Nil)
+        self.inner.borrow().0.get(&StrKey(key.as_ref())).cloned().unwrap_or(Value::Nil)
+    }
+
+    /// Get the value indexed by a key of any type.
+    ///
+    /// If the key does not exist, `Nil` is returned. Returns an error if the key
+    /// is not hashable.
+    pub fn get_value(&self, key: &Value) -> Result<Value, Exception> {
+        check_hashable(key)?;
+
+        Ok(self.inner.borrow().0.get(&KeyRef(key)).cloned().unwrap_or(Value::Nil))
     }
 
     /// Set the value for a given key, returning the old value.
     ///
-    /// If `Nil` is given as the value, the key is unset.
+    /// Overwriting an existing key keeps its original position. If `Nil` is
+    /// given as the value, the key is unset.
     pub fn set(&self, key: impl Into<RipString>, value: impl Into<Value>) -> Value {
-        let key = key.into();
-        let value = value.into();
+        self.set_key(Key(Value::String(key.into())), value.into())
+    }
 
+    /// Set the value for a key of any type, returning the old value.
+    ///
+    /// Behaves the same as [`Table::set`], but returns an error if the key is
+    /// not hashable.
+    pub fn set_value(&self, key: Value, value: impl Into<Value>) -> Result<Value, Exception> {
+        check_hashable(&key)?;
+
+        Ok(self.set_key(Key(key), value.into()))
+    }
+
+    fn set_key(&self, key: Key, value: Value) -> Value {
         match value {
-            Value::Nil => self.inner.borrow_mut().remove(key.as_bytes()).unwrap_or(Value::Nil),
-            value => self.inner.borrow_mut().insert(key, value).unwrap_or(Value::Nil),
+            Value::Nil => self.inner.borrow_mut().0.shift_remove(&key).unwrap_or(Value::Nil),
+            value => self.inner.borrow_mut().0.insert(key, value).unwrap_or(Value::Nil),
+        }
+    }
+
+    /// Create a new table with the same entries as this one. The values
+    /// themselves are not copied, so tables held by both are shared.
+    pub(crate) fn shallow_clone(&self) -> Self {
+        Self {
+            inner: Gc::new(GcCell::new(Entries(self.inner.borrow().0.clone()))),
         }
     }
 
+    /// Replace all of the entries of this table with the entries of another.
+    pub(crate) fn replace_entries(&self, other: &Table) {
+        let entries = other.inner.borrow().0.clone();
+
+        self.inner.borrow_mut().0 = entries;
+    }
+
+    /// Get the string keys of the table in insertion order.
+    ///
+    /// Keys of any other type are skipped. Use [`Table::all_keys`] to get every
+    /// key.
     pub fn keys(&self) -> impl Iterator<Item = RipString> {
-        self.inner.borrow().keys().cloned().collect::<Vec<RipString>>().into_iter()
+        self.inner.borrow().0.keys()
+            .filter_map(|key| key.0.as_string().cloned())
+            .collect::<Vec<RipString>>()
+            .into_iter()
+    }
+
+    /// Get all of the keys of the table in insertion order, whatever their
+    /// type.
+    pub fn all_keys(&self) -> impl Iterator<Item = Value> {
+        self.inner.borrow().0.keys().map(|key| key.0.clone()).collect::<Vec<Value>>().into_iter()
     }
 }
 
 impl<K: Into<RipString>, V: Into<Value>> FromIterator<(K, V)> for Table {
     fn from_iter<I: IntoIterator<Item=(K, V)>>(iter: I) -> Self {
         Self {
-            inner: Gc::new(GcCell::new(iter.into_iter()
-                .map(|(k, v)| (k.into(), v.into()))
-                .collect())),
+            inner: Gc::new(GcCell::new(Entries(iter.into_iter()
+                .map(|(k, v)| (Key(Value::String(k.into())), v.into()))
+                .collect()))),
         }
     }
 }
@@ -105,7 +237,7 @@ impl PartialEq for Table {
 
 impl fmt::Debug for Table {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        self.inner.borrow().fmt(f)
+        self.inner.borrow().0.fmt(f)
     }
 }
 
@@ -129,4 +261,48 @@ mod tests {
         assert!(table.get("foo") == "hello");
         assert!(table.get("foo") == Value::from("hello"));
     }
+
+    #[test]
+    fn keys_are_in_insertion_order() {
+        let table = Table::new();
+
+        table.set("c", 1.0);
+        table.set("a", 2.0);
+        table.set("b", 3.0);
+        table.set("a", 4.0);
+        table.set("c", Value::Nil);
+        table.set("c", 5.0);
+
+        assert_eq!(table.keys().collect::<Vec<_>>(), vec!["a", "b", "c"]);
+        assert!(table.get("a") == 4.0);
+    }
+
+    #[test]
+    fn hashable_keys() {
+        let table = Table::new();
+        let list = Value::from(vec![Value::from(1.0), Value::from("a")]);
+
+        table.set_value(Value::from(1.0), "number").unwrap();
+        table.set_value(Value::TRUE, "boolean").unwrap();
+        table.set_value(list.clone(), "list").unwrap();
+        table.set_value(Value::from("1"), "string").unwrap();
+        table.set_value(Value::from(0.0), "zero").unwrap();
+
+        assert!(table.get_value(&Value::from(1.0)).unwrap() == "number");
+        assert!(table.get_value(&Value::TRUE).unwrap() == "boolean");
+        assert!(table.get_value(&list).unwrap() == "list");
+        assert!(table.get("1") == "string");
+        assert!(table.get_value(&Value::from(-0.0)).unwrap() == "zero");
+        assert_eq!(table.all_keys().count(), 5);
+        assert_eq!(table.keys().collect::<Vec<_>>(), vec!["1"]);
+    }
+
+    #[test]
+    fn unhashable_keys_are_rejected() {
+        let table = Table::new();
+
+        assert!(table.set_value(Value::from(Table::new()), 1.0).is_err());
+        assert!(table.get_value(&Value::from(f64::NAN)).is_err());
+        assert!(table.get_value(&Value::from(vec![Value::from(Table::new())])).is_err());
+    }
 }
@@ -1,13 +1,16 @@
 //! Structures and implementations of the built-in data types.
 use super::{
     closure::Closure,
+    coerce,
     foreign::ForeignFn,
+    number,
     string::RipString,
     table::Table,
 };
 use regex::bytes::Regex;
 use std::{
     fmt,
+    hash::{Hash, Hasher},
     iter::FromIterator,
 };
 
@@ -80,16 +83,9 @@ impl Value {
 
     /// Determine if this expression is considered a truthy value.
     ///
-    /// Nil, the empty string, and the empty list are considered falsey, and all
-    /// other values are considered truthy.
+    /// See [`coerce::to_bool`] for the exact rules.
     pub fn is_truthy(&self) -> bool {
-        match self {
-            Value::Nil => false,
-            Value::Boolean(b) => *b,
-            Value::String(value) => !(value == "0" || value.as_bytes().is_empty() || &value.to_lowercase() == "false"),
-            Value::List(items) => !items.is_empty(),
-            _ => true,
-        }
+        coerce::to_bool(self)
     }
 
     /// If this value is a boolean, get its value.
@@ -116,6 +112,11 @@ impl Value {
         }
     }
 
+    /// If this value is a string, get its raw bytes.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        self.as_string().map(RipString::as_bytes)
+    }
+
     /// If this value is a list, get its contents.
     pub fn as_list(&self) -> Option<&[Self]> {
         match self {
@@ -124,6 +125,20 @@ impl Value {
         }
     }
 
+    /// Check if this value can be hashed, and so can be used as a table key.
+    ///
+    /// Booleans, numbers other than `NaN`, strings, and lists containing only
+    /// hashable values are hashable. Tables are mutable, and blocks and
+    /// functions have no meaningful notion of equality, so they are not.
+    pub fn is_hashable(&self) -> bool {
+        match self {
+            Value::Boolean(_) | Value::String(_) => true,
+            Value::Number(number) => !number.is_nan(),
+            Value::List(items) => items.iter().all(Value::is_hashable),
+            _ => false,
+        }
+    }
+
     /// If this value is a table, get a reference to it.
     pub fn as_table(&self) -> Option<Table> {
         match self {
@@ -150,6 +165,41 @@ impl Value {
     }
 }
 
+/// Equal values always produce the same hash. Values that are not hashable
+/// according to [`Value::is_hashable`] only hash their type.
+impl Hash for Value {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            Value::Boolean(b) => {
+                self.type_name().hash(state);
+                b.hash(state);
+            }
+            Value::Number(number) => {
+                self.type_name().hash(state);
+
+                // Zero and negative zero are equal, so must hash the same.
+                if *number == 0.0 { 0.0f64 } else { *number }.to_bits().hash(state);
+            }
+            Value::String(string) => hash_string(string.as_bytes(), state),
+            Value::List(items) => {
+                self.type_name().hash(state);
+                items.len().hash(state);
+
+                for item in items {
+                    item.hash(state);
+                }
+            }
+            _ => self.type_name().hash(state),
+        }
+    }
+}
+
+/// Hash a string the same way as [`Value::hash`], without needing a value.
+pub(crate) fn hash_string<H: Hasher>(bytes: &[u8], state: &mut H) {
+    "string".hash(state);
+    bytes.hash(state);
+}
+
 impl From<bool> for Value {
     fn from(value: bool) -> Self {
         Value::Boolean(value)
@@ -242,10 +292,7 @@ impl From<ForeignFn> for Value {
 
 impl From<Value> for RipString {
     fn from(value: Value) -> Self {
-        match &value {
-            Value::String(s) => s.clone(),
-            value => value.to_string().into(),
-        }
+        coerce::to_string(&value)
     }
 }
 
@@ -322,7 +369,7 @@ impl fmt::Debug for Value {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Value::Boolean(boolean) => write!(f, "{}", boolean),
-            Value::Number(number) => write!(f, "{}", number),
+            Value::Number(number) => f.write_str(&number::display(*number)),
             Value::String(string) => write!(f, "\"{}\"", string),
             Value::Block(closure) => match closure.name() {
                 Some(name) => write!(f, "<closure {}>", name),
@@ -338,7 +385,7 @@ impl fmt::Display for Value {
         match self {
             Value::Nil => write!(f, "nil"),
             Value::Boolean(boolean) => write!(f, "{}", boolean),
-            Value::Number(number) => write!(f, "{}", number),
+            Value::Number(number) => f.write_str(&number::display(*number)),
             Value::String(string) => write!(f, "{}", string),
             Value::Regex(regex) => write!(f, "{}", regex),
             Value::List(items) => {
@@ -0,0 +1,55 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn alias_expands_with_arguments() {
+    let result = eval(r#"
+        $greet = <greeting, name> {
+            return "$greeting, $name"
+        }
+
+        alias hi "greet hello"
+        hi world
+    "#).await;
+
+    assert_eq!(result.unwrap(), "hello, world");
+}
+
+#[tokio::test]
+async fn alias_chains_and_stops_at_cycles() {
+    let result = eval(r#"
+        alias a "b"
+        alias b "a"
+        a
+    "#).await;
+
+    // The cycle ends by running `a` as an external command, which does not
+    // exist.
+    assert_eq!(result.unwrap_err().message().to_string(), "no such command or file, did you mean 'b'?");
+
+    let result = eval(r#"
+        $echo-args = <...args> { return $args }
+        alias a "b one"
+        alias b "echo-args two"
+        a three
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::from(vec![Value::from("two"), Value::from("one"), Value::from("three")]));
+}
+
+#[tokio::test]
+async fn alias_list_and_unalias() {
+    let result = eval(r#"
+        alias ll "ls -l"
+        alias la "ls -a"
+        unalias la
+        alias
+    "#).await.unwrap();
+
+    assert_eq!(result.get("ll"), "ls -l");
+    assert!(result.get("la").is_nil());
+
+    assert!(eval(r#"alias bad "ls | grep x""#).await.is_err());
+}
@@ -0,0 +1,51 @@
+use riptide_runtime::io::{BufferMode, BufferedOutput};
+use std::time::Duration;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    time::timeout,
+};
+
+async fn is_empty(pipe: &mut (impl AsyncRead + Unpin)) -> bool {
+    timeout(Duration::from_millis(20), pipe.read(&mut [0; 16])).await.is_err()
+}
+
+async fn read_string(pipe: &mut (impl AsyncRead + Unpin), len: usize) -> String {
+    let mut buf = vec![0; len];
+    pipe.read_exact(&mut buf).await.unwrap();
+    String::from_utf8(buf).unwrap()
+}
+
+#[tokio::test]
+async fn block_mode_waits_for_flush() {
+    let (mut read, write) = tokio_pipe::pipe().unwrap();
+    let mut output = BufferedOutput::new(Box::new(write), BufferMode::Block);
+
+    output.write_all(b"hello\nworld").await.unwrap();
+    assert!(is_empty(&mut read).await);
+
+    output.flush().await.unwrap();
+    assert_eq!(read_string(&mut read, 11).await, "hello\nworld");
+}
+
+#[tokio::test]
+async fn line_mode_writes_completed_lines() {
+    let (mut read, write) = tokio_pipe::pipe().unwrap();
+    let mut output = BufferedOutput::new(Box::new(write), BufferMode::Line);
+
+    output.write_all(b"hello").await.unwrap();
+    assert!(is_empty(&mut read).await);
+
+    output.write_all(b" world\n").await.unwrap();
+    assert_eq!(read_string(&mut read, 12).await, "hello world\n");
+}
+
+#[tokio::test]
+async fn dropping_writes_buffered_data() {
+    let (mut read, write) = tokio_pipe::pipe().unwrap();
+    let mut output = BufferedOutput::new(Box::new(write), BufferMode::Block);
+
+    output.write_all(b"bye").await.unwrap();
+    drop(output);
+
+    assert_eq!(read_string(&mut read, 3).await, "bye");
+}
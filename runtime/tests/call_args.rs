@@ -0,0 +1,47 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn arg_returns_positional_argument_or_default() {
+    let result = eval(r#"
+        $f = { return [$(arg 0) $(arg 1 fallback) $(arg 2)] }
+        f first
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::from(vec![Value::from("first"), Value::from("fallback"), Value::Nil]));
+}
+
+#[tokio::test]
+async fn arg_count_counts_arguments() {
+    assert_eq!(eval("$f = { return $(arg-count) }; f a b c").await.unwrap(), 3.0);
+    assert_eq!(eval("$f = { return $(arg-count) }; f").await.unwrap(), 0.0);
+}
+
+#[tokio::test]
+async fn opt_reads_named_arguments() {
+    let result = eval(r#"
+        $f = { return [$(opt level) $(opt --verbose) $(opt color auto)] }
+        f --level warn --verbose
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::from(vec![Value::from("warn"), Value::TRUE, Value::from("auto")]));
+}
+
+#[tokio::test]
+async fn opt_reads_flags_from_positional_arguments() {
+    let result = eval(r#"
+        $f = { return [$(opt level) $(opt verbose) $(opt missing)] }
+        call $f [--level warn --verbose]
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::from(vec![Value::from("warn"), Value::TRUE, Value::Nil]));
+}
+
+#[tokio::test]
+async fn arg_rejects_bad_index() {
+    let error = eval("$f = { arg -1 }; f").await.unwrap_err();
+
+    assert!(error.message().to_string().starts_with("index must be"));
+}
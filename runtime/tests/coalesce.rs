@@ -0,0 +1,26 @@
+mod common;
+
+use common::eval;
+
+#[tokio::test]
+async fn nil_uses_right_hand_side() {
+    assert_eq!(eval("return $undefined ?? default").await.unwrap(), "default");
+    assert_eq!(eval("$x = value; return $x ?? default").await.unwrap(), "value");
+}
+
+#[tokio::test]
+async fn right_hand_side_is_not_evaluated_unless_needed() {
+    let result = eval("$x = value; return $x ?? $(throw 'evaluated')").await;
+
+    assert_eq!(result.unwrap(), "value");
+
+    let error = eval("return $undefined ?? $(throw 'evaluated')").await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "evaluated");
+}
+
+#[tokio::test]
+async fn chain_uses_first_value_that_is_not_nil() {
+    assert_eq!(eval("$b = second; return $a ?? $b ?? third").await.unwrap(), "second");
+    assert_eq!(eval("return $a ?? $b ?? third").await.unwrap(), "third");
+}
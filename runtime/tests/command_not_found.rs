@@ -0,0 +1,37 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn handler_receives_name_and_args() {
+    let result = eval(r#"
+        $GLOBALS->command-not-found = <...args> { return $args }
+        riptide-no-such-command one two
+    "#).await;
+
+    assert_eq!(
+        result.unwrap(),
+        Value::from(vec![Value::from("riptide-no-such-command"), Value::from("one"), Value::from("two")]),
+    );
+}
+
+#[tokio::test]
+async fn nil_from_handler_throws_usual_error() {
+    let error = eval(r#"
+        $GLOBALS->command-not-found = {}
+        riptide-no-such-command
+    "#).await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "no such command or file");
+}
+
+#[tokio::test]
+async fn handler_is_not_called_for_existing_commands() {
+    let result = eval(r#"
+        $GLOBALS->command-not-found = { throw 'handler called' }
+        true
+    "#).await;
+
+    assert!(result.is_ok());
+}
@@ -0,0 +1,22 @@
+//! Helpers shared by the runtime tests.
+
+#![allow(dead_code)]
+
+use riptide_runtime::prelude::*;
+use tokio::task::LocalSet;
+
+/// Evaluate a script in a new runtime with all builtins imported.
+///
+/// The script is run in a local task set, so that it can run pipelines and
+/// background jobs.
+pub async fn eval(script: &str) -> Result<Value, Exception> {
+    LocalSet::new().run_until(riptide_runtime::eval(&format!("
+        import 'builtins' for *
+        {}
+    ", script))).await
+}
+
+/// Create a list of numbers.
+pub fn numbers(items: &[f64]) -> Value {
+    items.iter().copied().map(Value::from).collect::<Vec<_>>().into()
+}
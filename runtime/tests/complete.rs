@@ -0,0 +1,52 @@
+use riptide_runtime::{completion, prelude::*};
+
+#[tokio::test]
+async fn complete_registers_function_by_command_name() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+
+    fiber.execute(None, r#"
+        import 'builtins' for complete
+        complete git <...words> {
+            return [add commit push]
+        }
+    "#).await.unwrap();
+
+    let function = completion::get(&fiber, "git").unwrap();
+    assert_eq!(function.type_name(), "block");
+    assert_eq!(
+        fiber.invoke(&function, &[Value::from("git"), Value::from("")]).await.unwrap(),
+        Value::from(vec![Value::from("add"), Value::from("commit"), Value::from("push")]),
+    );
+
+    assert!(completion::get(&fiber, "hg").is_none());
+
+    fiber.execute(None, r#"
+        import 'builtins' for complete nil
+        complete git (nil)
+    "#).await.unwrap();
+
+    assert!(completion::get(&fiber, "git").is_none());
+}
+
+#[tokio::test]
+async fn complete_requires_a_block() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for complete
+        complete git "add commit"
+    "#).await;
+
+    assert!(result.unwrap_err().message().to_string().contains("must be a block"));
+}
+
+#[tokio::test]
+async fn listing_completions_does_not_register_anything() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+
+    fiber.execute(None, r#"
+        import 'builtins' for complete
+        complete
+        complete git
+    "#).await.unwrap();
+
+    assert!(fiber.globals().get("__completions").is_nil());
+}
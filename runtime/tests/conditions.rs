@@ -0,0 +1,76 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn and_runs_next_only_after_success() {
+    let result = eval(r#"
+        $ran = [:]
+        sh -c 'exit 0' && table-set $ran first yes
+        sh -c 'exit 1' && table-set $ran second yes
+        return $ran
+    "#).await.unwrap();
+
+    let ran = result.as_table().unwrap();
+    assert_eq!(ran.get("first"), "yes");
+    assert_eq!(ran.get("second"), Value::Nil);
+}
+
+#[tokio::test]
+async fn or_runs_next_only_after_failure() {
+    let result = eval(r#"
+        $ran = [:]
+        sh -c 'exit 0' || table-set $ran skipped yes
+        sh -c 'exit 2' || table-set $ran fallback yes
+        return $ran
+    "#).await.unwrap();
+
+    let ran = result.as_table().unwrap();
+    assert_eq!(ran.get("skipped"), Value::Nil);
+    assert_eq!(ran.get("fallback"), "yes");
+}
+
+#[tokio::test]
+async fn chain_evaluates_to_last_result_run() {
+    let result = eval(r#"
+        $succeed = { return ok }
+        $fail = { return false }
+
+        return [
+            $(call { succeed && call { return second } })
+            $(call { fail && call { return skipped } })
+            $(call { fail || call { return fallback } })
+            $(call { fail && call { return skipped } || call { return recovered } })
+        ]
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::from(vec![
+        Value::from("second"),
+        Value::from("false"),
+        Value::from("fallback"),
+        Value::from("recovered"),
+    ]));
+}
+
+#[tokio::test]
+async fn numbers_are_truthy() {
+    let result = eval(r#"
+        $zero = { return 0 }
+
+        return $(call { zero && call { return ran } })
+    "#).await.unwrap();
+
+    assert_eq!(result, "ran");
+}
+
+#[tokio::test]
+async fn status_is_kept_when_no_command_runs() {
+    let result = eval(r#"
+        sh -c 'exit 3'
+        call { return ok } && call { return ok }
+        return $?
+    "#).await.unwrap();
+
+    assert_eq!(result, 3.0);
+}
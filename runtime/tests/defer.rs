@@ -0,0 +1,66 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn deferred_blocks_run_in_reverse_order_on_exit() {
+    let result = eval(r#"
+        $t = [log: start]
+        call {
+            defer { $t->log = [$t->log first] }
+            defer { $t->log = [$t->log second] }
+            $t->log = [$t->log body]
+        }
+        return $t->log
+    "#).await.unwrap();
+
+    let log = |a: Value, b: &str| Value::from(vec![a, Value::from(b)]);
+    let expected = log(log(log(Value::from("start"), "body"), "second"), "first");
+
+    assert_eq!(result, expected);
+}
+
+#[tokio::test]
+async fn deferred_blocks_run_after_return() {
+    let result = eval(r#"
+        $t = [done: false]
+        $value = $(call {
+            defer { $t->done = true }
+            return early
+        })
+        return [$value $t->done]
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::from(vec![Value::from("early"), Value::from("true")]));
+}
+
+#[tokio::test]
+async fn deferred_blocks_run_when_exception_is_thrown() {
+    let result = eval(r#"
+        $t = [done: false]
+        $message = $(try {
+            defer { $t->done = true }
+            throw oops
+        } <e> { return $e })
+        return [$message $t->done]
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::from(vec![Value::from("oops"), Value::from("true")]));
+}
+
+#[tokio::test]
+async fn deferred_exception_does_not_mask_original() {
+    let error = eval(r#"
+        call {
+            defer { throw cleanup }
+            throw original
+        }
+    "#).await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "original");
+
+    let error = eval("call { defer { throw cleanup } }").await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "cleanup");
+}
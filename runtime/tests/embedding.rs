@@ -0,0 +1,76 @@
+use riptide_runtime::{prelude::*, table, ForeignFn};
+use std::{cell::Cell, rc::Rc};
+
+async fn double(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    match args.first().and_then(Value::as_number) {
+        Some(number) => Ok(Value::from(number * 2.0)),
+        None => Ok(Value::Nil),
+    }
+}
+
+#[tokio::test]
+async fn register_fn_defines_global() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    fiber.register_fn("double", double).unwrap();
+
+    assert_eq!(fiber.execute(None, "double 21").await.unwrap(), 42.0);
+    assert!(fiber.register_fn("double", double).is_err());
+}
+
+#[tokio::test]
+async fn register_table_module_can_be_imported() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    fiber.register_table_module("host", table! {
+        "double" => Value::foreign_fn(double),
+    }).unwrap();
+
+    let result = fiber.execute(None, "
+        import 'host' for double
+        double 4
+    ").await;
+
+    assert_eq!(result.unwrap(), 8.0);
+    assert!(fiber.register_table_module("host", table!()).is_err());
+}
+
+#[tokio::test]
+async fn closure_can_capture_host_state() {
+    let counter = Rc::new(Cell::new(0u32));
+    let mut fiber = riptide_runtime::init().await.unwrap();
+
+    fiber.register_fn("count", ForeignFn::from_closure({
+        let counter = counter.clone();
+
+        move |_, _| {
+            let counter = counter.clone();
+
+            Box::pin(async move {
+                counter.set(counter.get() + 1);
+                Ok(counter.get().into())
+            })
+        }
+    })).unwrap();
+
+    assert_eq!(fiber.execute(None, "count; count").await.unwrap(), 2.0);
+    assert_eq!(counter.get(), 2);
+}
+
+async fn explode(_: &mut Fiber, _: Vec<Value>) -> Result<Value, Exception> {
+    panic!("kaboom");
+}
+
+#[tokio::test]
+async fn panic_in_native_function_becomes_exception() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    fiber.register_fn("explode", explode).unwrap();
+
+    let error = fiber.execute(None, "explode").await.unwrap_err();
+    assert!(error.message().to_string().contains("kaboom"));
+
+    let result = fiber.execute(None, "
+        import 'builtins' for try
+        try { explode } { return recovered }
+    ").await;
+
+    assert_eq!(result.unwrap(), "recovered");
+}
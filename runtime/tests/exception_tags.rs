@@ -0,0 +1,66 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn tagged_exception_carries_tag() {
+    let error = eval("throw io-error 'file not found'").await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "file not found");
+    assert_eq!(error.tag().unwrap(), "io-error");
+
+    let error = eval("throw 'file not found'").await.unwrap_err();
+
+    assert!(error.tag().is_none());
+}
+
+#[tokio::test]
+async fn catch_block_receives_tag() {
+    let result = eval(r#"
+        return $(try {
+            throw io-error oops
+        } <message, tag> {
+            return [$message $tag]
+        })
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::from(vec![Value::from("oops"), Value::from("io-error")]));
+}
+
+#[tokio::test]
+async fn try_with_type_only_catches_matching_tag() {
+    let result = eval(r#"
+        return $(try --type io-error {
+            throw io-error oops
+        } <message> {
+            return caught
+        })
+    "#).await;
+
+    assert_eq!(result.unwrap(), "caught");
+
+    let error = eval(r#"
+        try --type io-error {
+            throw parse-error oops
+        } {
+            return caught
+        }
+    "#).await.unwrap_err();
+
+    assert_eq!(error.tag().unwrap(), "parse-error");
+}
+
+#[tokio::test]
+async fn try_with_type_does_not_catch_untagged() {
+    let error = eval("try --type io-error { throw oops } { return caught }").await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "oops");
+}
+
+#[tokio::test]
+async fn try_without_type_catches_everything() {
+    let result = eval("return $(try { throw io-error oops } <message> { return $message })").await;
+
+    assert_eq!(result.unwrap(), "oops");
+}
@@ -0,0 +1,43 @@
+mod common;
+
+use common::eval;
+
+#[tokio::test]
+async fn foreach_visits_each_item_of_a_list() {
+    let result = eval(r#"
+        $t = [:]
+        foreach [a b c] <item> {
+            $t->last = $item
+            table-set $t $item yes
+        }
+        return $t
+    "#).await.unwrap();
+
+    let t = result.as_table().unwrap();
+    assert_eq!(t.get("last"), "c");
+    assert_eq!(t.get("a"), "yes");
+    assert_eq!(t.get("b"), "yes");
+}
+
+#[tokio::test]
+async fn foreach_visits_each_character_of_a_string() {
+    let result = eval(r#"
+        $t = [:]
+        foreach 'hé!' <c> {
+            $t->last = $c
+            table-set $t $c yes
+        }
+        return $t
+    "#).await.unwrap();
+
+    let t = result.as_table().unwrap();
+    assert_eq!(t.get("é"), "yes");
+    assert_eq!(t.get("last"), "!");
+    assert_eq!(t.keys().count(), 4);
+}
+
+#[tokio::test]
+async fn foreach_requires_a_list_or_string() {
+    assert!(eval("foreach [:] { }").await.is_err());
+    assert!(eval("foreach [a]").await.is_err());
+}
@@ -0,0 +1,49 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn subroutine_doc_string_is_stored_on_closure() {
+    let result = eval(r#"
+        $greet = sub greet {
+            "Says hello to someone."
+            return hello
+        }
+        return $greet
+    "#).await.unwrap();
+
+    match result {
+        Value::Block(closure) => assert_eq!(closure.doc(), Some("Says hello to someone.")),
+        value => panic!("not a block: {:?}", value),
+    }
+}
+
+#[tokio::test]
+async fn doc_string_is_not_evaluated() {
+    let result = eval(r#"
+        $greet = sub greet {
+            'Says hello.'
+            return hello
+        }
+        greet
+    "#).await;
+
+    assert_eq!(result.unwrap(), "hello");
+}
+
+#[tokio::test]
+async fn help_throws_without_documentation() {
+    let error = eval(r#"
+        $greet = sub greet {
+            return hello
+        }
+        help greet
+    "#).await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "no documentation for 'greet'");
+
+    let error = eval("help riptide-no-such-function").await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "no function named 'riptide-no-such-function'");
+}
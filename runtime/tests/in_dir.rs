@@ -0,0 +1,47 @@
+use riptide_runtime::Value;
+use std::env;
+
+#[tokio::test]
+async fn in_dir_sets_cwd_for_block() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    let original = fiber.current_dir();
+    let dir = env::temp_dir().canonicalize().unwrap();
+
+    fiber.globals().set("dir", dir.clone());
+
+    let result = fiber.execute(None, r#"
+        import 'builtins' for in-dir
+        in-dir $dir { pwd }
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::from(dir));
+    assert_eq!(fiber.current_dir(), original);
+}
+
+#[tokio::test]
+async fn in_dir_restores_cwd_after_throw() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    let original = fiber.current_dir();
+
+    fiber.globals().set("dir", env::temp_dir());
+
+    let error = fiber.execute(None, r#"
+        import 'builtins' for in-dir throw
+        in-dir $dir { throw oops }
+    "#).await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "oops");
+    assert_eq!(fiber.current_dir(), original);
+}
+
+#[tokio::test]
+async fn in_dir_requires_a_directory() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+
+    let error = fiber.execute(None, r#"
+        import 'builtins' for in-dir
+        in-dir riptide-no-such-dir { pwd }
+    "#).await.unwrap_err();
+
+    assert!(error.message().to_string().starts_with("not a directory: "));
+}
@@ -0,0 +1,29 @@
+use riptide_runtime::{prelude::*, table};
+
+async fn fiber() -> Fiber {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    fiber.globals().set("env", table! {
+        "HOME" => "/home/riptide",
+        "PATH" => "/usr/bin",
+    });
+    fiber
+}
+
+#[tokio::test]
+async fn interpolation_falls_back_to_environment() {
+    let result = fiber().await.execute(None, r#"
+        return "$HOME/bin:${PATH}"
+    "#).await;
+
+    assert_eq!(result.unwrap(), "/home/riptide/bin:/usr/bin");
+}
+
+#[tokio::test]
+async fn scope_variables_shadow_environment() {
+    let result = fiber().await.execute(None, r#"
+        $HOME = "/tmp"
+        return "$HOME ${HOME}"
+    "#).await;
+
+    assert_eq!(result.unwrap(), "/tmp /tmp");
+}
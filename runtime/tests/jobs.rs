@@ -0,0 +1,110 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::Value;
+use tokio::task::LocalSet;
+
+#[tokio::test]
+async fn background_job_returns_handle_and_wait_returns_status() {
+    LocalSet::new().run_until(async {
+        let mut fiber = riptide_runtime::init().await.unwrap();
+
+        let job = fiber.execute(None, "sh -c 'exit 4' &").await.unwrap();
+        assert_eq!(job.get("id"), 1.0);
+        assert_eq!(job.get("status"), "running");
+
+        fiber.globals().set("job", job.clone());
+
+        let status = fiber.execute(None, r#"
+            import 'builtins' for wait
+            wait $job
+        "#).await.unwrap();
+
+        assert_eq!(status, 4.0);
+        assert_eq!(job.get("status"), "done");
+    }).await;
+}
+
+#[tokio::test]
+async fn jobs_lists_background_jobs() {
+    let result = eval(r#"
+        import 'builtins' for jobs wait
+
+        sh -c 'exit 0' &
+        sh -c 'sleep 0.1; exit 1' &
+        wait
+        jobs
+    "#).await.unwrap();
+
+    let jobs = result.as_list().unwrap();
+
+    assert_eq!(jobs.len(), 2);
+    assert_eq!(jobs[0].get("id"), 1.0);
+    assert_eq!(jobs[1].get("exit-code"), 1.0);
+    assert!(jobs.iter().all(|job| job.get("status") == "done"));
+}
+
+#[tokio::test]
+async fn failing_job_has_nonzero_status() {
+    let result = eval(r#"
+        import 'builtins' for throw wait
+
+        throw oops &
+        wait 1
+    "#).await.unwrap();
+
+    assert_eq!(result, 1.0);
+}
+
+#[tokio::test]
+async fn wait_unknown_job_throws() {
+    let result = eval(r#"
+        import 'builtins' for wait
+        wait 42
+    "#).await;
+
+    assert!(result.unwrap_err().message().to_string().contains("no such job"));
+}
+
+#[tokio::test]
+async fn background_job_outside_local_set_throws() {
+    let result = riptide_runtime::eval("sh -c 'exit 0' &").await;
+
+    assert!(result.unwrap_err().message().to_string().contains("local task set"));
+}
+
+#[tokio::test]
+async fn jobs_is_empty_by_default() {
+    let result = eval(r#"
+        import 'builtins' for jobs
+        jobs
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::List(Vec::new()));
+}
+
+#[tokio::test]
+async fn wait_on_tracked_process_by_pid() {
+    let child = std::process::Command::new("sh").args(["-c", "exit 3"]).spawn().unwrap();
+    let pid = child.id();
+
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    let job = riptide_runtime::jobs::track_process(&fiber, pid as i32);
+    fiber.globals().set("pid", pid);
+
+    let status = fiber.execute(None, r#"
+        import 'builtins' for wait
+        wait $pid
+    "#).await.unwrap();
+
+    assert_eq!(status, 3.0);
+    assert_eq!(job.get("status"), "done");
+
+    // The process has been reaped, so the cached status is returned.
+    let status = fiber.execute(None, r#"
+        import 'builtins' for wait
+        wait $pid
+    "#).await.unwrap();
+
+    assert_eq!(status, 3.0);
+}
@@ -0,0 +1,40 @@
+use riptide_runtime::{prelude::*, RipString};
+
+#[tokio::test]
+async fn load_preserves_invalid_utf8_in_strings() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    fiber.globals().set("script", RipString::from(b"return ['a\xff' b\xfe \"c\xfd\"]".to_vec()));
+
+    let result = fiber.execute(None, r#"
+        import 'builtins' for call load
+
+        call (load $script)
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::from(vec![
+        Value::from(RipString::from(b"a\xff".to_vec())),
+        Value::from(RipString::from(b"b\xfe".to_vec())),
+        Value::from(RipString::from(b"c\xfd".to_vec())),
+    ]));
+}
+
+#[tokio::test]
+async fn interpolation_preserves_byte_escapes() {
+    let result = riptide_runtime::eval(r#"
+        $x = "\xfe"
+        return "\xff-\x41-\xzz-$x"
+    "#).await.unwrap();
+
+    assert_eq!(result, RipString::from(b"\xff-A-xzz-\xfe".to_vec()));
+}
+
+#[tokio::test]
+async fn private_use_characters_in_source_are_preserved() {
+    let result = riptide_runtime::eval("return ['\u{10ff80}' \u{10ffff} \"\u{10ff80}\"]").await.unwrap();
+
+    assert_eq!(result, Value::from(vec![
+        Value::from("\u{10ff80}"),
+        Value::from("\u{10ffff}"),
+        Value::from("\u{10ff80}"),
+    ]));
+}
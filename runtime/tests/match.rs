@@ -0,0 +1,47 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn first_matching_arm_is_run() {
+    let script = r#"
+        return match $subject {
+            'a' => { return first }
+            42 => { return second }
+            42 => { return third }
+            _ => { return default }
+        }
+    "#;
+
+    assert_eq!(eval(&format!("$subject = a\n{}", script)).await.unwrap(), "first");
+    assert_eq!(eval(&format!("$subject = 42\n{}", script)).await.unwrap(), "second");
+    assert_eq!(eval(&format!("$subject = b\n{}", script)).await.unwrap(), "default");
+}
+
+#[tokio::test]
+async fn no_matching_arm_is_nil() {
+    let result = eval("return match b { a => { return a } }").await.unwrap();
+
+    assert_eq!(result, Value::Nil);
+}
+
+#[tokio::test]
+async fn numbers_do_not_match_strings() {
+    let result = eval("return match '1' { 1 => { return number } _ => { return string } }").await.unwrap();
+
+    assert_eq!(result, "string");
+}
+
+#[tokio::test]
+async fn match_statement_runs_arm_without_calling_result() {
+    let result = eval(r#"
+        $log = [:]
+        match yes {
+            yes => { $log->result = matched }
+        }
+        return $log->result
+    "#).await.unwrap();
+
+    assert_eq!(result, "matched");
+}
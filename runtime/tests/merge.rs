@@ -0,0 +1,74 @@
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn later_tables_override_earlier_ones() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for merge
+
+        $a = [x: 1 y: 2]
+        $b = [y: 3 z: 4]
+        $c = [z: 5]
+        $merged = (merge $a $b $c)
+
+        return [$merged->x $merged->y $merged->z $a->y $b->z]
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::from(vec![
+        Value::from(1.0),
+        Value::from(3.0),
+        Value::from(5.0),
+        Value::from(2.0),
+        Value::from(4.0),
+    ]));
+}
+
+#[tokio::test]
+async fn shallow_merge_replaces_nested_tables() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for merge
+
+        $merged = (merge [db: [host: a port: 1]] [db: [host: b]])
+        return $merged->db->port
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::Nil);
+}
+
+#[tokio::test]
+async fn deep_merge_recurses_into_nested_tables() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for merge
+
+        $a = [db: [host: a port: 1]]
+        $merged = (merge --deep $a [db: [host: b]])
+
+        return [$merged->db->host $merged->db->port $a->db->host]
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::from(vec![Value::from("b"), Value::from(1.0), Value::from("a")]));
+}
+
+#[tokio::test]
+async fn nested_tables_are_shared_with_inputs() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for merge
+
+        $a = [db: [host: a]]
+        $merged = (merge $a [port: 1])
+        $merged->db->host = b
+
+        return $a->db->host
+    "#).await.unwrap();
+
+    assert_eq!(result, "b");
+}
+
+#[tokio::test]
+async fn merging_a_non_table_throws() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for merge
+        merge [a: 1] [1 2]
+    "#).await;
+
+    assert!(result.unwrap_err().message().to_string().contains("cannot merge a list"));
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use common::eval;
+
+#[tokio::test]
+async fn min_max_of_arguments_or_list() {
+    assert_eq!(eval("min 3 1.5 2").await.unwrap(), 1.5);
+    assert_eq!(eval("max 3 1.5 2").await.unwrap(), 3.0);
+    assert_eq!(eval("min [4 2 8]").await.unwrap(), 2.0);
+    assert_eq!(eval("max [4 2 8]").await.unwrap(), 8.0);
+    assert_eq!(eval("max 7").await.unwrap(), 7.0);
+}
+
+#[tokio::test]
+async fn min_max_by_key() {
+    let result = eval("max --by <item> { return $item->size } [[size: 1] [size: 5] [size: 3]]").await.unwrap();
+    assert_eq!(result.get("size"), 5.0);
+
+    let result = eval("min [[size: 2] [size: 1]] --by <item> { return $item->size }").await.unwrap();
+    assert_eq!(result.get("size"), 1.0);
+}
+
+#[tokio::test]
+async fn min_max_reject_empty_and_non_numbers() {
+    assert!(eval("min").await.is_err());
+    assert!(eval("max []").await.is_err());
+    assert!(eval("max 1 '2'").await.is_err());
+}
@@ -0,0 +1,96 @@
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn named_args_are_bound_to_opts() {
+    let result = riptide_runtime::eval(r#"
+        $test = {
+            return $opts->level
+        }
+
+        test --level warn "message"
+    "#).await;
+
+    assert_eq!(result.unwrap(), "warn");
+}
+
+#[tokio::test]
+async fn named_args_are_separate_from_positional_args() {
+    let result = riptide_runtime::eval(r#"
+        $test = <message> {
+            return $message
+        }
+
+        test --level warn "message"
+    "#).await;
+
+    assert_eq!(result.unwrap(), "message");
+}
+
+#[tokio::test]
+async fn named_arg_without_value_is_a_flag() {
+    let result = riptide_runtime::eval(r#"
+        $test = {
+            return $opts->verbose
+        }
+
+        test --verbose
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::TRUE);
+}
+
+#[tokio::test]
+async fn named_args_are_passed_through_to_native_functions() {
+    async fn echo_args(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+        Ok(args.into())
+    }
+
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    fiber.globals().set("echo-args", Value::foreign_fn(echo_args));
+
+    let result = fiber.execute(None, "echo-args a --level warn --verbose b").await.unwrap();
+    let args = result.as_list().unwrap();
+
+    assert_eq!(args.len(), 5);
+    assert_eq!(args[0], "a");
+    assert_eq!(args[1], "--level");
+    assert_eq!(args[2], "warn");
+    assert_eq!(args[3], "--verbose");
+    assert_eq!(args[4], "b");
+}
+
+#[tokio::test]
+async fn named_arg_with_true_value_keeps_its_value() {
+    async fn echo_args(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+        Ok(args.into())
+    }
+
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    fiber.globals().set("echo-args", Value::foreign_fn(echo_args));
+    fiber.globals().set("yes", Value::TRUE);
+
+    let result = fiber.execute(None, "echo-args --force $yes --verbose").await.unwrap();
+    let args = result.as_list().unwrap();
+
+    assert_eq!(args.len(), 3);
+    assert_eq!(args[0], "--force");
+    assert_eq!(args[1], Value::TRUE);
+    assert_eq!(args[2], "--verbose");
+}
+
+#[tokio::test]
+async fn opts_can_be_forwarded_with_splat() {
+    let result = riptide_runtime::eval(r#"
+        $inner = {
+            return $opts->level
+        }
+
+        $outer = {
+            inner ...$opts
+        }
+
+        outer --level warn
+    "#).await;
+
+    assert_eq!(result.unwrap(), "warn");
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use riptide_runtime::prelude::*;
+
+async fn number_format(args: &str) -> Result<Value, Exception> {
+    common::eval(&format!("number-format {}", args)).await
+}
+
+#[tokio::test]
+async fn number_format_defaults_to_display() {
+    assert_eq!(number_format("1000000").await.unwrap(), "1000000");
+    assert_eq!(number_format("2.5").await.unwrap(), "2.5");
+}
+
+#[tokio::test]
+async fn number_format_with_options() {
+    assert_eq!(number_format("--decimals 2 3.14159").await.unwrap(), "3.14");
+    assert_eq!(number_format("--separator ',' 1234567").await.unwrap(), "1,234,567");
+    assert_eq!(number_format("1234.5 --decimals 2 --separator _").await.unwrap(), "1_234.50");
+}
+
+#[tokio::test]
+async fn number_format_requires_number() {
+    assert!(number_format("abc").await.is_err());
+    assert!(number_format("--decimals -1 2").await.is_err());
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::Value;
+
+#[tokio::test]
+async fn results_are_in_order_of_blocks() {
+    let result = eval(r#"
+        parallel { sleep 0.1; return first } { return second }
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::from(vec![Value::from("first"), Value::from("second")]));
+}
+
+#[tokio::test]
+async fn blocks_run_concurrently() {
+    let result = eval(r#"
+        $t = [ready: no]
+        parallel {
+            sleep 0.1
+            return $t->ready
+        } {
+            $t->ready = yes
+        }
+    "#).await;
+
+    assert_eq!(result.unwrap().as_list().unwrap()[0], "yes");
+}
+
+#[tokio::test]
+async fn exception_cancels_other_blocks() {
+    let result = eval(r#"
+        $t = [finished: no]
+        try {
+            parallel {
+                sleep 0.2
+                $t->finished = yes
+            } {
+                throw oops
+            }
+        } <e> {
+            sleep 0.5
+            return [$e $t->finished]
+        }
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::from(vec![Value::from("oops"), Value::from("no")]));
+}
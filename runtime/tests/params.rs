@@ -0,0 +1,40 @@
+#[tokio::test]
+async fn default_param_used_when_arg_missing() {
+    let result = riptide_runtime::eval(r#"
+        $test = <a, [b "default"]> {
+            return $b
+        }
+
+        test "a"
+    "#).await;
+
+    assert_eq!(result.unwrap(), "default");
+}
+
+#[tokio::test]
+async fn default_param_can_refer_to_earlier_params() {
+    let result = riptide_runtime::eval(r#"
+        $test = <a, [b $a]> {
+            return $b
+        }
+
+        test "a"
+    "#).await;
+
+    assert_eq!(result.unwrap(), "a");
+}
+
+#[tokio::test]
+async fn default_param_evaluated_lazily() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for throw
+
+        $test = <[a (throw "should not be evaluated")]> {
+            return $a
+        }
+
+        test "given"
+    "#).await;
+
+    assert_eq!(result.unwrap(), "given");
+}
@@ -0,0 +1,22 @@
+use riptide_runtime::eval;
+use std::time::Duration;
+use tokio::time::timeout;
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn foreground_command_leads_its_own_process_group() {
+    // The fifth field of the stat file is the process group.
+    let result = eval(r#"
+        sh -c 'read -r _ _ _ _ pgid _ < /proc/$$/stat; test "$pgid" -eq $$'
+        return $?
+    "#).await.unwrap();
+
+    assert_eq!(result, 0.0);
+}
+
+#[tokio::test]
+async fn stopped_foreground_command_is_continued() {
+    let result = timeout(Duration::from_secs(10), eval("sh -c 'kill -STOP $$; exit 7'; return $?")).await;
+
+    assert_eq!(result.expect("waited on a stopped command").unwrap(), 7.0);
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::numbers;
+use riptide_runtime::prelude::*;
+
+async fn range(args: &str) -> Result<Value, Exception> {
+    common::eval(&format!("range {}", args)).await
+}
+
+#[tokio::test]
+async fn range_is_end_exclusive() {
+    assert_eq!(range("1 5").await.unwrap(), numbers(&[1.0, 2.0, 3.0, 4.0]));
+    assert_eq!(range("3 3").await.unwrap(), numbers(&[]));
+}
+
+#[tokio::test]
+async fn range_with_step() {
+    assert_eq!(range("0 10 3").await.unwrap(), numbers(&[0.0, 3.0, 6.0, 9.0]));
+    assert_eq!(range("0 1 0.25").await.unwrap(), numbers(&[0.0, 0.25, 0.5, 0.75]));
+}
+
+#[tokio::test]
+async fn range_counts_down_when_start_is_greater() {
+    assert_eq!(range("5 1").await.unwrap(), numbers(&[5.0, 4.0, 3.0, 2.0]));
+    assert_eq!(range("10 0 -4").await.unwrap(), numbers(&[10.0, 6.0, 2.0]));
+}
+
+#[tokio::test]
+async fn range_with_zero_step_throws() {
+    assert!(range("1 5 0").await.is_err());
+}
+
+#[tokio::test]
+async fn range_too_large_throws() {
+    assert!(range("0 1e300").await.is_err());
+    assert!(range("0 1 1e-300").await.is_err());
+}
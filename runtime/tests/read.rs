@@ -0,0 +1,41 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::{prelude::*, RipString};
+
+#[tokio::test]
+async fn read_line_returns_lines_then_nil() {
+    let result = eval(r#"
+        printf 'one\ntwo\r\n\nlast' | {
+            return [(read-line) (read-line) (read-line) (read-line) (read-line)]
+        }
+    "#).await.unwrap();
+
+    // The pipeline returns the value of each step, so find the list of lines.
+    let lines = result.as_list().unwrap().iter().find_map(Value::as_list).unwrap();
+
+    assert_eq!(lines, &[
+        Value::from("one"),
+        Value::from("two"),
+        Value::from(""),
+        Value::from("last"),
+        Value::Nil,
+    ]);
+}
+
+#[tokio::test]
+async fn read_all_returns_remaining_input() {
+    let result = eval(r#"
+        printf 'one\ntwo\n\xff' | {
+            return [(read-line) (read-all) (read-all)]
+        }
+    "#).await.unwrap();
+
+    let values = result.as_list().unwrap().iter().find_map(Value::as_list).unwrap();
+
+    assert_eq!(values, &[
+        Value::from("one"),
+        Value::from(RipString::from(b"two\n\xff".to_vec())),
+        Value::from(""),
+    ]);
+}
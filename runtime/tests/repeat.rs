@@ -0,0 +1,48 @@
+mod common;
+
+use common::{eval, numbers};
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn repeat_invokes_block_count_times() {
+    let result = eval(r#"
+        $t = [last: 0]
+        $result = $(repeat 3 <i> { $t->last = $i })
+        return [$t->last $result]
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::from(vec![Value::from(2.0), Value::Nil]));
+}
+
+#[tokio::test]
+async fn repeat_collects_results() {
+    assert_eq!(eval("repeat --collect 3 <i> { return $i }").await.unwrap(), numbers(&[0.0, 1.0, 2.0]));
+    assert_eq!(eval("repeat --collect 0 { return 1 }").await.unwrap(), numbers(&[]));
+}
+
+#[tokio::test]
+async fn repeat_stops_on_exception() {
+    let result = eval(r#"
+        $t = [last: 0]
+        try {
+            repeat 5 <i> {
+                $t->last = $i
+                match $i {
+                    2 => { throw stop }
+                }
+            }
+        } {}
+        return $t->last
+    "#).await;
+
+    assert_eq!(result.unwrap(), 2.0);
+}
+
+#[tokio::test]
+async fn repeat_rejects_bad_counts() {
+    for count in ["-1", "1.5", "many"] {
+        let error = eval(&format!("repeat {} {{}}", count)).await.unwrap_err();
+
+        assert!(error.message().to_string().starts_with("repeat count must be"));
+    }
+}
@@ -0,0 +1,40 @@
+use riptide_runtime::prelude::*;
+
+async fn double(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    match args.first().and_then(Value::as_number) {
+        Some(number) => Ok(Value::from(number * 2.0)),
+        None => Ok(Value::Nil),
+    }
+}
+
+#[tokio::test]
+async fn reset_restores_globals_after_init() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+
+    fiber.execute(None, "$GLOBALS->answer = 42").await.unwrap();
+    fiber.exit(3);
+
+    assert_eq!(fiber.globals().get("answer"), 42.0);
+
+    fiber.reset();
+
+    assert_eq!(fiber.globals().get("answer"), Value::Nil);
+    assert_eq!(fiber.exit_code(), None);
+
+    // The globals table is restored in place, so references to it still work.
+    let result = fiber.execute(None, "$GLOBALS->answer = 43; return $GLOBALS->answer").await;
+
+    assert_eq!(result.unwrap(), 43.0);
+}
+
+#[tokio::test]
+async fn reset_keeps_globals_defined_before_snapshot() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    fiber.register_fn("double", double).unwrap();
+    fiber.snapshot();
+
+    fiber.globals().set("double", Value::Nil);
+    fiber.reset();
+
+    assert_eq!(fiber.execute(None, "double 21").await.unwrap(), 42.0);
+}
@@ -0,0 +1,9 @@
+#[tokio::test]
+async fn statements_separated_by_semicolons_run_in_order() {
+    let result = riptide_runtime::eval(r#"
+        $log = a; $log = "$log b";; $log = "$log c";
+        $test = { $log = "$log d"; return $log; }; test
+    "#).await;
+
+    assert_eq!(result.unwrap(), "a b c d");
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::numbers;
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn sort_numbers_naturally() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for sort
+        sort [10 2 33 -1 2]
+    "#).await.unwrap();
+
+    assert_eq!(result, numbers(&[-1.0, 2.0, 2.0, 10.0, 33.0]));
+}
+
+#[tokio::test]
+async fn sort_strings_lexicographically() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for sort
+        sort [pear apple fig]
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::from(vec![Value::from("apple"), Value::from("fig"), Value::from("pear")]));
+}
+
+/// Compares two `[key name]` pairs by key, in descending order.
+async fn by_key_descending(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let key = |value: &Value| value.as_list().unwrap()[0].as_number().unwrap();
+
+    Ok(Value::from(key(&args[1]) - key(&args[0])))
+}
+
+#[tokio::test]
+async fn sort_with_comparator_is_stable() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    fiber.globals().set("by-key-descending", Value::foreign_fn(by_key_descending));
+
+    let result = fiber.execute(None, r#"
+        import 'builtins' for sort
+        sort [[1 a] [2 b] [1 c] [3 d] [2 e]] $by-key-descending
+    "#).await.unwrap();
+
+    let names = result.as_list().unwrap().iter()
+        .map(|pair| pair.as_list().unwrap()[1].to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(names, ["d", "b", "e", "a", "c"]);
+}
+
+#[tokio::test]
+async fn throwing_comparator_aborts_sort() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for sort throw
+        sort [3 2 1] { throw nope }
+    "#).await;
+
+    assert_eq!(result.unwrap_err().message(), &Value::from("nope"));
+}
+
+#[tokio::test]
+async fn sort_mixed_types_throws() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for sort
+        sort [1 a]
+    "#).await;
+
+    assert!(result.is_err());
+}
+
+#[tokio::test]
+async fn reverse_list() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for reverse
+        reverse [1 2 3]
+    "#).await.unwrap();
+
+    assert_eq!(result, numbers(&[3.0, 2.0, 1.0]));
+}
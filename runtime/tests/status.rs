@@ -0,0 +1,51 @@
+use riptide_runtime::{eval, Value};
+
+#[tokio::test]
+async fn nonzero_exit_sets_status_without_throwing() {
+    let result = eval("sh -c 'exit 3'; return $?").await.unwrap();
+
+    assert_eq!(result, 3.0);
+}
+
+#[tokio::test]
+async fn zero_exit_resets_status() {
+    let result = eval("sh -c 'exit 3'; true; return $?").await.unwrap();
+
+    assert_eq!(result, 0.0);
+}
+
+#[tokio::test]
+async fn strict_mode_throws_on_nonzero_exit() {
+    let error = eval("let @strict = true { sh -c 'exit 3' }").await.unwrap_err();
+
+    assert!(error.message().to_string().contains("exited with status 3"));
+}
+
+#[tokio::test]
+async fn signal_termination_reports_negative_status() {
+    let result = eval("sh -c 'kill -9 $$'; return $?").await.unwrap();
+
+    assert_eq!(result, -9.0);
+
+    let error = eval("let @strict = true { sh -c 'kill -9 $$' }").await.unwrap_err();
+
+    assert!(error.message().to_string().contains("terminated by signal 9"));
+}
+
+#[tokio::test]
+async fn pipeline_records_status_of_each_stage() {
+    let result = eval("sh -c 'exit 2' | true | sh -c 'exit 5'; return $PIPESTATUS").await.unwrap();
+
+    assert_eq!(result, Value::from(vec![Value::from(2.0), Value::from(0.0), Value::from(5.0)]));
+}
+
+#[tokio::test]
+async fn pipeline_status_is_only_replaced_by_pipelines() {
+    let result = eval("
+        sh -c 'exit 1' | call { return x }
+        sh -c 'exit 4'
+        return $PIPESTATUS
+    ").await.unwrap();
+
+    assert_eq!(result, Value::from(vec![Value::from(1.0), Value::Nil]));
+}
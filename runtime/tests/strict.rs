@@ -0,0 +1,65 @@
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn strict_builtin_toggles_strict_mode() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    assert!(!fiber.strict());
+
+    let result = fiber.execute(None, r#"
+        import 'builtins' for strict
+        strict true
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::FALSE);
+    assert!(fiber.strict());
+
+    let error = fiber.execute(None, "sh -c 'exit 3'").await.unwrap_err();
+
+    assert!(error.message().to_string().contains("exited with status 3"));
+}
+
+#[tokio::test]
+async fn strict_mode_can_be_turned_off_in_a_scope() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    fiber.set_strict(true);
+
+    let result = fiber.execute(None, r#"
+        let @strict = false {
+            sh -c 'exit 3'
+        }
+        return $?
+    "#).await.unwrap();
+
+    assert_eq!(result, 3.0);
+}
+
+#[tokio::test]
+async fn strict_mode_is_suppressed_by_try_and_conditions() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    fiber.set_strict(true);
+
+    let result = fiber.execute(None, r#"
+        import 'builtins' for table-set
+
+        $t = [:]
+        try {
+            sh -c 'exit 3'
+        } catch {
+            $t->caught = yes
+        }
+        sh -c 'exit 1' || table-set $t handled yes
+        sh -c 'exit 1' && table-set $t skipped yes || table-set $t recovered yes
+        return $t
+    "#).await.unwrap();
+
+    let t = result.as_table().unwrap();
+    assert_eq!(t.get("caught"), "yes");
+    assert_eq!(t.get("handled"), "yes");
+    assert_eq!(t.get("skipped"), Value::Nil);
+    assert_eq!(t.get("recovered"), "yes");
+
+    // The last pipeline of a chain is not handled by anything.
+    let error = fiber.execute(None, "sh -c 'exit 0' && sh -c 'exit 4'").await.unwrap_err();
+
+    assert!(error.message().to_string().contains("exited with status 4"));
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn list_index_counts_from_start_or_end() {
+    assert_eq!(eval("$l = [a b c]; return $l[0]").await.unwrap(), "a");
+    assert_eq!(eval("$l = [a b c]; return $l[2]").await.unwrap(), "c");
+    assert_eq!(eval("$l = [a b c]; return $l[-1]").await.unwrap(), "c");
+    assert_eq!(eval("$l = [a b c]; return $l[-3]").await.unwrap(), "a");
+}
+
+#[tokio::test]
+async fn list_index_out_of_range_is_nil() {
+    assert_eq!(eval("$l = [a b c]; return $l[3]").await.unwrap(), Value::Nil);
+    assert_eq!(eval("$l = [a b c]; return $l[-4]").await.unwrap(), Value::Nil);
+}
+
+#[tokio::test]
+async fn list_index_must_be_whole_number() {
+    let error = eval("$l = [a b c]; return $l[1.5]").await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "list index must be a whole number, not '1.5'");
+}
+
+#[tokio::test]
+async fn table_index_is_computed() {
+    let result = eval(r#"
+        $t = [name: riptide 1: one]
+        $key = name
+        return [$t[$key] $t['1'] $t[missing]]
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::List(vec!["riptide".into(), "one".into(), Value::Nil]));
+}
+
+#[tokio::test]
+async fn subscripts_chain_with_member_access() {
+    let result = eval(r#"
+        $config = [servers: [[host: a] [host: b]]]
+        return $config->servers[-1]->host
+    "#).await.unwrap();
+
+    assert_eq!(result, "b");
+}
+
+#[tokio::test]
+async fn indexing_other_values_throws() {
+    let error = eval("$s = text; return $s[0]").await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "cannot index a string value");
+}
@@ -0,0 +1,30 @@
+mod common;
+
+use common::eval;
+
+#[tokio::test]
+async fn unknown_command_suggests_similar_function() {
+    let error = eval(r#"
+        $frobnicate = {}
+        frobnicat
+    "#).await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "no such command or file, did you mean 'frobnicate'?");
+}
+
+#[tokio::test]
+async fn unknown_command_suggests_similar_alias() {
+    let error = eval(r#"
+        alias frobnicate "true"
+        frobincate
+    "#).await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "no such command or file, did you mean 'frobnicate'?");
+}
+
+#[tokio::test]
+async fn unknown_command_without_similar_names() {
+    let error = eval("riptide-no-such-command").await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "no such command or file");
+}
@@ -0,0 +1,32 @@
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn composite_values_as_table_keys() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for table-get table-set
+
+        $table = [:]
+        table-set $table [1 2] pair
+        table-set $table 3 number
+        table-set $table true boolean
+
+        return [(table-get $table [1 2]) (table-get $table 3) (table-get $table true) (table-get $table [2 1])]
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::from(vec![
+        Value::from("pair"),
+        Value::from("number"),
+        Value::from("boolean"),
+        Value::Nil,
+    ]));
+}
+
+#[tokio::test]
+async fn unhashable_table_key_throws() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for table-set
+        table-set [:] [:] value
+    "#).await;
+
+    assert!(result.unwrap_err().message().to_string().contains("cannot use a table as a table key"));
+}
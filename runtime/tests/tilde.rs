@@ -0,0 +1,16 @@
+use std::env;
+
+#[tokio::test]
+async fn tilde_expands_to_home_dir() {
+    let home = env::var("HOME").unwrap();
+
+    assert_eq!(riptide_runtime::eval("return ~").await.unwrap(), home.as_str());
+    assert_eq!(riptide_runtime::eval("return ~/src").await.unwrap(), format!("{}/src", home).as_str());
+}
+
+#[tokio::test]
+async fn tilde_is_literal_when_quoted_or_unknown_user() {
+    assert_eq!(riptide_runtime::eval("return '~/src'").await.unwrap(), "~/src");
+    assert_eq!(riptide_runtime::eval("return \"~/src\"").await.unwrap(), "~/src");
+    assert_eq!(riptide_runtime::eval("return ~no-such-user-riptide/src").await.unwrap(), "~no-such-user-riptide/src");
+}
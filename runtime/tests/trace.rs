@@ -0,0 +1,20 @@
+#[tokio::test]
+async fn trace_builtin_toggles_tracing() {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    assert!(!fiber.trace());
+
+    let result = fiber.execute(None, r#"
+        import 'builtins' for trace
+        trace true
+    "#).await.unwrap();
+
+    assert_eq!(result, riptide_runtime::Value::FALSE);
+    assert!(fiber.trace());
+
+    fiber.execute(None, r#"
+        import 'builtins' for trace
+        trace false
+    "#).await.unwrap();
+
+    assert!(!fiber.trace());
+}
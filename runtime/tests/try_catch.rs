@@ -0,0 +1,64 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn catch_binds_exception_and_provides_value() {
+    let result = eval(r#"
+        return $(try { throw oops } catch e { return "caught $e" })
+    "#).await;
+
+    assert_eq!(result.unwrap(), "caught oops");
+}
+
+#[tokio::test]
+async fn try_without_exception_evaluates_to_body() {
+    let result = eval(r#"
+        return $(try { return fine } catch { return caught })
+    "#).await;
+
+    assert_eq!(result.unwrap(), "fine");
+}
+
+#[tokio::test]
+async fn finally_always_runs() {
+    let result = eval(r#"
+        $t = [a: no b: no]
+        try { return fine } finally { $t->a = yes }
+        try { throw oops } catch { return caught } finally { $t->b = yes }
+        return [$t->a $t->b]
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::from(vec![Value::from("yes"), Value::from("yes")]));
+}
+
+#[tokio::test]
+async fn exception_without_catch_propagates_after_finally() {
+    let result = eval(r#"
+        $t = [done: no]
+        try {
+            try { throw oops } finally { $t->done = yes }
+        } catch e {
+            return [$e $t->done]
+        }
+    "#).await;
+
+    assert_eq!(result.unwrap(), Value::from(vec![Value::from("oops"), Value::from("yes")]));
+}
+
+#[tokio::test]
+async fn exception_in_finally_propagates() {
+    let error = eval(r#"
+        try { throw first } catch { return caught } finally { throw second }
+    "#).await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "second");
+}
+
+#[tokio::test]
+async fn try_builtin_is_still_callable() {
+    let result = eval("return $(try { throw oops } <e> { return $e })").await;
+
+    assert_eq!(result.unwrap(), "oops");
+}
@@ -0,0 +1,29 @@
+mod common;
+
+use riptide_runtime::prelude::*;
+
+async fn check(predicate: &str, value: &str) -> Value {
+    common::eval(&format!("{} {}", predicate, value)).await.unwrap()
+}
+
+#[tokio::test]
+async fn type_predicates_match_their_type() {
+    assert_eq!(check("is-string", "'a'").await, Value::TRUE);
+    assert_eq!(check("is-number", "42").await, Value::TRUE);
+    assert_eq!(check("is-list", "[1 2]").await, Value::TRUE);
+    assert_eq!(check("is-table", "[a: 1]").await, Value::TRUE);
+    assert_eq!(check("is-block", "{}").await, Value::TRUE);
+    assert_eq!(check("is-nil", "(nil)").await, Value::TRUE);
+    assert_eq!(check("is-nil", "").await, Value::TRUE);
+    assert_eq!(check("is-boolean", "(is-nil)").await, Value::TRUE);
+}
+
+#[tokio::test]
+async fn type_predicates_reject_other_types() {
+    assert_eq!(check("is-string", "42").await, Value::FALSE);
+    assert_eq!(check("is-number", "'42'").await, Value::FALSE);
+    assert_eq!(check("is-list", "[a: 1]").await, Value::FALSE);
+    assert_eq!(check("is-table", "[1 2]").await, Value::FALSE);
+    assert_eq!(check("is-block", "'a'").await, Value::FALSE);
+    assert_eq!(check("is-nil", "false").await, Value::FALSE);
+}
@@ -0,0 +1,14 @@
+#[tokio::test]
+async fn version_contains_version_string() {
+    let result = riptide_runtime::eval(r#"
+        import 'builtins' for version
+
+        version
+    "#).await.unwrap();
+
+    let version = result.get("version");
+
+    assert!(version.as_string().is_some());
+    assert!(!version.to_string().is_empty());
+    assert!(!result.get("target").to_string().is_empty());
+}
@@ -0,0 +1,40 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::prelude::*;
+
+#[tokio::test]
+async fn which_reports_builtins_and_functions() {
+    assert_eq!(eval("which which").await.unwrap().get("kind"), "builtin");
+
+    let result = eval(r#"
+        $greet = sub greet {}
+        which greet
+    "#).await.unwrap();
+
+    assert_eq!(result.get("kind"), "function");
+}
+
+#[tokio::test]
+async fn which_reports_aliases() {
+    let result = eval(r#"
+        alias ll "ls -l"
+        which ll
+    "#).await.unwrap();
+
+    assert_eq!(result.get("kind"), "alias");
+    assert_eq!(result.get("alias"), "ls -l");
+}
+
+#[tokio::test]
+async fn which_finds_external_commands() {
+    let result = eval("which sh").await.unwrap();
+
+    assert_eq!(result.get("kind"), "command");
+    assert!(result.get("path").to_string().ends_with("/sh"));
+}
+
+#[tokio::test]
+async fn which_returns_nil_for_unknown_names() {
+    assert_eq!(eval("which riptide-no-such-command").await.unwrap(), Value::Nil);
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use common::eval;
+
+#[tokio::test]
+async fn block_that_finishes_in_time_returns_value() {
+    assert_eq!(eval("with-timeout 5 { return done }").await.unwrap(), "done");
+}
+
+#[tokio::test]
+async fn block_that_takes_too_long_throws_timeout() {
+    let error = eval("with-timeout 0.1 { sleep 5 }").await.unwrap_err();
+
+    assert_eq!(error.tag().unwrap(), "timeout");
+    assert_eq!(error.message().to_string(), "timed out after 0.1 seconds");
+}
+
+#[tokio::test]
+async fn timeout_can_be_caught_by_type() {
+    let result = eval(r#"
+        try --type timeout {
+            with-timeout 0.1 { sleep 5 }
+        } <message> {
+            return caught
+        }
+    "#).await;
+
+    assert_eq!(result.unwrap(), "caught");
+}
+
+#[tokio::test]
+async fn nested_timeouts_use_the_shortest() {
+    let error = eval("with-timeout 5 { with-timeout 0.1 { sleep 5 } }").await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "timed out after 0.1 seconds");
+}
+
+#[tokio::test]
+async fn negative_timeout_is_rejected() {
+    let error = eval("with-timeout -1 { return done }").await.unwrap_err();
+
+    assert!(error.tag().is_none());
+    assert!(error.message().to_string().starts_with("timeout must be"));
+}
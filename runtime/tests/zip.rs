@@ -0,0 +1,30 @@
+mod common;
+
+use common::eval;
+use riptide_runtime::prelude::*;
+
+fn list(items: Vec<Value>) -> Value {
+    Value::List(items)
+}
+
+#[tokio::test]
+async fn zip_truncates_to_shortest() {
+    assert_eq!(eval("zip [1 2 3] [a b]").await.unwrap(), list(vec![
+        list(vec![1.0.into(), "a".into()]),
+        list(vec![2.0.into(), "b".into()]),
+    ]));
+    assert_eq!(eval("zip [1] [a] [true]").await.unwrap(), list(vec![
+        list(vec![1.0.into(), "a".into(), "true".into()]),
+    ]));
+    assert_eq!(eval("zip [1 2] []").await.unwrap(), list(vec![]));
+    assert!(eval("zip [1 2] a").await.is_err());
+}
+
+#[tokio::test]
+async fn enumerate_pairs_items_with_index() {
+    assert_eq!(eval("enumerate [a b]").await.unwrap(), list(vec![
+        list(vec![0.0.into(), "a".into()]),
+        list(vec![1.0.into(), "b".into()]),
+    ]));
+    assert_eq!(eval("enumerate []").await.unwrap(), list(vec![]));
+}
@@ -4,7 +4,8 @@ use std::mem;
 pub struct Buffer {
     // Current command line buffer text.
     text: String,
-    // Current cursor position in the buffer.
+    // Current cursor position in the buffer, as a byte offset that is always on
+    // a character boundary.
     cursor: usize,
 }
 
@@ -35,7 +36,7 @@ impl Buffer {
         text
     }
 
-    /// Get the current cursor position.
+    /// Get the current cursor position, as a byte offset into the text.
     pub fn cursor(&self) -> usize {
         self.cursor
     }
@@ -49,15 +50,33 @@ impl Buffer {
     /// Returns the new cursor position. The actual position may differ if the requested position was beyond the end of
     /// the buffer.
     pub fn move_cursor_to(&mut self, pos: usize) -> usize {
-        self.cursor = self.text.len().min(pos);
+        let mut pos = self.text.len().min(pos);
+
+        while !self.text.is_char_boundary(pos) {
+            pos -= 1;
+        }
+
+        self.cursor = pos;
         self.cursor
     }
 
-    /// Adjust the cursor position by a relative amount.
+    /// Adjust the cursor position by a relative number of characters.
     ///
     /// Returns the new cursor position.
     pub fn move_cursor_relative(&mut self, offset: isize) -> usize {
-        let pos = 0.max(self.cursor as isize + offset) as usize;
+        let pos = if offset < 0 {
+            self.text[..self.cursor]
+                .char_indices()
+                .rev()
+                .nth(offset.unsigned_abs() - 1)
+                .map_or(0, |(i, _)| i)
+        } else {
+            self.text[self.cursor..]
+                .char_indices()
+                .nth(offset as usize)
+                .map_or(self.text.len(), |(i, _)| self.cursor + i)
+        };
+
         self.move_cursor_to(pos)
     }
 
@@ -73,20 +92,20 @@ impl Buffer {
     /// Insert a character after the cursor.
     pub fn insert_char(&mut self, c: char) {
         self.text.insert(self.cursor, c);
-        self.move_cursor_relative(1);
+        self.cursor += c.len_utf8();
     }
 
     /// Insert a string after the cursor.
     pub fn insert_str<S: AsRef<str>>(&mut self, string: S) {
         let string = string.as_ref();
         self.text.insert_str(self.cursor, string);
-        self.move_cursor_relative(string.len() as isize);
+        self.cursor += string.len();
     }
 
     pub fn delete_before_cursor(&mut self) {
         if self.cursor > 0 {
-            self.text.remove(self.cursor - 1);
             self.move_cursor_relative(-1);
+            self.text.remove(self.cursor);
         }
     }
 
@@ -102,3 +121,20 @@ impl Buffer {
         self.cursor = 0;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_multibyte_text() {
+        let mut buffer = Buffer::new();
+        buffer.insert_str("héllo");
+        buffer.move_cursor_relative(-3);
+        buffer.delete_before_cursor();
+        buffer.insert_char('日');
+
+        assert_eq!(buffer.text(), "h日llo");
+        assert_eq!(buffer.cursor(), "h日".len());
+    }
+}
@@ -1,5 +1,6 @@
-use super::Completer;
+use super::{Completer, Completions};
 use crate::history::History;
+use riptide_runtime::Fiber;
 
 pub struct HistoryCompleter {
     history: History,
@@ -14,11 +15,13 @@ impl HistoryCompleter {
 }
 
 impl Completer for HistoryCompleter {
-    fn complete(&self, prefix: &str) -> Vec<String> {
-        self.history
+    fn complete<'a>(&'a self, _: &'a mut Fiber, prefix: &'a str) -> Completions<'a> {
+        let commands = self.history
             .frequent_commands_starting_with(prefix)
             .into_iter()
             .map(|summary| summary.command)
-            .collect()
+            .collect();
+
+        Box::pin(std::future::ready(commands))
     }
 }
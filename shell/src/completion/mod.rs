@@ -1,11 +1,26 @@
+use riptide_runtime::Fiber;
+use std::{future::Future, pin::Pin};
+
 pub mod history;
+pub mod scope;
+pub mod script;
+
+/// Completions that are being computed. Computing them may run script code, so
+/// this may need to wait on the fiber.
+pub type Completions<'a> = Pin<Box<dyn Future<Output = Vec<String>> + 'a>>;
 
 pub trait Completer {
-    fn complete(&self, prefix: &str) -> Vec<String>;
+    fn complete<'a>(&'a self, fiber: &'a mut Fiber, prefix: &'a str) -> Completions<'a>;
 
-    fn complete_one(&self, prefix: &str) -> Option<String> {
-        self.complete(prefix).drain(..).next()
+    fn complete_one<'a>(&'a self, fiber: &'a mut Fiber, prefix: &'a str) -> Pin<Box<dyn Future<Output = Option<String>> + 'a>> {
+        Box::pin(async move {
+            self.complete(fiber, prefix).await.drain(..).next()
+        })
     }
+
+    /// Called once a command line has been submitted, so that anything cached
+    /// while it was being typed can be forgotten.
+    fn finish_line(&self) {}
 }
 
 pub struct Composite {
@@ -31,18 +46,29 @@ impl Default for Composite {
 }
 
 impl Completer for Composite {
-    fn complete(&self, prefix: &str) -> Vec<String> {
-        self.completers
-            .iter()
-            .flat_map(|completer| completer.complete(prefix))
-            .collect()
+    fn complete<'a>(&'a self, fiber: &'a mut Fiber, prefix: &'a str) -> Completions<'a> {
+        Box::pin(async move {
+            let mut completions = Vec::new();
+
+            for completer in self.completers.iter() {
+                completions.extend(completer.complete(fiber, prefix).await);
+            }
+
+            completions
+        })
+    }
+
+    fn finish_line(&self) {
+        for completer in self.completers.iter() {
+            completer.finish_line();
+        }
     }
 }
 
 pub struct TestCompleter;
 
 impl Completer for TestCompleter {
-    fn complete(&self, prefix: &str) -> Vec<String> {
-        vec![format!("{} - test completer", prefix)]
+    fn complete<'a>(&'a self, _: &'a mut Fiber, prefix: &'a str) -> Completions<'a> {
+        Box::pin(std::future::ready(vec![format!("{} - test completer", prefix)]))
     }
 }
@@ -0,0 +1,134 @@
+use super::{Completer, Completions};
+use riptide_runtime::{Fiber, Table, Value};
+
+/// Completes the names of variables and functions that are in scope at the
+/// prompt.
+///
+/// Only the word being typed is completed, and only if it is not in command
+/// position, since commands are completed from history instead. A word that
+/// starts with `$` is completed to the name of any variable, and any other word
+/// to the name of a variable holding a block.
+pub struct ScopeCompleter {
+    /// Tables of bindings to complete names from. Tables are shared, so names
+    /// defined after the completer is created are included.
+    tables: Vec<Table>,
+}
+
+impl ScopeCompleter {
+    pub fn new(tables: Vec<Table>) -> Self {
+        Self {
+            tables,
+        }
+    }
+
+    fn names(&self, functions_only: bool) -> Vec<String> {
+        let mut names = self.tables
+            .iter()
+            .flat_map(|table| {
+                table.keys()
+                    .filter(move |key| !functions_only || matches!(table.get(key), Value::Block(_)))
+            })
+            .map(|name| name.to_string())
+            // Names starting with two underscores are for internal use.
+            .filter(|name| !name.starts_with("__"))
+            .collect::<Vec<_>>();
+
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    fn complete_word(&self, prefix: &str) -> Vec<String> {
+        let (before, word) = split_last_word(prefix);
+
+        if word.is_empty() || is_command_position(before) {
+            return Vec::new();
+        }
+
+        let (word, functions_only) = match word.strip_prefix('$') {
+            Some(name) => (name, false),
+            None => (word, true),
+        };
+
+        self.names(functions_only)
+            .into_iter()
+            .filter(|name| name.len() > word.len() && name.starts_with(word))
+            .map(|name| format!("{}{}", prefix, &name[word.len()..]))
+            .collect()
+    }
+}
+
+impl Completer for ScopeCompleter {
+    fn complete<'a>(&'a self, _: &'a mut Fiber, prefix: &'a str) -> Completions<'a> {
+        Box::pin(std::future::ready(self.complete_word(prefix)))
+    }
+}
+
+/// Split a line into the text before the word at the end of it, and the word.
+pub(super) fn split_last_word(line: &str) -> (&str, &str) {
+    let start = line
+        .char_indices()
+        .filter(|(_, c)| c.is_whitespace() || "|;{}()[]'\"".contains(*c))
+        .last()
+        .map(|(index, c)| index + c.len_utf8())
+        .unwrap_or(0);
+
+    line.split_at(start)
+}
+
+/// Check whether a word following the given text would be the name of a
+/// command being called.
+pub(super) fn is_command_position(before: &str) -> bool {
+    match before.trim_end().chars().last() {
+        None => true,
+        Some(c) => "|;{(".contains(c),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use riptide_runtime::table;
+
+    fn completer() -> ScopeCompleter {
+        let scope = table! {
+            "greeting" => "hello",
+            "__hidden" => "value",
+        };
+        let globals = table! {
+            "grep" => "not a function",
+        };
+
+        ScopeCompleter::new(vec![scope, globals])
+    }
+
+    #[test]
+    fn completes_variables_after_dollar() {
+        assert_eq!(completer().complete_word("println $gre"), ["println $greeting"]);
+        assert_eq!(completer().complete_word("println $__"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn completes_only_functions_without_dollar() {
+        assert_eq!(completer().complete_word("call gre"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn skips_command_position() {
+        assert_eq!(completer().complete_word("$gre"), Vec::<String>::new());
+        assert_eq!(completer().complete_word("ls | $gre"), Vec::<String>::new());
+        assert_eq!(completer().complete_word("{ $gre"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn sees_names_defined_later() {
+        let scope = table!();
+        let completer = ScopeCompleter::new(vec![scope.clone()]);
+
+        assert_eq!(completer.complete_word("echo $na"), Vec::<String>::new());
+
+        scope.set("name", "value");
+
+        assert_eq!(completer.complete_word("echo $na"), ["echo $name"]);
+    }
+}
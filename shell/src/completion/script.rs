@@ -0,0 +1,175 @@
+use super::{Completer, Completions};
+use riptide_runtime::{coerce, completion, Fiber, Value};
+use std::cell::RefCell;
+
+/// Completes the arguments of commands using functions registered by scripts
+/// with the `complete` builtin.
+///
+/// The registered function is called with the words of the command typed so
+/// far, starting with the name of the command and ending with the word being
+/// completed, which is empty if nothing has been typed for it yet. It returns a
+/// list of candidates, and those that start with the word being completed are
+/// suggested. If no function is registered for the command, or it fails, the
+/// fallback completer is used instead.
+///
+/// Since completions are computed every time the command line is redrawn, the
+/// candidates are reused as long as the word being completed only grows, and
+/// the function is called again once anything else on the line changes.
+pub struct ScriptCompleter<C> {
+    fallback: C,
+    cache: RefCell<Option<Cached>>,
+}
+
+/// Candidates returned by a completion function.
+struct Cached {
+    /// The command line up to the word being completed.
+    line: String,
+
+    /// The word being completed when the function was called.
+    word: String,
+
+    candidates: Vec<String>,
+}
+
+impl<C> ScriptCompleter<C> {
+    pub fn new(fallback: C) -> Self {
+        Self {
+            fallback,
+            cache: RefCell::new(None),
+        }
+    }
+
+    /// Get the candidates cached for a command line, if the function has
+    /// already been called for it.
+    fn cached(&self, line: &str, word: &str) -> Option<Vec<String>> {
+        self.cache
+            .borrow()
+            .as_ref()
+            .filter(|cached| cached.line == line && word.starts_with(&cached.word))
+            .map(|cached| cached.candidates.clone())
+    }
+}
+
+impl<C: Completer> Completer for ScriptCompleter<C> {
+    fn complete<'a>(&'a self, fiber: &'a mut Fiber, prefix: &'a str) -> Completions<'a> {
+        Box::pin(async move {
+            let words = current_words(prefix);
+
+            // The command name itself is never completed by a script.
+            let (command, word) = match words.as_slice() {
+                [command, .., word] => (*command, *word),
+                _ => return self.fallback.complete(fiber, prefix).await,
+            };
+
+            let function = match completion::get(fiber, command) {
+                Some(function) => function,
+                None => return self.fallback.complete(fiber, prefix).await,
+            };
+
+            let line = &prefix[..prefix.len() - word.len()];
+
+            let candidates = match self.cached(line, word) {
+                Some(candidates) => candidates,
+                None => {
+                    let args = words.iter().map(|word| Value::from(*word)).collect::<Vec<_>>();
+
+                    let candidates = match fiber.invoke(&function, &args).await {
+                        Ok(Value::List(candidates)) => candidates
+                            .iter()
+                            .filter_map(|candidate| coerce::to_string(candidate).as_utf8().map(str::to_owned))
+                            .collect::<Vec<_>>(),
+                        Ok(value) => {
+                            log::warn!("completion function for '{}' must return a list, not '{}'", command, value.type_name());
+                            return self.fallback.complete(fiber, prefix).await;
+                        }
+                        Err(e) => {
+                            log::warn!("completion function for '{}' threw exception: {}", command, e);
+                            return self.fallback.complete(fiber, prefix).await;
+                        }
+                    };
+
+                    *self.cache.borrow_mut() = Some(Cached {
+                        line: line.to_owned(),
+                        word: word.to_owned(),
+                        candidates: candidates.clone(),
+                    });
+
+                    candidates
+                }
+            };
+
+            candidates
+                .into_iter()
+                .filter(|candidate| candidate.len() > word.len() && candidate.starts_with(word))
+                .map(|candidate| format!("{}{}", prefix, &candidate[word.len()..]))
+                .collect()
+        })
+    }
+
+    fn finish_line(&self) {
+        self.cache.borrow_mut().take();
+        self.fallback.finish_line();
+    }
+}
+
+/// Split the command at the end of a line into its words. The last word is the
+/// one being typed, which is empty if the line ends with whitespace.
+fn current_words(line: &str) -> Vec<&str> {
+    let start = line
+        .rfind(|c: char| "|;{(".contains(c))
+        .map(|index| index + 1)
+        .unwrap_or(0);
+    let command = &line[start..];
+    let mut words = command.split_whitespace().collect::<Vec<_>>();
+
+    if command.is_empty() || command.ends_with(char::is_whitespace) {
+        words.push("");
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn words_of_last_command() {
+        assert_eq!(current_words("git com"), ["git", "com"]);
+        assert_eq!(current_words("git "), ["git", ""]);
+        assert_eq!(current_words("ls | git push  or"), ["git", "push", "or"]);
+        assert_eq!(current_words(""), [""]);
+    }
+
+    #[test]
+    fn candidates_are_reused_until_line_is_finished() {
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+
+        runtime.block_on(async {
+            let mut fiber = riptide_runtime::init().await.unwrap();
+
+            // Record the word being completed for each call.
+            fiber.execute(None, r#"
+                import 'builtins' for complete table-set
+                $GLOBALS->seen = [:]
+                complete git <...words> {
+                    table-set $GLOBALS->seen $words[-1] called
+                    return [commit checkout]
+                }
+            "#).await.unwrap();
+
+            let completer = ScriptCompleter::new(crate::completion::TestCompleter);
+            let seen = fiber.globals().get("seen").as_table().unwrap();
+
+            completer.complete(&mut fiber, "git ").await;
+            assert_eq!(completer.complete(&mut fiber, "git c").await, ["git commit", "git checkout"]);
+            assert_eq!(completer.complete(&mut fiber, "git co").await, ["git commit"]);
+            assert!(seen.get("").is_truthy());
+            assert!(seen.get("c").is_nil());
+
+            completer.finish_line();
+            completer.complete(&mut fiber, "git c").await;
+            assert!(seen.get("c").is_truthy());
+        });
+    }
+}
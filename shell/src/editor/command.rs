@@ -1,4 +1,64 @@
+//! Terminal control commands, used both by the editor and by scripts.
+
+use riptide_runtime::{io::Output, prelude::*, throw};
+use tokio::io::AsyncWriteExt;
+
 pub enum Command {
     ClearAfterCursor,
+    ClearScreen,
+    MoveCursorHome,
     MoveCursorLeft(usize),
+    MoveCursorUp(usize),
+}
+
+impl Command {
+    /// Get the escape sequence that performs this command.
+    pub fn escape_sequence(&self) -> String {
+        match self {
+            Command::ClearAfterCursor => String::from("\x1b[J"),
+            Command::ClearScreen => String::from("\x1b[H\x1b[2J"),
+            Command::MoveCursorHome => String::from("\x1b[H"),
+            Command::MoveCursorLeft(n) => format!("\x1b[{}D", n),
+            Command::MoveCursorUp(n) => format!("\x1b[{}A", n),
+        }
+    }
+}
+
+/// Clear the terminal screen and move the cursor to the top left corner.
+pub async fn clear(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    run(fiber, args, Command::ClearScreen).await
+}
+
+/// Move the cursor to the top left corner of the terminal screen.
+pub async fn cursor_home(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    run(fiber, args, Command::MoveCursorHome).await
+}
+
+/// Write a command to standard output. Nothing is written if standard output
+/// is not a terminal, so that escape sequences do not end up in files or
+/// captured output.
+async fn run(fiber: &mut Fiber, args: Vec<Value>, command: Command) -> Result<Value, Exception> {
+    if !args.is_empty() {
+        throw!("too many arguments");
+    }
+
+    let stdout = fiber.stdout();
+
+    if stdout.is_terminal() {
+        stdout.write_all(command.escape_sequence().as_bytes()).await?;
+        stdout.flush().await?;
+    }
+
+    Ok(Value::Nil)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_sequences() {
+        assert_eq!(Command::ClearScreen.escape_sequence(), "\x1b[H\x1b[2J");
+        assert_eq!(Command::MoveCursorLeft(3).escape_sequence(), "\x1b[3D");
+    }
 }
@@ -7,7 +7,12 @@ use crate::{
     theme::Theme,
 };
 use owo_colors::OwoColorize;
-use riptide_runtime::{Fiber, Value};
+use riptide_runtime::{
+    io::process::LAST_STATUS,
+    syntax::{error::ParseErrorKind, parse, source::SourceFile},
+    Fiber,
+    Value,
+};
 use std::{
     fmt::Write,
     os::unix::io::AsRawFd,
@@ -17,6 +22,20 @@ use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
 pub mod command;
 pub mod event;
 pub mod prompt;
+mod width;
+
+/// Name of the global variable holding a custom prompt.
+const PROMPT_GLOBAL: &str = "riptide-prompt";
+
+/// Name of the global variable holding the prompt that replaces the prompt of
+/// a command once it has been submitted.
+const TRANSIENT_PROMPT_GLOBAL: &str = "riptide-transient-prompt";
+
+/// Prompt shown when more lines are needed to complete the current command.
+const CONTINUATION_PROMPT: &str = "... ";
+
+/// Number of columns between tab stops if not set by the theme.
+const DEFAULT_TAB_WIDTH: usize = 4;
 
 /// Controls the interactive command line editor.
 pub struct Editor<I, O: AsRawFd, C> {
@@ -25,8 +44,16 @@ pub struct Editor<I, O: AsRawFd, C> {
     history: History,
     history_session: Session,
     history_cursor: Option<EntryCursor>,
+    /// Whether browsing history only shows commands run in the current
+    /// directory.
+    directory_history: bool,
     completer: C,
     buffer: Buffer,
+    /// Lines already entered for a command that is not yet complete.
+    pending: String,
+    /// Number of rows below the start of the prompt that the last redraw left
+    /// the cursor on, when the command line is wider than the terminal.
+    cursor_row: usize,
 }
 
 pub enum ReadLine {
@@ -42,8 +69,28 @@ impl<I, O: AsRawFd, C> Editor<I, O, C> {
             history,
             history_session: session,
             history_cursor: None,
+            directory_history: false,
             completer,
             buffer: Buffer::new(),
+            pending: String::new(),
+            cursor_row: 0,
+        }
+    }
+
+    /// Set whether browsing history only shows commands that were run in the
+    /// current directory. This can also be toggled with Ctrl-G.
+    pub fn set_directory_history(&mut self, enabled: bool) {
+        self.directory_history = enabled;
+        self.history_cursor = None;
+    }
+
+    /// Start browsing history from the most recent command.
+    fn history_entries(&self) -> EntryCursor {
+        // Commands are recorded with the working directory of the process, so
+        // compare against the same.
+        match std::env::current_dir().ok().filter(|_| self.directory_history) {
+            Some(cwd) => self.history.entries_in_dir(cwd.to_string_lossy()),
+            None => self.history.entries(),
         }
     }
 
@@ -52,39 +99,37 @@ impl<I, O: AsRawFd, C> Editor<I, O, C> {
         Theme::default()
     }
 
-    async fn get_prompt_str(&self, fiber: &mut Fiber) -> String {
-        match fiber.globals().get("riptide-prompt") {
-            // Static prompt.
-            Value::String(ref s) => return s.to_string(),
-
-            // Prompt is determined by a callback function.
-            value @ Value::Block(_) => match fiber.invoke(&value, &[]).await {
-                // Closure returned successfully.
-                Ok(Value::String(ref s)) => return s.to_string(),
-
-                // Closure succeeded, but returned an invalid data type.
-                Ok(value) => {
-                    log::warn!("prompt function returned invalid data type: {}", value.type_name());
-                }
+    /// Check whether the command entered so far is incomplete and needs more
+    /// lines before it can be executed.
+    fn is_incomplete(&self) -> bool {
+        let source = SourceFile::r#virtual("<tty>", format!("{}{}", self.pending, self.buffer.text()));
 
-                Err(e) => {
-                    log::warn!("prompt function threw exception: {}", e);
-                }
-            },
+        matches!(parse(source), Err(e) if e.kind() == ParseErrorKind::Incomplete)
+    }
 
-            Value::Nil => {
-                // Unspecified
-            }
+    async fn get_prompt_str(&self, fiber: &mut Fiber) -> String {
+        if !self.pending.is_empty() {
+            return CONTINUATION_PROMPT.to_owned();
+        }
 
-            value => {
-                // Invalid data type
-                log::warn!("prompt must be a closure or string, not '{}'", value.type_name());
-            }
+        if let Some(prompt) = resolve_prompt(fiber, PROMPT_GLOBAL).await {
+            return prompt;
         }
 
         let theme = self.get_theme();
         let mut buf = String::new();
 
+        let status = fiber.globals().get(LAST_STATUS);
+
+        if let Some(segment) = prompt::status_segment(theme.prompt.as_ref().unwrap(), &status) {
+            write!(
+                &mut buf,
+                "{}{}",
+                segment,
+                theme.prompt.as_ref().unwrap().item_separator.as_ref().unwrap(),
+            ).unwrap();
+        }
+
         let cwd = fiber.current_dir().to_string();
         write!(
             &mut buf,
@@ -111,6 +156,7 @@ impl<I: AsyncRead + Unpin, O: AsyncWrite + AsRawFd + Unpin, C: Completer> Editor
 
         self.stdout.write_all(prompt.as_bytes()).await.unwrap();
         self.stdout.flush().await.unwrap();
+        self.cursor_row = 0;
 
         let mut editor = scopeguard::guard(self, |editor| {
             editor.stdout.set_raw_mode(false).unwrap();
@@ -124,11 +170,27 @@ impl<I: AsyncRead + Unpin, O: AsyncWrite + AsRawFd + Unpin, C: Completer> Editor
             log::trace!("event: {:?}", event);
             match event {
                 Event::Char('\n') => {
+                    let submit = !editor.buffer.text().is_empty() && !editor.is_incomplete();
+
+                    // Only a command on a single line is redrawn, since the
+                    // prompt of the first of several lines has scrolled away.
+                    if submit && editor.pending.is_empty() {
+                        editor.draw_transient_prompt(fiber).await;
+                    }
+
                     editor.stdout.write_all(b"\r\n").await.unwrap();
+                    editor.cursor_row = 0;
 
-                    if !editor.buffer.text().is_empty() {
+                    if submit {
                         break;
                     }
+
+                    if !editor.buffer.text().is_empty() {
+                        // Hold on to this line and keep reading.
+                        let line = editor.buffer.take_text();
+                        editor.pending.push_str(&line);
+                        editor.pending.push('\n');
+                    }
                 }
                 Event::Left | Event::Ctrl('b') => {
                     editor.buffer.move_cursor_relative(-1);
@@ -138,7 +200,7 @@ impl<I: AsyncRead + Unpin, O: AsyncWrite + AsRawFd + Unpin, C: Completer> Editor
                         // If the cursor is already at the end of the line, then
                         // fill in the current suggested command, if any.
                         // TODO: Only compute suggestion one time each event.
-                        if let Some(suggestion) = editor.completer.complete_one(editor.buffer.text()) {
+                        if let Some(suggestion) = editor.completer.complete_one(fiber, editor.buffer.text()).await {
                             if let Some(suffix) = suggestion.strip_prefix(editor.buffer.text()) {
                                 if !suffix.is_empty() {
                                     editor.buffer.insert_str(suffix);
@@ -151,9 +213,11 @@ impl<I: AsyncRead + Unpin, O: AsyncWrite + AsRawFd + Unpin, C: Completer> Editor
                     }
                 }
                 Event::Up => {
-                    let history = editor.history.clone();
+                    if editor.history_cursor.is_none() {
+                        editor.history_cursor = Some(editor.history_entries());
+                    }
 
-                    match editor.history_cursor.get_or_insert_with(|| history.entries()).next() {
+                    match editor.history_cursor.as_mut().unwrap().next() {
                         Some(entry) => {
                             // TODO: Save buffer for later if user wants to return to
                             // what they typed.
@@ -192,11 +256,16 @@ impl<I: AsyncRead + Unpin, O: AsyncWrite + AsRawFd + Unpin, C: Completer> Editor
                 Event::Delete => {
                     editor.buffer.delete_after_cursor();
                 }
+                Event::Ctrl('g') => {
+                    let enabled = !editor.directory_history;
+                    editor.set_directory_history(enabled);
+                }
                 Event::Ctrl('c') => {
                     editor.buffer.clear();
+                    editor.pending.clear();
                 }
                 Event::Ctrl('d') | Event::Eof => {
-                    if editor.buffer.is_empty() {
+                    if editor.buffer.is_empty() && editor.pending.is_empty() {
                         return ReadLine::Eof;
                     }
                 }
@@ -207,41 +276,94 @@ impl<I: AsyncRead + Unpin, O: AsyncWrite + AsRawFd + Unpin, C: Completer> Editor
         }
 
         editor.history_cursor = None;
+        editor.completer.finish_line();
+
+        // Move the command line out of our buffer, along with any lines that
+        // preceded it.
+        let mut text = std::mem::take(&mut editor.pending);
+        text.push_str(&editor.buffer.take_text());
 
         // Record line to history.
-        editor.history_session.add(editor.buffer.text());
+        editor.history_session.add(&text);
 
-        // Move the command line out of out buffer and return it.
-        ReadLine::Input(editor.buffer.take_text())
+        ReadLine::Input(text)
     }
 
-    /// Redraw the buffer.
-    pub async fn redraw(&mut self, fiber: &mut Fiber) {
-        let prompt = self.get_prompt_str(fiber).await;
+    /// Redraw a submitted command with the transient prompt in place of the
+    /// full one, to keep scrollback compact. Nothing is redrawn if no
+    /// transient prompt is set.
+    async fn draw_transient_prompt(&mut self, fiber: &mut Fiber) {
+        let prompt = match resolve_prompt(fiber, TRANSIENT_PROMPT_GLOBAL).await {
+            Some(prompt) => prompt,
+            None => return,
+        };
+
+        let tab_width = self.get_theme().tab_width.unwrap_or(DEFAULT_TAB_WIDTH);
+        let text = width::expand_tabs(self.buffer.text(), tab_width, 0);
+
+        self.clear_from_prompt().await;
+        self.stdout
+            .write_all(format!("{}{}", prompt, text).as_bytes())
+            .await
+            .unwrap();
+    }
+
+    /// Move the cursor back to the start of the prompt, including any rows
+    /// that a long command line wrapped onto, and clear everything after it.
+    async fn clear_from_prompt(&mut self) {
+        if self.cursor_row > 0 {
+            self.stdout
+                .command(Command::MoveCursorUp(self.cursor_row))
+                .await
+                .unwrap();
+            self.cursor_row = 0;
+        }
 
-        // Render the current buffer text.
         self.stdout.write_all(b"\r").await.unwrap();
         self.stdout
             .command(Command::ClearAfterCursor)
             .await
             .unwrap();
+    }
+
+    /// Redraw the buffer.
+    pub async fn redraw(&mut self, fiber: &mut Fiber) {
+        let prompt = self.get_prompt_str(fiber).await;
+        let tab_width = self.get_theme().tab_width.unwrap_or(DEFAULT_TAB_WIDTH);
+
+        // Expand tabs in the text on either side of the cursor separately, so
+        // that we know how far to move the cursor back after rendering.
+        let (before_cursor, after_cursor) = self.buffer.text().split_at(self.buffer.cursor());
+        let before_cursor = width::expand_tabs(before_cursor, tab_width, 0);
+        let after_cursor = width::expand_tabs(after_cursor, tab_width, width::display_width(&before_cursor));
+        let end_column = width::display_width(&before_cursor) + width::display_width(&after_cursor);
+
+        // Keep track of how much is written, to know which row the cursor
+        // ends up on. Moving the cursor left does not change its row.
+        let mut written = width::display_width(&prompt) + end_column;
+
+        // Render the current buffer text.
+        self.clear_from_prompt().await;
         self.stdout
-            .write_all(format!("{}{}", prompt, self.buffer.text()).as_bytes())
+            .write_all(format!("{}{}{}", prompt, before_cursor, after_cursor).as_bytes())
             .await
             .unwrap();
 
         // Render the top completion suggestion.
         if !self.buffer.is_empty() {
-            if let Some(suggestion) = self.completer.complete_one(self.buffer.text()) {
+            if let Some(suggestion) = self.completer.complete_one(fiber, self.buffer.text()).await {
                 if let Some(suffix) = suggestion.strip_prefix(self.buffer.text()) {
                     if !suffix.is_empty() {
+                        let suffix = width::expand_tabs(suffix, tab_width, end_column);
+                        written += width::display_width(&suffix);
+
                         self.stdout
                             .write_all(format!("{}", suffix.dimmed()).as_bytes())
                             .await
                             .unwrap();
 
                         self.stdout
-                            .command(Command::MoveCursorLeft(suffix.len()))
+                            .command(Command::MoveCursorLeft(width::display_width(&suffix)))
                             .await
                             .unwrap();
                     }
@@ -250,7 +372,7 @@ impl<I: AsyncRead + Unpin, O: AsyncWrite + AsRawFd + Unpin, C: Completer> Editor
         }
 
         // Update the cursor position.
-        let diff = self.buffer.text().len() - self.buffer.cursor();
+        let diff = width::display_width(&after_cursor);
         if diff > 0 {
             self.stdout
                 .command(Command::MoveCursorLeft(diff))
@@ -258,7 +380,47 @@ impl<I: AsyncRead + Unpin, O: AsyncWrite + AsRawFd + Unpin, C: Completer> Editor
                 .unwrap();
         }
 
+        if let Some(columns) = self.stdout.columns() {
+            self.cursor_row = width::rows_below(written, columns);
+        }
+
         // Flush all changes from the IO buffer.
         self.stdout.flush().await.unwrap();
     }
 }
+
+/// Get the prompt set by the user in the given global variable, which may
+/// either be a string or a block that returns one. Returns `None` if it is not
+/// set, or is set to something that does not give a string.
+async fn resolve_prompt(fiber: &mut Fiber, global: &str) -> Option<String> {
+    match fiber.globals().get(global) {
+        // Static prompt.
+        Value::String(ref s) => return Some(s.to_string()),
+
+        // Prompt is determined by a callback function.
+        value @ Value::Block(_) => match fiber.invoke(&value, &[]).await {
+            // Closure returned successfully.
+            Ok(Value::String(ref s)) => return Some(s.to_string()),
+
+            // Closure succeeded, but returned an invalid data type.
+            Ok(value) => {
+                log::warn!("{} function returned invalid data type: {}", global, value.type_name());
+            }
+
+            Err(e) => {
+                log::warn!("{} function threw exception: {}", global, e);
+            }
+        },
+
+        Value::Nil => {
+            // Unspecified
+        }
+
+        value => {
+            // Invalid data type
+            log::warn!("{} must be a closure or string, not '{}'", global, value.type_name());
+        }
+    }
+
+    None
+}
@@ -0,0 +1,56 @@
+//! Segments of the default prompt.
+
+use crate::theme::Prompt;
+use owo_colors::OwoColorize;
+use riptide_runtime::Value;
+
+const DEFAULT_SUCCESS_FORMAT: &str = "✓";
+const DEFAULT_FAILURE_FORMAT: &str = "✗ %s";
+
+/// Show whether the last command succeeded, given its exit status, if the
+/// theme asks for it.
+///
+/// Nothing is shown until a command has been run and the status is set.
+pub fn status_segment(theme: &Prompt, status: &Value) -> Option<String> {
+    if theme.show_status != Some(true) {
+        return None;
+    }
+
+    let status = status.as_number()?;
+
+    Some(if status == 0.0 {
+        let format = theme.success_format.as_deref().unwrap_or(DEFAULT_SUCCESS_FORMAT);
+        format.replace("%s", "0").green().to_string()
+    } else {
+        let format = theme.failure_format.as_deref().unwrap_or(DEFAULT_FAILURE_FORMAT);
+        format.replace("%s", &status.to_string()).red().to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn theme(show_status: bool) -> Prompt {
+        Prompt {
+            format: None,
+            item_separator: None,
+            item_format: None,
+            show_status: Some(show_status),
+            success_format: None,
+            failure_format: Some("exit %s".into()),
+        }
+    }
+
+    #[test]
+    fn status_is_only_shown_when_enabled() {
+        assert_eq!(status_segment(&theme(false), &Value::from(0.0)), None);
+        assert_eq!(status_segment(&theme(true), &Value::from(0.0)), Some("✓".green().to_string()));
+    }
+
+    #[test]
+    fn failure_shows_exit_status() {
+        assert_eq!(status_segment(&theme(true), &Value::Nil), None);
+        assert_eq!(status_segment(&theme(true), &Value::from(127.0)), Some("exit 127".red().to_string()));
+    }
+}
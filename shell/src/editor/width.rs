@@ -0,0 +1,90 @@
+//! Measuring how much space text takes up when displayed in a terminal.
+
+use unicode_width::UnicodeWidthChar;
+
+/// Replace tabs in the given text with spaces up to the next tab stop.
+///
+/// Tab stops are every `tab_width` columns, counted from `start_column`. Text
+/// is rendered with tabs expanded so that its width on screen is known,
+/// regardless of how the terminal treats tabs.
+pub fn expand_tabs(text: &str, tab_width: usize, start_column: usize) -> String {
+    let tab_width = tab_width.max(1);
+    let mut expanded = String::with_capacity(text.len());
+    let mut column = start_column;
+
+    for c in text.chars() {
+        if c == '\t' {
+            let spaces = tab_width - column % tab_width;
+            expanded.extend(std::iter::repeat(' ').take(spaces));
+            column += spaces;
+        } else {
+            expanded.push(c);
+            column += c.width().unwrap_or(0);
+        }
+    }
+
+    expanded
+}
+
+/// Get the number of terminal columns the given text occupies.
+///
+/// Wide characters such as CJK and emoji take up two columns, while control
+/// and combining characters take up none. Escape sequences, such as those that
+/// color a prompt, take up no columns either. Tabs should be expanded first.
+pub fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip a control sequence up to and including its final byte.
+            if chars.as_str().starts_with('[') {
+                chars.next();
+                chars.find(|c| ('@'..='~').contains(c));
+            }
+        } else {
+            width += c.width().unwrap_or(0);
+        }
+    }
+
+    width
+}
+
+/// Get the number of rows below the first that the cursor is on after writing
+/// text of the given width to a terminal with the given number of columns.
+///
+/// A terminal does not wrap to the next row until something is written past
+/// the end of a full row, so text that exactly fills its last row leaves the
+/// cursor on that row.
+pub fn rows_below(width: usize, columns: usize) -> usize {
+    width.saturating_sub(1) / columns.max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_of_wide_and_multibyte_text() {
+        assert_eq!(display_width("echo"), 4);
+        assert_eq!(display_width("héllo"), 5);
+        assert_eq!(display_width("日本語"), 6);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(display_width("\x1b[34m~/src\x1b[0m $ "), 8);
+    }
+
+    #[test]
+    fn rows_of_wrapped_text() {
+        assert_eq!(rows_below(0, 80), 0);
+        assert_eq!(rows_below(80, 80), 0);
+        assert_eq!(rows_below(81, 80), 1);
+        assert_eq!(rows_below(200, 80), 2);
+    }
+
+    #[test]
+    fn tabs_expand_to_next_tab_stop() {
+        assert_eq!(expand_tabs("a\tb", 4, 0), "a   b");
+        assert_eq!(expand_tabs("\t", 4, 4), "    ");
+        assert_eq!(expand_tabs("日\tb", 4, 1), "日 b");
+    }
+}
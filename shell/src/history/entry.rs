@@ -41,13 +41,17 @@ impl TryFrom<&Row<'_>> for CommandEntry {
 pub struct EntryCursor {
     db: Rc<Connection>,
     key: Option<(i64, i64)>,
+
+    /// Only include commands run in this directory, if set.
+    cwd: Option<String>,
 }
 
 impl EntryCursor {
-    pub(super) fn new(db: &Rc<Connection>) -> Self {
+    pub(super) fn new(db: &Rc<Connection>, cwd: Option<String>) -> Self {
         Self {
             db: db.clone(),
             key: None,
+            cwd,
         }
     }
 
@@ -56,11 +60,11 @@ impl EntryCursor {
             let (timestamp, entry) = self.db.query_row(
                 r#"
                     SELECT rowid, command, cwd, timestamp FROM command_history
-                    WHERE (timestamp, rowid) > (?, ?)
+                    WHERE (timestamp, rowid) > (?1, ?2) AND (?3 IS NULL OR cwd = ?3)
                     ORDER BY timestamp ASC, rowid ASC
                     LIMIT 1
                 "#,
-                params![timestamp, rowid],
+                params![timestamp, rowid, self.cwd],
                 |row| Ok((row.get("timestamp")?, CommandEntry::try_from(row)?)),
             ).ok()?;
 
@@ -81,21 +85,22 @@ impl Iterator for EntryCursor {
             self.db.query_row(
                 r#"
                     SELECT rowid, command, cwd, timestamp FROM command_history
-                    WHERE (timestamp, rowid) < (?, ?)
+                    WHERE (timestamp, rowid) < (?1, ?2) AND (?3 IS NULL OR cwd = ?3)
                     ORDER BY timestamp DESC, rowid DESC
                     LIMIT 1
                 "#,
-                params![timestamp, rowid],
+                params![timestamp, rowid, self.cwd],
                 |row| Ok((row.get("timestamp")?, CommandEntry::try_from(row)?)),
             ).ok()?
         } else {
             self.db.query_row(
                 r#"
                     SELECT rowid, command, cwd, timestamp FROM command_history
+                    WHERE ?1 IS NULL OR cwd = ?1
                     ORDER BY timestamp DESC, rowid DESC
                     LIMIT 1
                 "#,
-                params![],
+                params![self.cwd],
                 |row| Ok((row.get("timestamp")?, CommandEntry::try_from(row)?)),
             ).ok()?
         };
@@ -21,10 +21,15 @@
 //!   for segregating the history by session in some queries.
 //! - `timestamp`: Timestamp of when the command was run.
 //!
+//! The table can be limited to a maximum number of entries, in which case the
+//! oldest entries are pruned from time to time as new ones are added. Duplicate
+//! commands are handled according to [`Duplicates`].
+//!
 //! ## Directory history
 
 use rusqlite::{params, Connection, Row};
 use std::{
+    cell::Cell,
     convert::TryFrom,
     error::Error,
     path::Path,
@@ -34,15 +39,45 @@ use std::{
 mod entry;
 mod session;
 
-pub use entry::{CommandEntry, EntryCursor};
+pub use entry::EntryCursor;
 pub use session::Session;
 
 type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
+/// The maximum number of commands kept in history if not configured otherwise.
+pub const DEFAULT_MAX_ENTRIES: usize = 10000;
+
 /// A connection to a history database.
 #[derive(Clone)]
 pub struct History {
     db: Rc<Connection>,
+
+    /// Shared with sessions, which apply the settings as they add commands.
+    settings: Rc<Cell<Settings>>,
+}
+
+/// Settings for how commands are added to history.
+#[derive(Clone, Copy, Default)]
+struct Settings {
+    /// Maximum number of commands to keep, or `None` to keep all of them.
+    max_entries: Option<usize>,
+
+    duplicates: Duplicates,
+}
+
+/// How to handle a command that is already in history when it is added.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum Duplicates {
+    /// Record every command.
+    Keep,
+
+    /// Skip a command that is the same as the previous command entered in the
+    /// same session.
+    #[default]
+    IgnoreConsecutive,
+
+    /// Remove all earlier occurrences of a command when it is added again.
+    Erase,
 }
 
 /// Aggregated information about a particular command string.
@@ -82,6 +117,7 @@ impl History {
     fn from_connection(connection: Connection) -> Result<Self> {
         let history = Self {
             db: Rc::new(connection),
+            settings: Rc::new(Cell::new(Settings::default())),
         };
 
         match history.get_version() {
@@ -95,7 +131,38 @@ impl History {
 
     /// Create a new history session and return it.
     pub fn create_session(&self) -> Session {
-        Session::new(&self.db)
+        Session::new(&self.db, &self.settings)
+    }
+
+    /// Set the maximum number of commands to keep in history, or `None` for no
+    /// limit. Any commands already over the limit are pruned right away.
+    pub fn set_max_entries(&self, max_entries: Option<usize>) -> Result<()> {
+        self.settings.set(Settings {
+            max_entries,
+            ..self.settings.get()
+        });
+
+        if let Some(max_entries) = max_entries {
+            prune(&self.db, max_entries)?;
+        }
+
+        Ok(())
+    }
+
+    /// Set how commands that are already in history are handled when added
+    /// again.
+    pub fn set_duplicates(&self, duplicates: Duplicates) {
+        self.settings.set(Settings {
+            duplicates,
+            ..self.settings.get()
+        });
+    }
+
+    /// Remove all commands from history.
+    pub fn clear(&self) -> Result<()> {
+        self.db.execute("DELETE FROM command_history", params![])?;
+
+        Ok(())
     }
 
     fn get_version(&self) -> i64 {
@@ -136,7 +203,13 @@ impl History {
     }
 
     pub fn entries(&self) -> EntryCursor {
-        EntryCursor::new(&self.db)
+        EntryCursor::new(&self.db, None)
+    }
+
+    /// Get the commands that were run in the given directory, from newest to
+    /// oldest.
+    pub fn entries_in_dir(&self, cwd: impl Into<String>) -> EntryCursor {
+        EntryCursor::new(&self.db, Some(cwd.into()))
     }
 
     // /// Query for frequent commands.
@@ -177,6 +250,25 @@ impl History {
     }
 }
 
+/// Delete the oldest commands in history so that at most `max_entries` remain.
+///
+/// Since commands are inserted in order, this only needs to find the rowid of
+/// the oldest command to keep.
+fn prune(db: &Connection, max_entries: usize) -> rusqlite::Result<usize> {
+    if max_entries == 0 {
+        return db.execute("DELETE FROM command_history", params![]);
+    }
+
+    db.execute(
+        "
+        DELETE FROM command_history WHERE rowid < (
+            SELECT rowid FROM command_history ORDER BY rowid DESC LIMIT 1 OFFSET ?
+        )
+        ",
+        params![max_entries as i64 - 1],
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,4 +288,83 @@ mod tests {
             assert_eq!(cursor.next().unwrap().command(), format!("echo {}", 8 - i));
         }
     }
+
+    fn commands(history: &History) -> Vec<String> {
+        history.entries().map(|entry| entry.command().to_owned()).collect()
+    }
+
+    #[test]
+    fn prune_oldest_entries() {
+        let history = History::in_memory().unwrap();
+        let session = history.create_session();
+
+        for i in 0..5 {
+            session.add(format!("echo {}", i));
+        }
+
+        // Existing entries are pruned as soon as a limit is set.
+        history.set_max_entries(Some(3)).unwrap();
+        assert_eq!(commands(&history), ["echo 4", "echo 3", "echo 2"]);
+
+        session.add("echo 5");
+        assert_eq!(commands(&history), ["echo 5", "echo 4", "echo 3"]);
+    }
+
+    #[test]
+    fn ignore_consecutive_duplicates() {
+        let history = History::in_memory().unwrap();
+        let session = history.create_session();
+
+        session.add("echo 1");
+        session.add("echo 1");
+        session.add("echo 2");
+        session.add("echo 1");
+
+        assert_eq!(commands(&history), ["echo 1", "echo 2", "echo 1"]);
+
+        // Only the previous command from the same session counts.
+        history.create_session().add("echo 1");
+
+        assert_eq!(commands(&history), ["echo 1", "echo 1", "echo 2", "echo 1"]);
+    }
+
+    #[test]
+    fn erase_duplicates() {
+        let history = History::in_memory().unwrap();
+        history.set_duplicates(Duplicates::Erase);
+        let session = history.create_session();
+
+        session.add("echo 1");
+        session.add("echo 2");
+        session.add("echo 1");
+
+        assert_eq!(commands(&history), ["echo 1", "echo 2"]);
+    }
+
+    #[test]
+    fn filter_entries_by_dir() {
+        let history = History::in_memory().unwrap();
+        let session = history.create_session();
+
+        session.add("echo here");
+        history.db.execute("UPDATE command_history SET cwd = '/elsewhere'", params![]).unwrap();
+        session.add("echo there");
+
+        let commands = history.entries_in_dir("/elsewhere")
+            .map(|entry| entry.command().to_owned())
+            .collect::<Vec<_>>();
+
+        assert_eq!(commands, ["echo here"]);
+    }
+
+    #[test]
+    fn clear_removes_all_entries() {
+        let history = History::in_memory().unwrap();
+        let session = history.create_session();
+
+        session.add("echo 1");
+        history.clear().unwrap();
+
+        assert!(commands(&history).is_empty());
+    }
 }
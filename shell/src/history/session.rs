@@ -1,17 +1,26 @@
-use rusqlite::{params, Connection};
+use super::{Duplicates, Settings};
+use rusqlite::{params, Connection, OptionalExtension};
 use std::{
+    cell::Cell,
     env,
     process,
     rc::Rc,
 };
 
+/// The most commands that are added between each time history is pruned.
+const MAX_PRUNE_INTERVAL: usize = 100;
+
 pub struct Session {
     db: Rc<Connection>,
     id: i64,
+    settings: Rc<Cell<Settings>>,
+
+    /// Number of commands added since history was last pruned.
+    added: Cell<usize>,
 }
 
 impl Session {
-    pub(super) fn new(db: &Rc<Connection>) -> Self {
+    pub(super) fn new(db: &Rc<Connection>, settings: &Rc<Cell<Settings>>) -> Self {
         db.execute(
             "INSERT INTO session_history (pid) VALUES (?)",
             params![process::id()],
@@ -20,20 +29,72 @@ impl Session {
         Self {
             db: db.clone(),
             id: db.last_insert_rowid(),
+            settings: settings.clone(),
+            added: Cell::new(0),
         }
     }
 
     /// Record a command and add it to the history.
     pub fn add(&self, command: impl Into<String>) {
+        let command = command.into();
         let cwd = env::current_dir()
             .ok()
             .and_then(|path| path.to_str().map(String::from));
 
+        match self.settings.get().duplicates {
+            Duplicates::Keep => {}
+            Duplicates::IgnoreConsecutive => {
+                if self.last_command().as_ref() == Some(&command) {
+                    return;
+                }
+            }
+            Duplicates::Erase => {
+                self.db
+                    .execute("DELETE FROM command_history WHERE command = ?", params![command])
+                    .unwrap();
+            }
+        }
+
         self.db
             .execute(
                 "INSERT INTO command_history (session_id, command, cwd) VALUES (?, ?, ?)",
-                params![self.id, command.into(), cwd],
+                params![self.id, command, cwd],
             )
             .unwrap();
+
+        self.prune_if_needed();
+    }
+
+    /// Get the command most recently added by this session.
+    fn last_command(&self) -> Option<String> {
+        self.db
+            .query_row(
+                "SELECT command FROM command_history WHERE session_id = ? ORDER BY rowid DESC LIMIT 1",
+                params![self.id],
+                |row| row.get(0),
+            )
+            .optional()
+            .unwrap()
+    }
+
+    /// Prune history if enough commands have been added since the last time.
+    ///
+    /// Pruning after every command would be wasteful, so history is allowed to
+    /// grow a little past the limit in between.
+    fn prune_if_needed(&self) {
+        let max_entries = match self.settings.get().max_entries {
+            Some(max_entries) => max_entries,
+            None => return,
+        };
+
+        self.added.set(self.added.get() + 1);
+
+        if self.added.get() >= (max_entries / 10).clamp(1, MAX_PRUNE_INTERVAL) {
+            self.added.set(0);
+
+            if let Err(e) = super::prune(&self.db, max_entries) {
+                log::warn!("failed to prune history: {}", e);
+            }
+        }
     }
 }
@@ -3,13 +3,14 @@
 #![allow(dead_code)]
 
 use crate::editor::{Editor, ReadLine};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use riptide_runtime::{
     prelude::*,
+    ForeignFn,
     syntax::source::SourceFile,
 };
 use std::{
-    io::{IsTerminal, Read},
+    io::{IsTerminal, Read, Write},
     path::{Path, PathBuf},
     process::ExitCode,
 };
@@ -24,6 +25,7 @@ mod os;
 mod paths;
 mod session;
 mod theme;
+mod vcs;
 
 #[derive(Debug, Parser)]
 #[command(author, version, about, long_about = None)]
@@ -36,17 +38,48 @@ struct Options {
     #[arg(short = 'l', long = "login")]
     login: bool,
 
-    /// Set the verbosity level
+    /// Set the verbosity level with `-v`, or silence output with `-q`
     #[command(flatten)]
     verbose: clap_verbosity_flag::Verbosity,
 
-    /// Silence all output
-    #[arg(short = 'q', long = "quiet")]
-    quiet: bool,
+    /// Print each command to standard error before running it
+    #[arg(short = 'x', long = "trace")]
+    trace: bool,
+
+    /// Throw an exception when an external command exits with a nonzero status
+    #[arg(short = 'e', long = "strict")]
+    strict: bool,
+
+    /// Start without loading the standard library.
+    ///
+    /// Only the runtime builtins will be available. This is useful for fast
+    /// startup of scripts that do not need any standard library modules.
+    #[arg(long = "no-stdlib")]
+    no_stdlib: bool,
+
+    /// Start an interactive session without loading the user config script.
+    #[arg(long = "no-config")]
+    no_config: bool,
 
     /// File to execute
     file: Option<PathBuf>,
 
+    /// Print the syntax tree of the input and exit without executing it.
+    ///
+    /// The tree is printed in debug format by default, or as JSON with
+    /// `--ast=json`. The input is read from the given file, or from standard
+    /// input if no file is given.
+    #[arg(
+        long = "ast",
+        value_enum,
+        value_name = "FORMAT",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "debug",
+        conflicts_with = "commands",
+    )]
+    ast: Option<AstFormat>,
+
     /// Open a session in private mode.
     ///
     /// In private mode, session history is kept independent from other sessions
@@ -54,6 +87,27 @@ struct Options {
     /// session will be forgotten when the session terminates.
     #[arg(long = "private")]
     private: bool,
+
+    /// Maximum number of commands to keep in history, or 0 for no limit.
+    #[arg(long = "history-size", value_name = "N", default_value_t = history::DEFAULT_MAX_ENTRIES)]
+    history_size: usize,
+
+    /// How to handle commands that are already in history.
+    #[arg(long = "history-duplicates", value_enum, value_name = "MODE", default_value_t)]
+    history_duplicates: history::Duplicates,
+
+    /// Browse only the history of commands run in the current directory.
+    ///
+    /// This can be toggled at the prompt with Ctrl-G.
+    #[arg(long = "directory-history")]
+    directory_history: bool,
+}
+
+/// Output format for printing a syntax tree.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum AstFormat {
+    Debug,
+    Json,
 }
 
 /// Entrypoint of the program. This just does some boring setup and teardown
@@ -73,13 +127,27 @@ fn main() -> ExitCode {
         .build()
         .unwrap();
 
-    // Run real main and return the exit code.
-    rt.block_on(real_main(options)).unwrap_or_default()
+    // Run real main and return the exit code. Fibers are spawned as local
+    // tasks, so everything runs inside a local task set.
+    tokio::task::LocalSet::new()
+        .block_on(&rt, real_main(options))
+        .unwrap_or_default()
 }
 
 /// Main program body.
 async fn real_main(options: Options) -> Option<ExitCode> {
-    let mut fiber = create_runtime().await;
+    // Dumping the syntax tree never executes anything, so no runtime is needed.
+    if let Some(format) = options.ast {
+        return Some(dump_ast(options.file.as_deref(), format));
+    }
+
+    let mut fiber = create_runtime(!options.no_stdlib).await;
+    fiber.set_trace(options.trace);
+    fiber.set_strict(options.strict);
+
+    if options.login || is_login_name() {
+        load_profile(&mut fiber).await;
+    }
 
     // If at least one command is given, execute those in order and exit.
     if !options.commands.is_empty() {
@@ -117,6 +185,48 @@ async fn real_main(options: Options) -> Option<ExitCode> {
     })
 }
 
+/// Parse a file, or standard input if no file is given, and print its syntax
+/// tree.
+fn dump_ast(path: Option<&Path>, format: AstFormat) -> ExitCode {
+    let source = match path {
+        Some(path) => match SourceFile::open(path) {
+            Ok(source) => source,
+            Err(e) => {
+                log::error!("opening file {:?}: {}", path, e);
+                return ExitCode::from(exitcode::NOINPUT as u8);
+            }
+        },
+        None => {
+            let mut source = Vec::new();
+
+            if let Err(e) = std::io::stdin().read_to_end(&mut source) {
+                log::error!("{}", e);
+                return ExitCode::from(exitcode::IOERR as u8);
+            }
+
+            SourceFile::virtual_from_bytes("<stdin>", source)
+        }
+    };
+
+    let ast = match riptide_syntax::parse(source) {
+        Ok(ast) => ast,
+        Err(e) => {
+            log::error!("{}", e);
+            return ExitCode::from(exitcode::DATAERR as u8);
+        }
+    };
+
+    let output = match format {
+        AstFormat::Debug => format!("{:#?}", ast),
+        AstFormat::Json => serde_json::to_string_pretty(&ast).unwrap(),
+    };
+
+    // Ignore errors writing the output, such as when piped into `head`.
+    let _ = writeln!(std::io::stdout(), "{}", output);
+
+    ExitCode::SUCCESS
+}
+
 async fn execute_file(fiber: &mut Fiber, path: impl AsRef<Path>) {
     let path = path.as_ref();
     let source = match SourceFile::open(path) {
@@ -136,20 +246,74 @@ async fn execute_file(fiber: &mut Fiber, path: impl AsRef<Path>) {
 
 async fn execute_stdin(fiber: &mut Fiber) {
     let mut stdin = std::io::stdin();
-    let mut source = String::new();
+    let mut source = Vec::new();
 
-    if let Err(e) = stdin.read_to_string(&mut source) {
+    if let Err(e) = stdin.read_to_end(&mut source) {
         log::error!("{}", e);
         fiber.exit(exitcode::IOERR);
         return;
     }
 
-    if let Err(e) = fiber.execute(None, SourceFile::r#virtual("<stdin>", source)).await {
+    let source = SourceFile::virtual_from_bytes("<stdin>", source);
+
+    if let Err(e) = fiber.execute(None, source).await {
         log::error!("{}", e);
         fiber.exit(1);
     }
 }
 
+/// Check if the shell was started with a name beginning with a dash, which is
+/// how `login` and similar programs start a login shell.
+fn is_login_name() -> bool {
+    std::env::args_os()
+        .next()
+        .is_some_and(|name| name.to_string_lossy().starts_with('-'))
+}
+
+/// Execute the user profile script, if it exists. Errors are reported, but do
+/// not prevent the shell from starting.
+async fn load_profile(fiber: &mut Fiber) {
+    if let Some((path, source)) = open_user_script("profile", paths::profile_script()) {
+        if let Err(e) = fiber.execute(Some("main"), source).await {
+            log::error!("error in profile {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Execute the user config script in the given scope, if it exists. Errors are
+/// reported, but do not prevent the session from starting.
+async fn load_config(fiber: &mut Fiber, scope: Table) {
+    if let Some((path, source)) = open_user_script("config", paths::init_script()) {
+        if let Err(e) = fiber.execute_in_scope(Some("main"), source, scope).await {
+            log::error!("error in config {:?}: {}", path, e);
+        }
+    }
+}
+
+/// Open a script in the user's config directory, returning `None` if there is
+/// no such script or it could not be read.
+fn open_user_script(kind: &str, path: std::io::Result<PathBuf>) -> Option<(PathBuf, SourceFile)> {
+    let path = match path {
+        Ok(path) => path,
+        Err(e) => {
+            log::warn!("finding config directory: {}", e);
+            return None;
+        }
+    };
+
+    if !path.exists() {
+        return None;
+    }
+
+    match SourceFile::open(&path) {
+        Ok(source) => Some((path, source)),
+        Err(e) => {
+            log::error!("reading {} {:?}: {}", kind, path, e);
+            None
+        }
+    }
+}
+
 /// Main loop for an interactive shell session.
 ///
 /// It is also worth noting that this function is infallible. Once set up, the
@@ -162,19 +326,64 @@ async fn interactive_main(fiber: &mut Fiber, options: Options) {
         history::History::open_default().unwrap()
     };
 
+    let max_entries = Some(options.history_size).filter(|&size| size > 0);
+
+    if let Err(e) = history.set_max_entries(max_entries) {
+        log::warn!("failed to prune history: {}", e);
+    }
+
+    history.set_duplicates(options.history_duplicates);
+
     let session = history.create_session();
 
+    if let Err(e) = fiber.register_fn("history-clear", {
+        let history = history.clone();
+
+        ForeignFn::from_closure(move |_, _| {
+            let history = history.clone();
+
+            Box::pin(async move {
+                match history.clear() {
+                    Ok(()) => Ok(Value::Nil),
+                    Err(e) => Err(Exception::from(format!("failed to clear history: {}", e))),
+                }
+            })
+        })
+    }) {
+        log::warn!("{}", e);
+    }
+
     // We want successive commands to act like they are being executed in the
     // same file, so set up a shared scope to execute them in.
     let scope = riptide_runtime::table!();
 
-    // Prepare this scope by running an init script in it.
-    let interactive = SourceFile::r#virtual("<input>", include_str!("interactive.rt"));
-    fiber.execute_in_scope(Some("main"), interactive, scope.clone())
-        .await
-        .expect("bug in interactive.rt");
+    // Provided for use in a custom prompt.
+    if let Err(e) = fiber.register_fn("git-status", vcs::git_status) {
+        log::warn!("{}", e);
+    }
+
+    // Prepare this scope by running an init script in it. The script depends
+    // on the standard library, so skip it if the stdlib was not loaded.
+    if !options.no_stdlib {
+        let interactive = SourceFile::r#virtual("<input>", include_str!("interactive.rt"));
+        fiber.execute_in_scope(Some("main"), interactive, scope.clone())
+            .await
+            .expect("bug in interactive.rt");
+    }
+
+    // Run the user's config script in the same scope, so that anything it
+    // defines is available at the prompt.
+    if !options.no_config {
+        load_config(fiber, scope.clone()).await;
+    }
 
-    let completer = completion::history::HistoryCompleter::new(history.clone());
+    let mut completer = completion::Composite::new();
+    completer.add(completion::history::HistoryCompleter::new(history.clone()));
+    completer.add(completion::scope::ScopeCompleter::new(vec![scope.clone(), fiber.globals().clone()]));
+
+    // Completions registered by scripts take priority for the commands they
+    // are registered for.
+    let completer = completion::script::ScriptCompleter::new(completer);
 
     let mut editor = Editor::new(
         fiber.stdin().try_clone().unwrap(),
@@ -184,6 +393,8 @@ async fn interactive_main(fiber: &mut Fiber, options: Options) {
         completer,
     );
 
+    editor.set_directory_history(options.directory_history);
+
     while fiber.exit_code().is_none() {
         match editor.read_line(fiber).await {
             ReadLine::Input(line) => {
@@ -201,19 +412,28 @@ async fn interactive_main(fiber: &mut Fiber, options: Options) {
                         println!();
                     }
 
-                    result = fiber.execute_in_scope(Some("main"), SourceFile::r#virtual("<tty>", line), scope.clone()) => match result {
-                        Ok(Value::Nil) => {}
-                        Ok(value) => {
-                            if let Some(values) = value.as_list() {
-                                for value in values {
+                    result = fiber.execute_in_scope(Some("main"), SourceFile::r#virtual("<tty>", line), scope.clone()) => {
+                        // Write out anything the command left buffered, so that
+                        // it appears before the result or error.
+                        let _ = fiber.io().flush().await;
+
+                        match result {
+                            Ok(value) => {
+                                // Make the result available to the next command as
+                                // `$_`. Setting nil clears any previous result.
+                                scope.set("_", value.clone());
+
+                                if let Some(values) = value.as_list() {
+                                    for value in values {
+                                        println!("{}", value);
+                                    }
+                                } else if !value.is_nil() {
                                     println!("{}", value);
                                 }
-                            } else {
-                                println!("{}", value);
                             }
-                        }
-                        Err(e) => if fiber.exit_code().is_none() {
-                            log::error!("{}", e)
+                            Err(e) => if fiber.exit_code().is_none() {
+                                log::error!("{}", e)
+                            }
                         }
                     }
                 }
@@ -227,8 +447,23 @@ async fn interactive_main(fiber: &mut Fiber, options: Options) {
     }
 }
 
-async fn create_runtime() -> Fiber {
+async fn create_runtime(stdlib: bool) -> Fiber {
     let mut fiber = riptide_runtime::init().await.expect("error in runtime initialization");
-    riptide_stdlib::init(&mut fiber).await.expect("error in runtime initialization");
+
+    if stdlib {
+        riptide_stdlib::init(&mut fiber).await.expect("error in runtime initialization");
+    } else {
+        log::debug!("skipping standard library initialization");
+    }
+
+    // Terminal control is provided by the shell, since it already knows how.
+    if let Err(e) = fiber.register_fn("clear", editor::command::clear) {
+        log::warn!("{}", e);
+    }
+
+    if let Err(e) = fiber.register_fn("cursor-home", editor::command::cursor_home) {
+        log::warn!("{}", e);
+    }
+
     fiber
 }
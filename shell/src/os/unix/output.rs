@@ -27,6 +27,16 @@ impl<O: AsRawFd> TerminalOutput<O> {
         })
     }
 
+    /// Get the width of the terminal in columns, if it is known.
+    pub fn columns(&self) -> Option<usize> {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+
+        match unsafe { libc::ioctl(self.as_raw_fd(), libc::TIOCGWINSZ, &mut size) } {
+            0 if size.ws_col > 0 => Some(size.ws_col as usize),
+            _ => None,
+        }
+    }
+
     pub fn set_raw_mode(&mut self, raw: bool) -> io::Result<()> {
         if raw {
             termios::tcsetattr(self.as_raw_fd(), 0, &self.raw_termios)
@@ -38,10 +48,7 @@ impl<O: AsRawFd> TerminalOutput<O> {
 
 impl<O: AsyncWrite + AsRawFd + Unpin> TerminalOutput<O> {
     pub async fn command(&mut self, command: Command) -> io::Result<()> {
-        self.write_all(match command {
-            Command::ClearAfterCursor => String::from("\x1b[J"),
-            Command::MoveCursorLeft(n) => format!("\x1b[{}D", n),
-        }.as_bytes()).await
+        self.write_all(command.escape_sequence().as_bytes()).await
     }
 }
 
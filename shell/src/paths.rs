@@ -19,6 +19,16 @@ pub fn config_file() -> io::Result<PathBuf> {
     config_dir().map(|dir| dir.join("riptide.toml"))
 }
 
+/// Path to the user's script that is run at the start of interactive sessions.
+pub fn init_script() -> io::Result<PathBuf> {
+    config_dir().map(|dir| dir.join("config.rt"))
+}
+
+/// Path to the user's script that is run once when starting a login shell.
+pub fn profile_script() -> io::Result<PathBuf> {
+    config_dir().map(|dir| dir.join("profile.rt"))
+}
+
 pub fn history_db() -> io::Result<PathBuf> {
     data_dir().map(|dir| dir.join("history.db"))
 }
@@ -3,6 +3,9 @@
 pub struct Theme {
     pub name: String,
     pub prompt: Option<Prompt>,
+
+    /// Number of columns between tab stops when displaying tabs.
+    pub tab_width: Option<usize>,
 }
 
 impl Default for Theme {
@@ -17,4 +20,14 @@ pub struct Prompt {
     pub format: Option<String>,
     pub item_separator: Option<String>,
     pub item_format: Option<String>,
+
+    /// Whether to show if the last command succeeded at the start of the
+    /// prompt.
+    pub show_status: Option<bool>,
+
+    /// Shown when the last command succeeded.
+    pub success_format: Option<String>,
+
+    /// Shown when the last command failed, where `%s` is the exit status.
+    pub failure_format: Option<String>,
 }
@@ -0,0 +1,223 @@
+//! Version control status for use in the prompt.
+//!
+//! Status is found by reading the repository files directly instead of running
+//! `git`, since it is checked every time the prompt is shown.
+
+use riptide_runtime::{prelude::*, table, throw};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fs,
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// How long a status is reused for before checking the repository again.
+const CACHE_TTL: Duration = Duration::from_secs(2);
+
+/// The most files in the index to check for changes. In larger repositories
+/// whether there are changes is reported as unknown instead.
+const MAX_ENTRIES: usize = 10_000;
+
+/// Mode of an index entry for a submodule, which is not checked for changes.
+const GITLINK_MODE: u32 = 0o160000;
+
+thread_local! {
+    static CACHE: RefCell<HashMap<PathBuf, (Instant, Option<Status>)>> = RefCell::default();
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Status {
+    /// The name of the checked out branch, or the abbreviated commit hash if
+    /// no branch is checked out.
+    branch: String,
+
+    /// Whether any files have been changed since they were last staged, if
+    /// known.
+    dirty: Option<bool>,
+}
+
+/// Get the git status of the current directory, or of the given path, as a
+/// table with a `branch` name and a `dirty` flag. Returns nil if the directory
+/// is not in a git repository.
+///
+/// Only tracked files that have been changed since they were last staged are
+/// considered dirty. The `dirty` flag is nil if there are too many files in the
+/// repository to check quickly.
+pub async fn git_status(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let dir = match args.first() {
+        Some(Value::String(path)) => PathBuf::from(path.as_os_str()),
+        Some(value) => throw!("path must be a string, got {}", value.type_name()),
+        None => PathBuf::from(fiber.current_dir().to_string()),
+    };
+
+    Ok(match cached_status(&dir) {
+        Some(status) => table! {
+            "branch" => status.branch,
+            "dirty" => status.dirty.map(Value::from).unwrap_or_default(),
+        }.into(),
+        None => Value::Nil,
+    })
+}
+
+fn cached_status(dir: &Path) -> Option<Status> {
+    let cached = CACHE.with(|cache| match cache.borrow().get(dir) {
+        Some((checked, status)) if checked.elapsed() < CACHE_TTL => Some(status.clone()),
+        _ => None,
+    });
+
+    if let Some(status) = cached {
+        return status;
+    }
+
+    let status = status(dir);
+
+    CACHE.with(|cache| {
+        let mut cache = cache.borrow_mut();
+
+        // Forget directories that are no longer fresh, so that the cache does
+        // not grow forever.
+        cache.retain(|_, (checked, _)| checked.elapsed() < CACHE_TTL);
+        cache.insert(dir.to_owned(), (Instant::now(), status.clone()));
+    });
+
+    status
+}
+
+fn status(dir: &Path) -> Option<Status> {
+    let (work_tree, git_dir) = find_repository(dir)?;
+    let head = fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+
+    let branch = match head.strip_prefix("ref: ") {
+        Some(reference) => reference.strip_prefix("refs/heads/").unwrap_or(reference).to_owned(),
+        None => head.chars().take(7).collect(),
+    };
+
+    let dirty = fs::read(git_dir.join("index"))
+        .ok()
+        .and_then(|index| has_changes(&work_tree, &index));
+
+    Some(Status {
+        branch,
+        dirty,
+    })
+}
+
+/// Find the work tree and git directory of the repository containing a
+/// directory.
+fn find_repository(dir: &Path) -> Option<(PathBuf, PathBuf)> {
+    for dir in dir.ancestors() {
+        let dot_git = dir.join(".git");
+
+        if dot_git.is_dir() {
+            return Some((dir.to_owned(), dot_git));
+        }
+
+        // Worktrees and submodules have a file that points to the git directory
+        // instead.
+        if let Ok(contents) = fs::read_to_string(&dot_git) {
+            let git_dir = contents.trim().strip_prefix("gitdir: ")?;
+            return Some((dir.to_owned(), dir.join(git_dir)));
+        }
+    }
+
+    None
+}
+
+/// Check whether any files in the work tree have changed since they were
+/// added to the index, by comparing their size and modification time with the
+/// ones recorded in the index.
+///
+/// Returns `None` if the index can't be read, such as if it uses a format
+/// version that isn't supported or there are too many files to check.
+fn has_changes(work_tree: &Path, index: &[u8]) -> Option<bool> {
+    let read_u32 = |offset: usize| index.get(offset..offset + 4).map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()));
+
+    if index.get(..4)? != b"DIRC" {
+        return None;
+    }
+
+    let version = read_u32(4)?;
+    let count = read_u32(8)? as usize;
+
+    // Version 4 compresses paths, which we don't bother reading.
+    if !(2..=3).contains(&version) || count > MAX_ENTRIES {
+        return None;
+    }
+
+    let mut offset = 12;
+
+    for _ in 0..count {
+        let mtime = (read_u32(offset + 8)?, read_u32(offset + 12)?);
+        let mode = read_u32(offset + 24)?;
+        let size = read_u32(offset + 36)?;
+        let flags = u16::from_be_bytes(index.get(offset + 60..offset + 62)?.try_into().unwrap());
+
+        // Entries with extended flags have two more bytes before the path.
+        let path_start = offset + if flags & 0x4000 != 0 { 64 } else { 62 };
+        let path_len = index.get(path_start..)?.iter().position(|&b| b == 0)?;
+        let path = std::str::from_utf8(&index[path_start..path_start + path_len]).ok()?;
+
+        // Entries are padded with one to eight null bytes to a multiple of
+        // eight bytes.
+        offset += (path_start - offset + path_len + 8) & !7;
+
+        if mode == GITLINK_MODE {
+            continue;
+        }
+
+        let metadata = match fs::symlink_metadata(work_tree.join(path)) {
+            Ok(metadata) => metadata,
+            Err(_) => return Some(true),
+        };
+
+        // The index only stores the lower 32 bits of these values.
+        if metadata.size() as u32 != size
+            || metadata.mtime() as u32 != mtime.0
+            || metadata.mtime_nsec() as u32 != mtime.1
+        {
+            return Some(true);
+        }
+    }
+
+    Some(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{env, process};
+
+    #[test]
+    fn outside_repository() {
+        let dir = env::temp_dir().join(format!("riptide-vcs-none-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        // The temporary directory could itself be inside a repository.
+        if find_repository(&dir).is_none() {
+            assert_eq!(status(&dir), None);
+        }
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn branch_from_head() {
+        let dir = env::temp_dir().join(format!("riptide-vcs-head-{}", process::id()));
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join(".git/HEAD"), "ref: refs/heads/feature/prompt\n").unwrap();
+
+        assert_eq!(status(&dir.join("src")), Some(Status {
+            branch: String::from("feature/prompt"),
+            dirty: None,
+        }));
+
+        fs::write(dir.join(".git/HEAD"), "0123456789abcdef0123456789abcdef01234567\n").unwrap();
+        assert_eq!(status(&dir).unwrap().branch, "0123456");
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
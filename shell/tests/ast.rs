@@ -0,0 +1,51 @@
+use std::{
+    io::Write,
+    process::{Command, Output, Stdio},
+};
+
+fn run(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_riptide-shell"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn ast_prints_debug_tree_without_executing() {
+    let output = run(&["--ast"], "exit 3");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert!(stdout.starts_with("Block {"));
+    assert!(stdout.contains(r#"function: "exit""#));
+}
+
+#[test]
+fn ast_prints_json_tree() {
+    let output = run(&["--ast=json"], "echo hi");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    assert!(output.status.success());
+    assert!(stdout.contains(r#""function": "echo""#));
+    assert!(stdout.contains(r#""file": "<stdin>""#));
+}
+
+#[test]
+fn ast_reports_syntax_errors() {
+    let output = run(&["--ast"], "(");
+
+    assert_eq!(output.status.code(), Some(65));
+}
+
+#[test]
+fn ast_conflicts_with_commands() {
+    let output = run(&["--ast", "-c", "echo hi"], "");
+
+    assert!(!output.status.success());
+}
@@ -0,0 +1,39 @@
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_riptide-shell"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn stdlib_modules_are_loaded_by_default() {
+    let output = run(&["-c", "import 'std/fs' for read"]);
+
+    assert!(output.status.success());
+}
+
+#[test]
+fn no_stdlib_omits_stdlib_modules() {
+    let output = run(&["--no-stdlib", "-c", "import 'std/fs' for read"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("the standard library is not loaded"));
+}
+
+#[test]
+fn no_stdlib_import_math_fails() {
+    let output = run(&["--no-stdlib", "-c", "import 'std/math' for *"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("module 'std/math' not found"));
+}
+
+#[test]
+fn no_stdlib_keeps_builtins() {
+    let output = run(&["--no-stdlib", "-c", "import 'builtins' for exit; exit 3"]);
+
+    assert_eq!(output.status.code(), Some(3));
+}
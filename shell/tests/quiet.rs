@@ -0,0 +1,27 @@
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_riptide-shell"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn errors_are_logged_by_default() {
+    let output = run(&["-c", "import 'std/log' for error; error oops"]);
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("oops"));
+}
+
+#[test]
+fn quiet_silences_log_output() {
+    for flag in ["-q", "--quiet"] {
+        let output = run(&[flag, "-c", "import 'std/log' for error; error oops"]);
+
+        assert!(output.status.success());
+        assert!(!String::from_utf8_lossy(&output.stderr).contains("oops"));
+    }
+}
@@ -0,0 +1,13 @@
+use std::process::{Command, Stdio};
+
+#[test]
+fn trace_prints_commands_with_evaluated_args() {
+    let output = Command::new(env!("CARGO_BIN_EXE_riptide-shell"))
+        .args(["-x", "-c", "$name = world; true hello $name --flag"])
+        .stdin(Stdio::piped())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("+ true hello world --flag"));
+}
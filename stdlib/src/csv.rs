@@ -0,0 +1,198 @@
+//! Reading and writing comma-separated values, as described by RFC 4180.
+
+use riptide_runtime::{
+    coerce,
+    prelude::*,
+    table,
+    throw,
+    RipString,
+};
+
+pub fn load() -> Result<Value, Exception> {
+    Ok(table! {
+        "parse" => Value::foreign_fn(parse),
+        "stringify" => Value::foreign_fn(stringify),
+    }
+    .into())
+}
+
+/// Parse CSV text into a list of rows, each of which is a list of fields.
+///
+/// With the `--header` flag, the first row is taken as the names of the
+/// columns, and each following row is returned as a table keyed by column name
+/// instead.
+async fn parse(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut header = false;
+    let mut text = None;
+
+    for arg in args.iter() {
+        if arg == "--header" {
+            header = true;
+        } else if text.is_none() {
+            text = Some(coerce::to_string(arg));
+        } else {
+            throw!("too many arguments");
+        }
+    }
+
+    let text = match text {
+        Some(text) => text,
+        None => throw!("text to parse required"),
+    };
+
+    let mut rows = parse_rows(text.as_bytes())?.into_iter();
+
+    if !header {
+        return Ok(rows.map(|row| row.into_iter().collect::<Value>()).collect());
+    }
+
+    let columns = rows.next().unwrap_or_default();
+
+    for (i, column) in columns.iter().enumerate() {
+        if columns[..i].contains(column) {
+            throw!("duplicate column name '{}'", column);
+        }
+    }
+
+    let mut tables = Vec::new();
+
+    // The header is row 1, so the first row of data is row 2.
+    for (i, row) in rows.enumerate() {
+        if row.len() != columns.len() {
+            throw!("row {} has {} fields, but the header has {}", i + 2, row.len(), columns.len());
+        }
+
+        let table = table!();
+
+        for (column, field) in columns.iter().zip(row) {
+            table.set(column.clone(), field);
+        }
+
+        tables.push(Value::from(table));
+    }
+
+    Ok(Value::List(tables))
+}
+
+/// Split CSV text into rows of fields.
+fn parse_rows(text: &[u8]) -> Result<Vec<Vec<RipString>>, Exception> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut bytes = text.iter().copied().peekable();
+
+    // Text that ends with a line break does not have an empty row after it.
+    while bytes.peek().is_some() {
+        let position = (rows.len() + 1, row.len() + 1);
+        let mut field = Vec::new();
+
+        if bytes.next_if_eq(&b'"').is_some() {
+            loop {
+                match bytes.next() {
+                    Some(b'"') if bytes.next_if_eq(&b'"').is_some() => field.push(b'"'),
+                    Some(b'"') => break,
+                    Some(byte) => field.push(byte),
+                    None => throw!("row {}, column {}: unterminated quoted field", position.0, position.1),
+                }
+            }
+
+            if !matches!(bytes.peek(), None | Some(b',' | b'\r' | b'\n')) {
+                throw!("row {}, column {}: unexpected text after closing quote", position.0, position.1);
+            }
+        } else {
+            while let Some(byte) = bytes.next_if(|byte| !matches!(byte, b',' | b'\r' | b'\n')) {
+                if byte == b'"' {
+                    throw!("row {}, column {}: quote in unquoted field", position.0, position.1);
+                }
+
+                field.push(byte);
+            }
+        }
+
+        row.push(RipString::from(field));
+
+        match bytes.next() {
+            Some(b',') => {
+                // A separator at the very end means there is one more empty
+                // field.
+                if bytes.peek().is_none() {
+                    row.push(RipString::default());
+                }
+            }
+            Some(b'\r') if bytes.next_if_eq(&b'\n').is_some() => rows.push(std::mem::take(&mut row)),
+            Some(b'\n') => rows.push(std::mem::take(&mut row)),
+            Some(_) => throw!("row {}, column {}: unexpected carriage return", position.0, position.1),
+            None => {}
+        }
+    }
+
+    if !row.is_empty() {
+        rows.push(row);
+    }
+
+    Ok(rows)
+}
+
+/// Write a list of rows as CSV text.
+///
+/// Each row is either a list of fields, or a table keyed by column name. If
+/// the rows are tables, a header row is written first with the column names of
+/// the first table, and the fields of every row are written in that order.
+async fn stringify(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let rows = match args.first().and_then(Value::as_list) {
+        Some(rows) => rows,
+        None => throw!("list of rows required"),
+    };
+
+    let columns = rows.first().and_then(Value::as_table).map(|table| table.all_keys().collect::<Vec<_>>());
+    let mut output = Vec::new();
+
+    if let Some(columns) = columns.as_ref() {
+        write_row(&mut output, columns.iter().cloned());
+    }
+
+    for row in rows {
+        match (row, columns.as_ref()) {
+            (Value::List(fields), None) => write_row(&mut output, fields.iter().cloned()),
+            (Value::Table(table), Some(columns)) => write_row(
+                &mut output,
+                columns.iter().map(|column| table.get_value(column).unwrap_or_default()),
+            ),
+            _ => throw!("rows must be all lists or all tables"),
+        }
+    }
+
+    Ok(RipString::from(output).into())
+}
+
+fn write_row(output: &mut Vec<u8>, fields: impl Iterator<Item = Value>) {
+    for (i, field) in fields.enumerate() {
+        if i > 0 {
+            output.push(b',');
+        }
+
+        let field = match field {
+            Value::Nil => RipString::default(),
+            field => coerce::to_string(&field),
+        };
+        let field = field.as_bytes();
+
+        // Fields only need to be quoted if they contain special characters.
+        if field.iter().any(|byte| matches!(byte, b',' | b'"' | b'\r' | b'\n')) {
+            output.push(b'"');
+
+            for &byte in field {
+                if byte == b'"' {
+                    output.push(b'"');
+                }
+
+                output.push(byte);
+            }
+
+            output.push(b'"');
+        } else {
+            output.extend_from_slice(field);
+        }
+    }
+
+    output.extend_from_slice(b"\r\n");
+}
@@ -0,0 +1,154 @@
+//! Encoding and decoding of arbitrary bytes as text.
+
+use riptide_runtime::{
+    coerce,
+    prelude::*,
+    table,
+    throw,
+    RipString,
+};
+
+const BASE64_STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const BASE64_URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+pub fn load() -> Result<Value, Exception> {
+    Ok(table! {
+        "base64-decode" => Value::foreign_fn(base64_decode),
+        "base64-encode" => Value::foreign_fn(base64_encode),
+        "hex-decode" => Value::foreign_fn(hex_decode),
+        "hex-encode" => Value::foreign_fn(hex_encode),
+    }
+    .into())
+}
+
+/// Get the string argument of an encoding function, and whether the `--url`
+/// flag was given.
+fn string_and_url_flag(args: &[Value]) -> Result<(RipString, bool), Exception> {
+    let mut url = false;
+    let mut string = None;
+
+    for arg in args.iter() {
+        if arg == "--url" {
+            url = true;
+        } else if string.is_none() {
+            string = Some(coerce::to_string(arg));
+        } else {
+            throw!("too many arguments");
+        }
+    }
+
+    match string {
+        Some(string) => Ok((string, url)),
+        None => throw!("string required"),
+    }
+}
+
+/// Encode a string as base64.
+///
+/// With the `--url` flag, the URL and filename safe alphabet is used instead,
+/// and the padding at the end is left off.
+async fn base64_encode(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (string, url) = string_and_url_flag(&args)?;
+    let alphabet = if url { BASE64_URL_SAFE } else { BASE64_STANDARD };
+    let mut output = Vec::with_capacity(string.as_bytes().len().div_ceil(3) * 4);
+
+    for chunk in string.as_bytes().chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (i, &byte)| bits | (byte as u32) << (16 - 8 * i));
+
+        // Each byte in the chunk contributes to one more character.
+        for i in 0..=chunk.len() {
+            output.push(alphabet[(bits >> (18 - 6 * i)) as usize & 0x3f]);
+        }
+
+        if !url {
+            output.resize(output.len() + 3 - chunk.len(), b'=');
+        }
+    }
+
+    Ok(RipString::from(output).into())
+}
+
+/// Decode a base64 string.
+///
+/// Padding at the end is optional, and whitespace is ignored. With the `--url`
+/// flag, the URL and filename safe alphabet is expected instead.
+async fn base64_decode(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (string, url) = string_and_url_flag(&args)?;
+    let alphabet = if url { BASE64_URL_SAFE } else { BASE64_STANDARD };
+
+    let mut input = string.as_bytes()
+        .iter()
+        .copied()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+
+    // Strip up to two padding characters.
+    for _ in 0..2 {
+        if input.last() == Some(&b'=') {
+            input.pop();
+        }
+    }
+
+    if input.len() % 4 == 1 {
+        throw!("invalid base64: wrong length");
+    }
+
+    let mut output = Vec::with_capacity(input.len() * 3 / 4);
+
+    for chunk in input.chunks(4) {
+        let mut bits = 0u32;
+
+        for (i, byte) in chunk.iter().enumerate() {
+            match alphabet.iter().position(|c| c == byte) {
+                Some(value) => bits |= (value as u32) << (18 - 6 * i),
+                None => throw!("invalid base64: unexpected character '{}'", byte.escape_ascii()),
+            }
+        }
+
+        // Each character after the first contributes to one more byte.
+        for i in 0..chunk.len() - 1 {
+            output.push((bits >> (16 - 8 * i)) as u8);
+        }
+    }
+
+    Ok(RipString::from(output).into())
+}
+
+/// Encode a string as lowercase hexadecimal, two digits per byte.
+async fn hex_encode(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let string = match args.first() {
+        Some(value) => coerce::to_string(value),
+        None => throw!("string required"),
+    };
+
+    let mut output = String::with_capacity(string.as_bytes().len() * 2);
+
+    for byte in string.as_bytes() {
+        output.push_str(&format!("{:02x}", byte));
+    }
+
+    Ok(output.into())
+}
+
+/// Decode a hexadecimal string, in either case. Whitespace is ignored.
+async fn hex_decode(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let string = match args.first() {
+        Some(value) => coerce::to_string(value),
+        None => throw!("string required"),
+    };
+
+    let digits = string.as_bytes()
+        .iter()
+        .filter(|byte| !byte.is_ascii_whitespace())
+        .map(|&byte| match (byte as char).to_digit(16) {
+            Some(digit) => Ok(digit as u8),
+            None => throw!("invalid hex: unexpected character '{}'", byte.escape_ascii()),
+        })
+        .collect::<Result<Vec<_>, Exception>>()?;
+
+    if digits.len() % 2 != 0 {
+        throw!("invalid hex: odd number of digits");
+    }
+
+    Ok(RipString::from(digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect::<Vec<_>>()).into())
+}
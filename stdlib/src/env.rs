@@ -0,0 +1,260 @@
+use riptide_runtime::{coerce, prelude::*, table, throw, RipString};
+use std::{env, path::Path};
+use tokio::fs;
+
+pub fn load() -> Result<Value, Exception> {
+    Ok(table! {
+        "env-get" => Value::foreign_fn(env_get),
+        "env-list" => Value::foreign_fn(env_list),
+        "env-set" => Value::foreign_fn(env_set),
+        "load-dotenv" => Value::foreign_fn(load_dotenv),
+        "path-append" => Value::foreign_fn(path_append),
+        "path-prepend" => Value::foreign_fn(path_prepend),
+        "path-remove" => Value::foreign_fn(path_remove),
+    }
+    .into())
+}
+
+/// Get the value of an environment variable, or nil if it is not set.
+async fn env_get(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let name = match args.first().and_then(Value::as_string) {
+        Some(name) => name,
+        None => throw!("variable name required"),
+    };
+
+    Ok(env::var_os(name.as_os_str())
+        .map(|value| Value::from(RipString::from(value)))
+        .unwrap_or_default())
+}
+
+/// Get a table of all environment variables.
+async fn env_list(_: &mut Fiber, _: Vec<Value>) -> Result<Value, Exception> {
+    let table = table!();
+
+    for (name, value) in env::vars_os() {
+        table.set(name, RipString::from(value));
+    }
+
+    Ok(table.into())
+}
+
+/// Set an environment variable. Setting a variable to nil removes it.
+async fn env_set(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let name = match args.first().and_then(Value::as_string) {
+        Some(name) => name.clone(),
+        None => throw!("variable name required"),
+    };
+
+    let value = match args.get(1) {
+        None | Some(Value::Nil) => None,
+        Some(value) => Some(RipString::from(value.clone())),
+    };
+
+    set_var(fiber, name, value)?;
+
+    Ok(Value::Nil)
+}
+
+/// Load environment variables from a dotenv file, `.env` by default.
+///
+/// Returns a table of the variables that were set.
+async fn load_dotenv(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let path = match args.first().and_then(Value::as_string) {
+        Some(path) => Path::new(path.as_os_str()).to_owned(),
+        None => ".env".into(),
+    };
+
+    let contents = match fs::read_to_string(&path).await {
+        Ok(contents) => contents,
+        Err(e) => throw!("error reading '{}': {}", path.display(), e),
+    };
+
+    let vars = match parse_dotenv(&contents) {
+        Ok(vars) => vars,
+        Err(line) => throw!("invalid syntax in '{}' on line {}", path.display(), line),
+    };
+
+    let table = table!();
+
+    for (name, value) in vars {
+        set_var(fiber, name.as_str(), Some(RipString::from(value.as_str())))?;
+        table.set(name, value);
+    }
+
+    Ok(table.into())
+}
+
+/// Add directories to the front of `PATH`, or of another colon-separated
+/// variable given with `--var`.
+async fn path_prepend(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    edit_path(fiber, args, PathEdit::Prepend)
+}
+
+/// Add directories to the end of `PATH`, or of another colon-separated
+/// variable given with `--var`.
+async fn path_append(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    edit_path(fiber, args, PathEdit::Append)
+}
+
+/// Remove directories from `PATH`, or from another colon-separated variable
+/// given with `--var`.
+async fn path_remove(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    edit_path(fiber, args, PathEdit::Remove)
+}
+
+enum PathEdit {
+    Prepend,
+    Append,
+    Remove,
+}
+
+/// Update a colon-separated list of directories in an environment variable.
+///
+/// Directories that are added are moved to the requested end if they are
+/// already present. Afterwards only the first occurrence of each directory is
+/// kept, and empty entries are dropped.
+fn edit_path(fiber: &mut Fiber, args: Vec<Value>, edit: PathEdit) -> Result<Value, Exception> {
+    let mut name = RipString::from("PATH");
+    let mut dirs = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--var" {
+            name = match args.next().as_ref().and_then(Value::as_string) {
+                Some(name) => name.clone(),
+                None => throw!("--var requires a variable name"),
+            };
+        } else {
+            dirs.extend(split_path(coerce::to_string(&arg).as_bytes()));
+        }
+    }
+
+    if dirs.is_empty() {
+        throw!("at least one directory required");
+    }
+
+    let current = env::var_os(name.as_os_str()).map(RipString::from).unwrap_or_default();
+    let existing = split_path(current.as_bytes()).filter(|dir| !dirs.contains(dir));
+
+    let entries: Vec<RipString> = match edit {
+        PathEdit::Prepend => dirs.iter().cloned().chain(existing).collect(),
+        PathEdit::Append => existing.chain(dirs.iter().cloned()).collect(),
+        PathEdit::Remove => existing.collect(),
+    };
+
+    let mut value = Vec::new();
+
+    for (i, entry) in entries.iter().enumerate() {
+        if entries[..i].contains(entry) {
+            continue;
+        }
+
+        if !value.is_empty() {
+            value.push(b':');
+        }
+
+        value.extend_from_slice(entry.as_bytes());
+    }
+
+    set_var(fiber, name, Some(RipString::from(value)))?;
+
+    Ok(Value::Nil)
+}
+
+/// Split a colon-separated list of directories, skipping empty entries.
+fn split_path(value: &[u8]) -> impl Iterator<Item = RipString> + '_ {
+    value.split(|&byte| byte == b':')
+        .filter(|entry| !entry.is_empty())
+        .map(RipString::from)
+}
+
+/// Set a variable in both the process environment, which child processes
+/// inherit, and the `$env` global.
+fn set_var(fiber: &mut Fiber, name: impl Into<RipString>, value: Option<RipString>) -> Result<(), Exception> {
+    let name = name.into();
+
+    if name.as_bytes().is_empty() || name.as_bytes().contains(&b'=') {
+        throw!("invalid environment variable name: {}", name);
+    }
+
+    match &value {
+        Some(value) => env::set_var(name.as_os_str(), value.as_os_str()),
+        None => env::remove_var(name.as_os_str()),
+    }
+
+    if let Some(globals_env) = fiber.globals().get("env").as_table() {
+        globals_env.set(name, value.map(Value::from).unwrap_or_default());
+    }
+
+    Ok(())
+}
+
+/// Parse the contents of a dotenv file into a list of variables.
+///
+/// Each line is a `KEY=value` pair, optionally preceded by `export`. Values may
+/// be double quoted (with escapes), single quoted (taken literally), or
+/// unquoted. Blank lines and `#` comments are ignored. On a syntax error, the
+/// offending line number is returned.
+fn parse_dotenv(contents: &str) -> Result<Vec<(String, String)>, usize> {
+    let mut vars = Vec::new();
+
+    for (index, line) in contents.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.strip_prefix("export ").unwrap_or(line);
+
+        let (name, value) = match line.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() => (name.trim(), value.trim()),
+            _ => return Err(index + 1),
+        };
+
+        let value = if let Some(quoted) = value.strip_prefix('"') {
+            parse_double_quoted(quoted).ok_or(index + 1)?
+        } else if let Some(quoted) = value.strip_prefix('\'') {
+            match quoted.split_once('\'') {
+                Some((value, rest)) if is_comment(rest) => value.to_owned(),
+                _ => return Err(index + 1),
+            }
+        } else {
+            // Unquoted values end at the start of a comment.
+            match value.find(" #") {
+                Some(end) => value[..end].trim_end().to_owned(),
+                None => value.to_owned(),
+            }
+        };
+
+        vars.push((name.to_owned(), value));
+    }
+
+    Ok(vars)
+}
+
+fn parse_double_quoted(s: &str) -> Option<String> {
+    let mut value = String::new();
+    let mut chars = s.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => return is_comment(chars.as_str()).then_some(value),
+            '\\' => value.push(match chars.next()? {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                c => c,
+            }),
+            c => value.push(c),
+        }
+    }
+
+    None
+}
+
+/// Check if the remainder of a line after a value is empty or a comment.
+fn is_comment(s: &str) -> bool {
+    let s = s.trim_start();
+    s.is_empty() || s.starts_with('#')
+}
@@ -1,37 +1,487 @@
-use riptide_runtime::{prelude::*, table, throw};
-use tokio::{fs::File, io};
+use riptide_runtime::{prelude::*, table, throw, RipString};
+use std::{
+    collections::HashSet,
+    io,
+    os::unix::fs::{MetadataExt, PermissionsExt},
+    path::{Path, PathBuf},
+    time::UNIX_EPOCH,
+};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::{AsyncReadExt, AsyncWriteExt},
+};
 
 pub fn load() -> Result<Value, Exception> {
     Ok(table! {
+        "append" => Value::foreign_fn(append),
+        "copy" => Value::foreign_fn(copy),
+        "dir-size" => Value::foreign_fn(dir_size),
+        "exists" => Value::foreign_fn(exists),
+        "glob" => Value::foreign_fn(glob),
+        "list-dir" => Value::foreign_fn(list_dir),
+        "mkdir" => Value::foreign_fn(mkdir),
+        "move" => Value::foreign_fn(rename),
         "read" => Value::foreign_fn(read),
+        "read-bytes" => Value::foreign_fn(read_bytes),
+        "read-lines" => Value::foreign_fn(read_lines),
+        "remove" => Value::foreign_fn(remove),
+        "rename" => Value::foreign_fn(rename),
+        "stat" => Value::foreign_fn(stat),
+        "tee" => Value::foreign_fn(tee),
         "write" => Value::foreign_fn(write),
-        "glob" => Value::foreign_fn(glob),
     }
     .into())
 }
 
-async fn read(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
-    let path = match args.first().and_then(Value::as_string) {
-        Some(p) => p.as_os_str(),
+/// Read the entire contents of a file as a UTF-8 string.
+pub(crate) async fn read(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let path = path_arg(&args)?;
+
+    match fs::read_to_string(path).await {
+        Ok(contents) => Ok(Value::from(contents)),
+        Err(e) => Err(io_error("reading", path, e)),
+    }
+}
+
+/// Read the entire contents of a file as a raw byte string, without checking
+/// the encoding.
+async fn read_bytes(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let path = path_arg(&args)?;
+
+    match fs::read(path).await {
+        Ok(contents) => Ok(Value::from(RipString::from(contents))),
+        Err(e) => Err(io_error("reading", path, e)),
+    }
+}
+
+/// Read a file and return its lines as a list of strings.
+///
+/// Line terminators are stripped the same way as by `split-lines` in the
+/// `std/string` module.
+async fn read_lines(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let path = path_arg(&args)?;
+
+    match fs::read(path).await {
+        Ok(contents) => Ok(crate::string::lines(&contents, false)),
+        Err(e) => Err(io_error("reading", path, e)),
+    }
+}
+
+/// Write to a file, replacing its contents if it already exists.
+///
+/// If no content is given, standard input is written to the file instead.
+pub(crate) async fn write(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let path = path_arg(&args)?;
+
+    match File::create(path).await {
+        Ok(file) => write_to(fiber, file, &args[1..]).await.map_err(|e| io_error("writing", path, e)),
+        Err(e) => Err(io_error("creating", path, e)),
+    }
+}
+
+/// Append to the end of a file, creating it if it does not exist.
+///
+/// If no content is given, standard input is appended to the file instead.
+async fn append(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let path = path_arg(&args)?;
+
+    match OpenOptions::new().append(true).create(true).open(path).await {
+        Ok(file) => write_to(fiber, file, &args[1..]).await.map_err(|e| io_error("writing", path, e)),
+        Err(e) => Err(io_error("opening", path, e)),
+    }
+}
+
+/// Copy standard input to both a file and standard output, such as to save the
+/// data passing through a pipeline. Returns the number of bytes copied.
+///
+/// The file is replaced unless the `-a` or `--append` flag is given. If
+/// standard output is closed, such as when the next command in a pipeline exits
+/// early, the rest of the input is still written to the file.
+async fn tee(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut append = false;
+    let mut path = None;
+
+    for arg in args.iter() {
+        if arg == "-a" || arg == "--append" {
+            append = true;
+        } else if path.is_none() {
+            match arg.as_string() {
+                Some(s) => path = Some(Path::new(s.as_os_str())),
+                None => throw!("file path required"),
+            }
+        } else {
+            throw!("too many arguments");
+        }
+    }
+
+    let path = match path {
+        Some(path) => path,
         None => throw!("file path required"),
     };
 
-    let mut file = File::open(path).await?;
-    let count = io::copy(&mut file, fiber.stdout()).await?;
+    let file = if append {
+        OpenOptions::new().append(true).create(true).open(path).await
+    } else {
+        File::create(path).await
+    };
+
+    match file {
+        Ok(file) => tee_to(fiber, file).await.map_err(|e| io_error("writing", path, e)),
+        Err(e) => Err(io_error("opening", path, e)),
+    }
+}
+
+async fn tee_to(fiber: &mut Fiber, mut file: File) -> io::Result<Value> {
+    let mut stdin = fiber.stdin().try_clone()?;
+    let stdout = fiber.stdout();
+    let mut stdout_open = true;
+    let mut buf = vec![0; 8192];
+    let mut count = 0;
+
+    loop {
+        let len = stdin.read(&mut buf).await?;
+
+        if len == 0 {
+            break;
+        }
+
+        let chunk = &buf[..len];
+
+        if stdout_open {
+            let (file_result, stdout_result) = tokio::join!(file.write_all(chunk), stdout.write_all(chunk));
+            file_result?;
+            stdout_open = still_open(stdout_result)?;
+        } else {
+            file.write_all(chunk).await?;
+        }
+
+        count += len as u64;
+    }
+
+    file.flush().await?;
+
+    if stdout_open {
+        still_open(stdout.flush().await)?;
+    }
 
     Ok(Value::from(count))
 }
 
-async fn write(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
-    let path = match args.first().and_then(Value::as_string) {
-        Some(p) => p.as_os_str(),
+/// Check the result of writing to an output that the reader may have closed.
+fn still_open(result: io::Result<()>) -> io::Result<bool> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Check if a file or directory exists at the given path.
+async fn exists(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let path = path_arg(&args)?;
+
+    Ok(Value::from(fs::try_exists(path).await.unwrap_or(false)))
+}
+
+/// Get metadata about a file as a table.
+///
+/// Symbolic links are followed unless the `-l` or `--lstat` flag is given, in
+/// which case the metadata of the link itself is returned.
+async fn stat(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut follow = true;
+    let mut path = None;
+
+    for arg in args.iter() {
+        if arg == "-l" || arg == "--lstat" {
+            follow = false;
+        } else if let Some(s) = arg.as_string() {
+            path = Some(Path::new(s.as_os_str()));
+        }
+    }
+
+    let path = match path {
+        Some(path) => path,
         None => throw!("file path required"),
     };
 
-    let mut file = File::create(path).await?;
-    let count = io::copy(fiber.stdin(), &mut file).await?;
+    let link_metadata = fs::symlink_metadata(path).await.map_err(|e| io_error("reading metadata of", path, e))?;
 
-    Ok(Value::from(count))
+    let metadata = if follow && link_metadata.is_symlink() {
+        fs::metadata(path).await.map_err(|e| io_error("reading metadata of", path, e))?
+    } else {
+        link_metadata.clone()
+    };
+
+    let modified = metadata.modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| Value::from(duration.as_secs()))
+        .unwrap_or_default();
+
+    Ok(table! {
+        "size" => metadata.len(),
+        "is-dir" => metadata.is_dir(),
+        "is-file" => metadata.is_file(),
+        "is-symlink" => link_metadata.is_symlink(),
+        "modified" => modified,
+        "mode" => metadata.permissions().mode() & 0o7777,
+    }
+    .into())
+}
+
+/// Remove a file or an empty directory.
+async fn remove(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let path = path_arg(&args)?;
+
+    let result = match fs::symlink_metadata(path).await {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir(path).await,
+        Ok(_) => fs::remove_file(path).await,
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => Ok(Value::Nil),
+        Err(e) => Err(io_error("removing", path, e)),
+    }
+}
+
+/// Get a list of the names of the entries in a directory.
+async fn list_dir(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let path = path_arg(&args)?;
+    let mut names = Vec::new();
+
+    let mut entries = match fs::read_dir(path).await {
+        Ok(entries) => entries,
+        Err(e) => return Err(io_error("listing", path, e)),
+    };
+
+    loop {
+        match entries.next_entry().await {
+            Ok(Some(entry)) => names.push(RipString::from(entry.file_name())),
+            Ok(None) => break,
+            Err(e) => return Err(io_error("listing", path, e)),
+        }
+    }
+
+    // Directory entries are returned in no particular order, so sort them to
+    // keep the result deterministic.
+    names.sort();
+
+    Ok(names.into_iter().collect())
+}
+
+/// Get the total size in bytes of all the files in a directory and its
+/// subdirectories.
+///
+/// Symbolic links are skipped rather than followed, so links that form a cycle
+/// cannot cause the same files to be counted forever, and files with more than
+/// one hard link are only counted once. Entries that cannot be read, such as
+/// because of their permissions, are skipped as well. The `--report-errors`
+/// flag prints a message to standard error for each entry that is skipped this
+/// way.
+///
+/// If the `--by-dir` flag is given, a table of the total size of every
+/// directory, including the one given, is returned instead.
+async fn dir_size(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut by_dir = false;
+    let mut report_errors = false;
+    let mut path = None;
+
+    for arg in args.iter() {
+        if arg == "--by-dir" {
+            by_dir = true;
+        } else if arg == "--report-errors" {
+            report_errors = true;
+        } else if path.is_none() {
+            match arg.as_string() {
+                Some(s) => path = Some(Path::new(s.as_os_str())),
+                None => throw!("directory path required"),
+            }
+        } else {
+            throw!("too many arguments");
+        }
+    }
+
+    let path = match path {
+        Some(path) => path,
+        None => throw!("directory path required"),
+    };
+
+    let metadata = fs::symlink_metadata(path).await.map_err(|e| io_error("reading metadata of", path, e))?;
+
+    if !metadata.is_dir() {
+        throw!("'{}' is not a directory", path.display());
+    }
+
+    struct Dir {
+        path: PathBuf,
+        parent: Option<usize>,
+        size: u64,
+    }
+
+    let mut dirs = vec![Dir {
+        path: path.to_owned(),
+        parent: None,
+        size: 0,
+    }];
+    let mut pending = vec![0];
+    let mut seen = HashSet::new();
+
+    while let Some(index) = pending.pop() {
+        let dir_path = dirs[index].path.clone();
+
+        let mut entries = match fs::read_dir(&dir_path).await {
+            Ok(entries) => entries,
+            Err(e) if index == 0 => return Err(io_error("listing", &dir_path, e)),
+            Err(e) => {
+                skipped(fiber, report_errors, "listing", &dir_path, e).await?;
+                continue;
+            }
+        };
+
+        loop {
+            let entry = match entries.next_entry().await {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(e) => {
+                    skipped(fiber, report_errors, "listing", &dir_path, e).await?;
+                    break;
+                }
+            };
+
+            // Directory entries do not follow symbolic links.
+            let metadata = match entry.metadata().await {
+                Ok(metadata) => metadata,
+                Err(e) => {
+                    skipped(fiber, report_errors, "reading metadata of", &entry.path(), e).await?;
+                    continue;
+                }
+            };
+
+            if metadata.is_dir() {
+                pending.push(dirs.len());
+                dirs.push(Dir {
+                    path: entry.path(),
+                    parent: Some(index),
+                    size: 0,
+                });
+            } else if metadata.is_file() && (metadata.nlink() == 1 || seen.insert((metadata.dev(), metadata.ino()))) {
+                dirs[index].size += metadata.len();
+            }
+        }
+    }
+
+    // Subdirectories are always found after their parents, so adding up the
+    // sizes in reverse order totals each directory before its parent.
+    for index in (1..dirs.len()).rev() {
+        if let Some(parent) = dirs[index].parent {
+            dirs[parent].size += dirs[index].size;
+        }
+    }
+
+    if by_dir {
+        let totals = table!();
+
+        for dir in dirs {
+            totals.set(dir.path.into_os_string(), dir.size);
+        }
+
+        Ok(totals.into())
+    } else {
+        Ok(Value::from(dirs[0].size))
+    }
+}
+
+/// Handle an error reading an entry that `dir-size` is skipping.
+async fn skipped(fiber: &mut Fiber, report: bool, action: &str, path: &Path, error: io::Error) -> Result<(), Exception> {
+    if report {
+        let message = format!("dir-size: skipping '{}': error {}: {}\n", path.display(), action, error);
+        fiber.stderr().write_all(message.as_bytes()).await?;
+    } else {
+        log::debug!("skipping '{}': error {}: {}", path.display(), action, error);
+    }
+
+    Ok(())
+}
+
+/// Create a directory.
+///
+/// If the `-p` or `--parents` flag is given, any missing parent directories
+/// are created as well, and it is not an error if the directory already
+/// exists.
+async fn mkdir(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut parents = false;
+    let mut paths = Vec::new();
+
+    for arg in args.iter() {
+        if arg == "-p" || arg == "--parents" {
+            parents = true;
+        } else if let Some(path) = arg.as_string() {
+            paths.push(Path::new(path.as_os_str()));
+        }
+    }
+
+    if paths.is_empty() {
+        throw!("directory path required");
+    }
+
+    for path in paths {
+        let result = if parents {
+            fs::create_dir_all(path).await
+        } else {
+            fs::create_dir(path).await
+        };
+
+        if let Err(e) = result {
+            return Err(io_error("creating directory", path, e));
+        }
+    }
+
+    Ok(Value::Nil)
+}
+
+/// Rename or move a file or directory.
+async fn rename(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (from, to) = path_args(&args)?;
+
+    match fs::rename(from, to).await {
+        Ok(()) => Ok(Value::Nil),
+        Err(e) => Err(transfer_error("moving", from, to, e)),
+    }
+}
+
+/// Copy a file, or a directory and all of its contents.
+async fn copy(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (from, to) = path_args(&args)?;
+
+    let result = match fs::metadata(from).await {
+        // Tokio has no recursive copy, so use the synchronous API.
+        Ok(metadata) if metadata.is_dir() => copy_dir(from, to),
+        Ok(_) => fs::copy(from, to).await.map(|_| ()),
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(()) => Ok(Value::Nil),
+        Err(e) => Err(transfer_error("copying", from, to, e)),
+    }
+}
+
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    std::fs::create_dir_all(to)?;
+
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let to = to.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() {
+            copy_dir(&entry.path(), &to)?;
+        } else {
+            std::fs::copy(entry.path(), to)?;
+        }
+    }
+
+    Ok(())
 }
 
 async fn glob(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
@@ -58,3 +508,47 @@ async fn glob(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
         })
         .map_err(|e| Exception::from(e.to_string()))
 }
+
+/// Write the given values to a file, or standard input if no values are
+/// given. Returns the number of bytes written.
+async fn write_to(fiber: &mut Fiber, mut file: File, values: &[Value]) -> io::Result<Value> {
+    let count = if values.is_empty() {
+        tokio::io::copy(fiber.stdin(), &mut file).await?
+    } else {
+        let mut count = 0;
+
+        for value in values {
+            let bytes = RipString::from(value.clone());
+            file.write_all(bytes.as_bytes()).await?;
+            count += bytes.as_bytes().len() as u64;
+        }
+
+        count
+    };
+
+    file.flush().await?;
+
+    Ok(Value::from(count))
+}
+
+fn path_arg(args: &[Value]) -> Result<&Path, Exception> {
+    match args.first().and_then(Value::as_string) {
+        Some(p) => Ok(Path::new(p.as_os_str())),
+        None => throw!("file path required"),
+    }
+}
+
+fn path_args(args: &[Value]) -> Result<(&Path, &Path), Exception> {
+    match (args.first().and_then(Value::as_string), args.get(1).and_then(Value::as_string)) {
+        (Some(from), Some(to)) => Ok((Path::new(from.as_os_str()), Path::new(to.as_os_str()))),
+        _ => throw!("source and destination paths required"),
+    }
+}
+
+fn io_error(action: &str, path: &Path, error: io::Error) -> Exception {
+    Exception::from(format!("error {} '{}': {}", action, path.display(), error))
+}
+
+fn transfer_error(action: &str, from: &Path, to: &Path, error: io::Error) -> Exception {
+    Exception::from(format!("error {} '{}' to '{}': {}", action, from.display(), to.display(), error))
+}
@@ -0,0 +1,90 @@
+//! Cryptographic hash functions, for computing checksums.
+
+use md5::Md5;
+use riptide_runtime::{
+    coerce,
+    prelude::*,
+    table,
+    throw,
+};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use std::{io, path::Path};
+use tokio::{fs::File, io::AsyncReadExt};
+
+pub fn load() -> Result<Value, Exception> {
+    Ok(table! {
+        "hash-file" => Value::foreign_fn(hash_file),
+        "md5" => Value::foreign_fn(hash_string::<Md5>),
+        "sha1" => Value::foreign_fn(hash_string::<Sha1>),
+        "sha256" => Value::foreign_fn(hash_string::<Sha256>),
+    }
+    .into())
+}
+
+/// Hash the bytes of a string, returning the digest in hexadecimal.
+async fn hash_string<D: Digest>(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let string = match args.first() {
+        Some(value) => coerce::to_string(value),
+        None => throw!("string required"),
+    };
+
+    Ok(to_hex(&D::digest(string.as_bytes())).into())
+}
+
+/// Hash the contents of a file, returning the digest in hexadecimal.
+///
+/// SHA-256 is used unless the `--sha1` or `--md5` flag is given. The file is
+/// read a piece at a time, so large files can be hashed without reading them
+/// into memory all at once.
+async fn hash_file(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut algorithm = None;
+    let mut path = None;
+
+    for arg in args.iter() {
+        if arg == "--sha256" || arg == "--sha1" || arg == "--md5" {
+            if algorithm.is_some() {
+                throw!("only one hash algorithm may be given");
+            }
+
+            algorithm = Some(arg);
+        } else if path.is_none() {
+            path = arg.as_string().map(|path| Path::new(path.as_os_str()));
+        } else {
+            throw!("too many arguments");
+        }
+    }
+
+    let path = match path {
+        Some(path) => path,
+        None => throw!("file path required"),
+    };
+
+    let result = match algorithm {
+        Some(flag) if flag == "--md5" => digest_file::<Md5>(path).await,
+        Some(flag) if flag == "--sha1" => digest_file::<Sha1>(path).await,
+        _ => digest_file::<Sha256>(path).await,
+    };
+
+    match result {
+        Ok(digest) => Ok(to_hex(&digest).into()),
+        Err(e) => throw!("error reading '{}': {}", path.display(), e),
+    }
+}
+
+async fn digest_file<D: Digest>(path: &Path) -> io::Result<Vec<u8>> {
+    let mut file = File::open(path).await?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0; 64 * 1024];
+
+    loop {
+        match file.read(&mut buffer).await? {
+            0 => return Ok(hasher.finalize().to_vec()),
+            len => hasher.update(&buffer[..len]),
+        }
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,133 @@
+//! A minimal HTTP client, for scripts that talk to web APIs.
+
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    Client,
+    Method,
+};
+use riptide_runtime::{
+    coerce,
+    prelude::*,
+    table,
+    throw,
+    RipString,
+};
+use std::time::Duration;
+
+/// How long to wait for a request to complete if no timeout is given.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+pub fn load() -> Result<Value, Exception> {
+    Ok(table! {
+        "get" => Value::foreign_fn(get),
+        "post" => Value::foreign_fn(post),
+    }
+    .into())
+}
+
+/// Send a GET request to a URL.
+async fn get(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    request(Method::GET, args).await
+}
+
+/// Send a POST request to a URL, with an optional body after the URL.
+async fn post(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    request(Method::POST, args).await
+}
+
+/// Send a request and return a table of the response's `status`, `headers`
+/// and `body`.
+///
+/// The `--headers` option takes a table of headers to send, and the
+/// `--timeout` option sets the number of seconds to wait for the response.
+/// Responses with a status outside of the 2xx range are returned like any
+/// other, unless the `--strict` flag is given, in which case an exception is
+/// thrown instead.
+async fn request(method: Method, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut url = None;
+    let mut body = None;
+    let mut headers = HeaderMap::new();
+    let mut timeout = DEFAULT_TIMEOUT;
+    let mut strict = false;
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--headers" {
+            let table = match args.next() {
+                Some(Value::Table(table)) => table,
+                _ => throw!("--headers requires a table"),
+            };
+
+            for key in table.all_keys() {
+                let name = coerce::to_string(&key);
+                let value = coerce::to_string(&table.get_value(&key)?);
+
+                match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_bytes(value.as_bytes())) {
+                    (Ok(name), Ok(value)) => headers.append(name, value),
+                    _ => throw!("invalid header '{}: {}'", name, value),
+                };
+            }
+        } else if arg == "--timeout" {
+            timeout = match args.next().as_ref().and_then(coerce::to_number) {
+                Some(seconds) if seconds > 0.0 => Duration::from_secs_f64(seconds),
+                _ => throw!("timeout must be a positive number of seconds"),
+            };
+        } else if arg == "--strict" {
+            strict = true;
+        } else if url.is_none() {
+            url = Some(coerce::to_string(&arg).to_string());
+        } else if method == Method::POST && body.is_none() {
+            body = Some(Vec::<u8>::from(coerce::to_string(&arg)));
+        } else {
+            throw!("too many arguments");
+        }
+    }
+
+    let url = match url {
+        Some(url) => url,
+        None => throw!("URL required"),
+    };
+
+    let client = match Client::builder().timeout(timeout).build() {
+        Ok(client) => client,
+        Err(e) => throw!("error creating HTTP client: {}", e),
+    };
+
+    let mut request = client.request(method, &url).headers(headers);
+
+    if let Some(body) = body {
+        request = request.body(body);
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => throw!("error requesting '{}': {}", url, e),
+    };
+
+    let status = response.status();
+
+    if strict && !status.is_success() {
+        throw!("request to '{}' failed with status {}", url, status);
+    }
+
+    // Headers that appear more than once are combined into one, which HTTP
+    // treats as equivalent.
+    let headers = table!();
+
+    for name in response.headers().keys() {
+        let values = response.headers().get_all(name).iter().map(HeaderValue::as_bytes).collect::<Vec<_>>();
+        headers.set(name.as_str(), RipString::from(values.join(&b", "[..])));
+    }
+
+    let body = match response.bytes().await {
+        Ok(body) => body,
+        Err(e) => throw!("error reading response from '{}': {}", url, e),
+    };
+
+    Ok(table! {
+        "status" => u32::from(status.as_u16()),
+        "headers" => headers,
+        "body" => RipString::from(body.to_vec()),
+    }
+    .into())
+}
@@ -1,4 +1,5 @@
 use riptide_runtime::{
+    coerce,
     prelude::*,
     table,
 };
@@ -31,10 +32,10 @@ async fn panic(_: &mut Fiber, _: Vec<Value>) -> Result<Value, Exception> {
 }
 
 /// Print the given values to standard output.
-async fn print(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+pub(crate) async fn print(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     let stdout = fiber.stdout();
     for arg in args.iter() {
-        stdout.write_all(arg.to_string().as_bytes()).await?;
+        stdout.write_all(coerce::to_string(arg).as_bytes()).await?;
     }
     stdout.flush().await?;
 
@@ -42,10 +43,10 @@ async fn print(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception>
 }
 
 /// Print the given values to standard output, followed by a newline.
-async fn println(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+pub(crate) async fn println(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     let stdout = fiber.stdout();
     for arg in args.iter() {
-        stdout.write_all(arg.to_string().as_bytes()).await?;
+        stdout.write_all(coerce::to_string(arg).as_bytes()).await?;
         stdout.write_all(b"\n").await?;
     }
 
@@ -56,7 +57,7 @@ async fn println(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception
 async fn eprint(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     let stderr = fiber.stderr();
     for arg in args.iter() {
-        stderr.write_all(arg.to_string().as_bytes()).await?;
+        stderr.write_all(coerce::to_string(arg).as_bytes()).await?;
     }
     stderr.flush().await?;
 
@@ -67,14 +68,14 @@ async fn eprint(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception>
 async fn eprintln(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     let stderr = fiber.stderr();
     for arg in args.iter() {
-        stderr.write_all(arg.to_string().as_bytes()).await?;
+        stderr.write_all(coerce::to_string(arg).as_bytes()).await?;
         stderr.write_all(b"\n").await?;
     }
 
     Ok(Value::Nil)
 }
 
-async fn dump(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+pub(crate) async fn dump(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     fn dump(value: &Value, indent: usize, depth: usize) {
         match value {
             Value::List(items) => {
@@ -90,10 +91,10 @@ async fn dump(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
             }
             Value::Table(table) => {
                 println!("{:indent$}[", "", indent = indent);
-                for key in table.keys() {
+                for key in table.all_keys() {
                     println!("{:indent$}{:?} =>", "", key, indent = indent + 4);
                     if depth > 0 {
-                        dump(&table.get(key), indent + 4, depth - 1);
+                        dump(&table.get_value(&key).unwrap_or_default(), indent + 4, depth - 1);
                     } else {
                         println!("{:indent$}...", "", indent = indent + 4);
                     }
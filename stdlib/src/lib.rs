@@ -1,18 +1,56 @@
 use riptide_runtime::prelude::*;
+use std::time::Instant;
 
+mod csv;
+mod encoding;
+mod env;
 mod fs;
+mod hash;
+mod http;
 mod lang;
+mod log;
 mod process;
 mod string;
+mod term;
+mod text;
 
 pub async fn init(fiber: &mut Fiber) -> Result<(), Exception> {
+    let start_time = Instant::now();
+
+    // Modules are only loaded when they are first imported.
+    fiber.register_native_module("std/csv", csv::load);
+    fiber.register_native_module("std/encoding", encoding::load);
+    fiber.register_native_module("std/env", env::load);
     fiber.register_native_module("std/fs", fs::load);
+    fiber.register_native_module("std/hash", hash::load);
+    fiber.register_native_module("std/http", http::load);
     fiber.register_native_module("std/lang", lang::load);
+    fiber.register_native_module("std/log", log::load);
     fiber.register_native_module("std/process", process::load);
     fiber.register_native_module("std/string", string::load);
+    fiber.register_native_module("std/term", term::load);
+    fiber.register_native_module("std/text", text::load);
+
+    // Functions that are available everywhere are defined directly, rather
+    // than imported, so that the modules they come from are not loaded up
+    // front.
+    let globals = fiber.globals();
+    globals.set("print", Value::foreign_fn(lang::print));
+    globals.set("println", Value::foreign_fn(lang::println));
+    globals.set("echo", Value::foreign_fn(lang::println));
+    globals.set("dump", Value::foreign_fn(lang::dump));
+    globals.set("command", Value::foreign_fn(process::command));
+    globals.set("exec", Value::foreign_fn(process::exec));
+    globals.set("read", Value::foreign_fn(fs::read));
+    globals.set("write", Value::foreign_fn(fs::write));
 
     // Execute initialization
     fiber.execute(None, include_str!("init.rt")).await?;
 
+    ::log::debug!("standard library took {:?} to initialize", start_time.elapsed());
+
+    // Resetting the fiber returns it to this point.
+    fiber.snapshot();
+
     Ok(())
 }
@@ -0,0 +1,50 @@
+//! Functions for writing diagnostic messages to the runtime's log.
+//!
+//! Messages are routed through the same logger as the runtime itself, so they
+//! go to standard error and respect the verbosity the shell was started with.
+
+use log::Level;
+use riptide_runtime::{coerce, prelude::*, table};
+
+/// The log target used for messages logged by scripts.
+const TARGET: &str = "riptide::script";
+
+pub fn load() -> Result<Value, Exception> {
+    Ok(table! {
+        "debug" => Value::foreign_fn(debug),
+        "info" => Value::foreign_fn(info),
+        "warn" => Value::foreign_fn(warn),
+        "error" => Value::foreign_fn(error),
+    }
+    .into())
+}
+
+async fn debug(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    log_args(Level::Debug, &args)
+}
+
+async fn info(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    log_args(Level::Info, &args)
+}
+
+async fn warn(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    log_args(Level::Warn, &args)
+}
+
+async fn error(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    log_args(Level::Error, &args)
+}
+
+/// Log the given values, separated by spaces.
+fn log_args(level: Level, args: &[Value]) -> Result<Value, Exception> {
+    if log::log_enabled!(target: TARGET, level) {
+        let message = args.iter()
+            .map(|arg| coerce::to_string(arg).to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        log::log!(target: TARGET, level, "{}", message);
+    }
+
+    Ok(Value::Nil)
+}
@@ -1,5 +1,6 @@
-use riptide_runtime::io::process;
+use riptide_runtime::{io::process, jobs};
 use riptide_runtime::{
+    coerce,
     prelude::*,
     table,
     throw,
@@ -19,7 +20,8 @@ pub fn load() -> Result<Value, Exception> {
 
 /// Spawns a new child process and executes a given block in it.
 ///
-/// Returns the child process PID.
+/// Returns the child process PID. The process is tracked as a background job,
+/// and can be waited on with `wait`.
 async fn spawn(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
     let block = match args.first() {
         Some(arg) if arg.type_name() == "block" => arg.clone(),
@@ -27,6 +29,10 @@ async fn spawn(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception>
     };
     let child_args = args.iter().skip(1).cloned().collect::<Vec<_>>();
 
+    // Write out anything buffered so far, otherwise the child process would
+    // inherit a copy of it and write it out a second time.
+    fiber.io().flush().await?;
+
     // Create a child fiber to correspond to the child process, otherwise the
     // child will try and share file descriptors with the parent.
     // TODO: This is borken somehow, as the child process is still messing with
@@ -35,17 +41,34 @@ async fn spawn(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception>
 
     let pid = process::spawn(async {
         child_fiber.invoke(&block, &child_args).await.unwrap();
+
+        // The child process exits without dropping the fiber, so its buffered
+        // output must be written out here.
+        let _ = child_fiber.io().flush().await;
     }).await?;
 
+    jobs::track_process(fiber, pid);
+
     Ok(Value::Number(pid as f64))
 }
 
 /// Executes a shell command in the foreground, waiting for it to complete.
 ///
-/// Returns the process exit code.
-async fn command(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+/// If the `--status` flag is given, returns the exit status of the command.
+pub(crate) async fn command(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (return_status, args) = match args.split_first() {
+        Some((flag, rest)) if flag == "--status" => (true, rest),
+        _ => (false, args.as_slice()),
+    };
+
     if let Some(Value::String(command)) = args.first() {
-        process::command(fiber, command, &args[1..]).await
+        process::command(fiber, command, &args[1..]).await?;
+
+        if return_status {
+            Ok(fiber.globals().get(process::LAST_STATUS))
+        } else {
+            Ok(Value::Nil)
+        }
     } else {
         throw!("command to execute is required")
     }
@@ -53,16 +76,18 @@ async fn command(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception
 
 /// Executes a shell command, replacing the current process with the new process.
 ///
-/// Does not return.
-async fn exec(_: &mut Fiber, _: Vec<Value>) -> Result<Value, Exception> {
-    unimplemented!();
+/// Does not return, unless the command could not be executed.
+pub(crate) async fn exec(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    if let Some(Value::String(command)) = args.first() {
+        match process::exec(fiber, command, &args[1..]).await? {}
+    } else {
+        throw!("command to execute is required")
+    }
 }
 
 /// Puts the current process to sleep for a given number of seconds.
 async fn sleep(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
-    if let Some(Value::Number(seconds)) = args.first() {
-        let seconds = *seconds;
-
+    if let Some(seconds) = args.first().and_then(coerce::to_number) {
         let duration = if seconds.is_normal() && seconds > 0f64 {
             Duration::new(
                 seconds.trunc() as u64,
@@ -1,10 +1,33 @@
 use riptide_runtime::{
+    coerce,
     prelude::*,
     table,
+    throw,
+    RipString,
 };
+use unicode_width::UnicodeWidthStr;
+
+/// Units of binary sizes, each 1024 times the last.
+const BINARY_UNITS: [&str; 9] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB", "ZiB", "YiB"];
+
+/// Units of decimal sizes, each 1000 times the last.
+const DECIMAL_UNITS: [&str; 9] = ["B", "KB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"];
 
 pub fn load() -> Result<Value, Exception> {
     Ok(table! {
+        "byte-at" => Value::foreign_fn(byte_at),
+        "bytes" => Value::foreign_fn(bytes),
+        "char-at" => Value::foreign_fn(char_at),
+        "chars" => Value::foreign_fn(chars),
+        "format" => Value::foreign_fn(format),
+        "format-bytes" => Value::foreign_fn(format_bytes),
+        "from-bytes" => Value::foreign_fn(from_bytes),
+        "pad-left" => Value::foreign_fn(pad_left),
+        "pad-right" => Value::foreign_fn(pad_right),
+        "parse-bytes" => Value::foreign_fn(parse_bytes),
+        "split-lines" => Value::foreign_fn(split_lines),
+        "trim-prefix" => Value::foreign_fn(trim_prefix),
+        "trim-suffix" => Value::foreign_fn(trim_suffix),
         // "len" => Value::foreign_fn(|_, _| {
         //     async {
         //         Ok(Value::Nil)
@@ -22,3 +45,464 @@ pub fn load() -> Result<Value, Exception> {
     }
     .into())
 }
+
+/// Format values using a printf-style format string.
+///
+/// Each placeholder has the form `%[flags][width][.precision]type`, and
+/// consumes the next argument. Supported types are `s` (string), `d` (integer),
+/// `f` (decimal number, six digits after the point unless a precision is
+/// given), and `x` (hexadecimal integer). The flag `-` aligns the value to the
+/// left of the field and `0` pads numbers with zeros. `%%` is a literal `%`.
+async fn format(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (template, mut values) = match args.split_first() {
+        Some((template, values)) => (coerce::to_string(template), values.iter()),
+        None => throw!("format string required"),
+    };
+
+    let template = match template.as_utf8() {
+        Some(template) => template.to_owned(),
+        None => throw!("format string must be valid UTF-8"),
+    };
+
+    let mut output = String::new();
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+
+        if chars.peek() == Some(&'%') {
+            chars.next();
+            output.push('%');
+            continue;
+        }
+
+        let mut left_align = false;
+        let mut zero_pad = false;
+
+        while let Some(&flag @ ('-' | '0')) = chars.peek() {
+            left_align |= flag == '-';
+            zero_pad |= flag == '0';
+            chars.next();
+        }
+
+        let width = parse_digits(&mut chars).unwrap_or(0);
+        let precision = match chars.peek() {
+            Some('.') => {
+                chars.next();
+                Some(parse_digits(&mut chars).unwrap_or(0))
+            }
+            _ => None,
+        };
+
+        let conversion = match chars.next() {
+            Some(c) => c,
+            None => throw!("incomplete placeholder at end of format string"),
+        };
+
+        let value = match values.next() {
+            Some(value) => value,
+            None => throw!("not enough arguments for format string"),
+        };
+
+        let number = || match coerce::to_number(value) {
+            Some(number) => Ok(number),
+            None => Err(Exception::from(format!("placeholder %{} expects a number, got {}", conversion, value.type_name()))),
+        };
+
+        let (text, numeric) = match conversion {
+            's' => {
+                let text = coerce::to_string(value).to_string();
+                (match precision {
+                    Some(precision) => text.chars().take(precision).collect(),
+                    None => text,
+                }, false)
+            }
+            'd' => (format!("{}", number()?.trunc()), true),
+            'f' => (format!("{:.*}", precision.unwrap_or(6), number()?), true),
+            'x' => {
+                let number = number()? as i64;
+                (if number < 0 {
+                    format!("-{:x}", number.unsigned_abs())
+                } else {
+                    format!("{:x}", number)
+                }, true)
+            }
+            c => throw!("unknown placeholder type: %{}", c),
+        };
+
+        pad(&mut output, &text, width, left_align, zero_pad && numeric && !left_align);
+    }
+
+    if values.len() > 0 {
+        throw!("too many arguments for format string");
+    }
+
+    Ok(output.into())
+}
+
+/// Split a string into a list of lines.
+///
+/// Lines may end in either `\n` or `\r\n`, and the line terminators are not
+/// included in the returned lines. An empty line after a final line terminator
+/// is dropped unless the `--keep-trailing` flag is given.
+async fn split_lines(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut keep_trailing = false;
+    let mut string = None;
+
+    for arg in args.iter() {
+        if arg == "--keep-trailing" {
+            keep_trailing = true;
+        } else if string.is_none() {
+            string = Some(coerce::to_string(arg));
+        } else {
+            throw!("too many arguments");
+        }
+    }
+
+    match string {
+        Some(string) => Ok(lines(string.as_bytes(), keep_trailing)),
+        None => throw!("string required"),
+    }
+}
+
+/// Split bytes into a list of lines, stripping `\n` and `\r\n` terminators.
+pub(crate) fn lines(bytes: &[u8], keep_trailing: bool) -> Value {
+    let mut lines = bytes.split(|&b| b == b'\n').collect::<Vec<_>>();
+
+    if !keep_trailing && lines.last() == Some(&&b""[..]) {
+        lines.pop();
+    }
+
+    lines
+        .into_iter()
+        .map(|line| RipString::from(line.strip_suffix(b"\r").unwrap_or(line)))
+        .collect()
+}
+
+/// Remove a prefix from the start of a string, if present. The string is
+/// returned unchanged if it does not start with the prefix.
+async fn trim_prefix(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (string, prefix) = string_and_affix(&args)?;
+
+    Ok(match string.as_bytes().strip_prefix(prefix.as_bytes()) {
+        Some(trimmed) => RipString::from(trimmed).into(),
+        None => string.into(),
+    })
+}
+
+/// Remove a suffix from the end of a string, if present. The string is
+/// returned unchanged if it does not end with the suffix.
+async fn trim_suffix(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (string, suffix) = string_and_affix(&args)?;
+
+    Ok(match string.as_bytes().strip_suffix(suffix.as_bytes()) {
+        Some(trimmed) => RipString::from(trimmed).into(),
+        None => string.into(),
+    })
+}
+
+fn string_and_affix(args: &[Value]) -> Result<(RipString, RipString), Exception> {
+    match args {
+        [string, affix] => Ok((coerce::to_string(string), coerce::to_string(affix))),
+        _ => throw!("a string and the substring to remove are required"),
+    }
+}
+
+/// Pad the start of a string with a fill character up to a width.
+async fn pad_left(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (string, padding) = padding(&args)?;
+
+    Ok(format!("{}{}", padding, string).into())
+}
+
+/// Pad the end of a string with a fill character up to a width.
+async fn pad_right(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let (string, padding) = padding(&args)?;
+
+    Ok(format!("{}{}", string, padding).into())
+}
+
+/// Get the string to pad and the padding needed to bring it up to the width
+/// given in the arguments.
+///
+/// Width is measured in terminal columns, so wide characters count as two and
+/// combining characters count as none. The fill character defaults to a space.
+fn padding(args: &[Value]) -> Result<(String, String), Exception> {
+    let (string, width, fill) = match args {
+        [string, width] => (string, width, None),
+        [string, width, fill] => (string, width, Some(coerce::to_string(fill))),
+        _ => throw!("a string and a width are required"),
+    };
+
+    let string = match coerce::to_string(string).as_utf8() {
+        Some(string) => string.to_owned(),
+        None => throw!("string to pad must be valid UTF-8"),
+    };
+
+    let width = match coerce::to_number(width) {
+        Some(width) if width >= 0.0 => width as usize,
+        _ => throw!("width must be a non-negative number"),
+    };
+
+    let fill = match fill.as_ref().map(|fill| fill.as_utf8()) {
+        None => " ",
+        Some(Some(fill)) if fill.chars().count() == 1 && fill.width() > 0 => fill,
+        Some(_) => throw!("fill must be a single printable character"),
+    };
+
+    let count = width.saturating_sub(string.width()) / fill.width();
+
+    Ok((string, fill.repeat(count)))
+}
+
+fn parse_digits(chars: &mut std::iter::Peekable<std::str::Chars<'_>>) -> Option<usize> {
+    let mut digits = String::new();
+
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+
+        digits.push(c);
+        chars.next();
+    }
+
+    digits.parse().ok()
+}
+
+/// Write text to the output, padded to fill the given width.
+fn pad(output: &mut String, text: &str, width: usize, left_align: bool, zero_pad: bool) {
+    let padding = width.saturating_sub(text.chars().count());
+
+    if left_align {
+        output.push_str(text);
+        output.extend(std::iter::repeat(' ').take(padding));
+    } else if zero_pad {
+        // Zeros go after the sign.
+        let (sign, digits) = match text.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", text),
+        };
+
+        output.push_str(sign);
+        output.extend(std::iter::repeat('0').take(padding));
+        output.push_str(digits);
+    } else {
+        output.extend(std::iter::repeat(' ').take(padding));
+        output.push_str(text);
+    }
+}
+
+/// Format a number of bytes as a human-readable size, such as `1.5 KiB`.
+///
+/// Sizes use binary units that are powers of 1024, unless the `--decimal` flag
+/// is given, in which case decimal units that are powers of 1000 are used
+/// instead. The `--precision` option sets the most digits to show after the
+/// decimal point, which defaults to 1. Trailing zeros are left off, and sizes
+/// in bytes are always whole numbers.
+async fn format_bytes(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut decimal = false;
+    let mut precision = 1;
+    let mut bytes = None;
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        if arg == "--decimal" {
+            decimal = true;
+        } else if arg == "--precision" {
+            precision = match args.next().as_ref().and_then(coerce::to_number) {
+                Some(precision) if (0.0..=20.0).contains(&precision) && precision.fract() == 0.0 => precision as usize,
+                _ => throw!("precision must be a whole number from 0 to 20"),
+            };
+        } else if bytes.is_none() {
+            bytes = Some(arg);
+        } else {
+            throw!("too many arguments");
+        }
+    }
+
+    let bytes = match bytes.as_ref().and_then(coerce::to_number) {
+        Some(bytes) if bytes.is_finite() => bytes,
+        _ => throw!("number of bytes required"),
+    };
+
+    let (base, units) = if decimal { (1000.0, &DECIMAL_UNITS) } else { (1024.0, &BINARY_UNITS) };
+    let mut size = bytes.abs();
+    let mut unit = 0;
+
+    while size >= base && unit < units.len() - 1 {
+        size /= base;
+        unit += 1;
+    }
+
+    let mut text = format_size(size, if unit == 0 { 0 } else { precision });
+
+    // Rounding can carry a size over into the next unit, such as 1023.96 KiB
+    // becoming 1024 KiB.
+    if text.parse::<f64>().is_ok_and(|rounded| rounded >= base) && unit < units.len() - 1 {
+        unit += 1;
+        text = format_size(size / base, precision);
+    }
+
+    let sign = if bytes < 0.0 && text != "0" { "-" } else { "" };
+
+    Ok(format!("{}{} {}", sign, text, units[unit]).into())
+}
+
+/// Format a number with at most the given number of decimal places.
+fn format_size(size: f64, precision: usize) -> String {
+    let text = format!("{:.*}", precision, size);
+
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_owned()
+    } else {
+        text
+    }
+}
+
+/// Parse a human-readable size, such as `1.5 KiB` or `20MB`, into a number of
+/// bytes.
+///
+/// Units are not case sensitive, and may be binary such as `KiB` or decimal
+/// such as `KB`. A unit of a single letter, such as `K`, is binary. Without a
+/// unit, the number is a number of bytes. The result is rounded to a whole
+/// number of bytes.
+async fn parse_bytes(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let text = match args.first() {
+        Some(value) => coerce::to_string(value),
+        None => throw!("size to parse required"),
+    };
+
+    let text = match text.as_utf8() {
+        Some(text) => text.trim().to_owned(),
+        None => throw!("invalid size '{}'", text),
+    };
+
+    let split = text.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-' || c == '+')).unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+
+    let number = match number.parse::<f64>() {
+        Ok(number) if number.is_finite() => number,
+        _ => throw!("invalid size '{}'", text),
+    };
+
+    let unit = unit.trim().to_ascii_lowercase();
+    let mut chars = unit.chars();
+
+    let multiplier = match (chars.next(), chars.as_str()) {
+        (None, _) | (Some('b'), "") => 1.0,
+        (Some(prefix), suffix) => {
+            let exponent = match "kmgtpezy".find(prefix) {
+                Some(index) => index as i32 + 1,
+                None => throw!("invalid size '{}': unknown unit", text),
+            };
+
+            // A bare prefix such as "K" is binary, like in `du -h` and `ls -h`.
+            let base: f64 = match suffix {
+                "" | "i" | "ib" => 1024.0,
+                "b" => 1000.0,
+                _ => throw!("invalid size '{}': unknown unit", text),
+            };
+
+            base.powi(exponent)
+        }
+    };
+
+    Ok(Value::from((number * multiplier).round()))
+}
+
+/// Get the bytes of a string as a list of numbers from 0 to 255.
+async fn bytes(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let bytes = match args.first() {
+        Some(value) => match value.as_bytes() {
+            Some(bytes) => bytes,
+            None => throw!("expected a string, got {}", value.type_name()),
+        },
+        None => throw!("string required"),
+    };
+
+    Ok(bytes.iter().map(|&byte| u32::from(byte)).collect())
+}
+
+/// Create a string from a list of byte values. The string does not need to be
+/// valid UTF-8.
+async fn from_bytes(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let list = match args.first().and_then(Value::as_list) {
+        Some(list) => list,
+        None => throw!("list of bytes required"),
+    };
+
+    let mut bytes = Vec::with_capacity(list.len());
+
+    for value in list {
+        match value.as_number() {
+            Some(byte) if (0.0..=255.0).contains(&byte) && byte.fract() == 0.0 => bytes.push(byte as u8),
+            _ => throw!("invalid byte value '{}', must be a whole number from 0 to 255", value),
+        }
+    }
+
+    Ok(RipString::from(bytes).into())
+}
+
+/// Get the byte at an index in a string as a number, or nil if the index is
+/// out of range.
+async fn byte_at(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let bytes = match args.first() {
+        Some(value) => match value.as_bytes() {
+            Some(bytes) => bytes,
+            None => throw!("expected a string, got {}", value.type_name()),
+        },
+        None => throw!("string required"),
+    };
+
+    let index = match args.get(1).and_then(coerce::to_number) {
+        Some(index) => index,
+        None => throw!("index must be a number"),
+    };
+
+    if index < 0.0 {
+        return Ok(Value::Nil);
+    }
+
+    Ok(bytes.get(index as usize).map(|&byte| Value::from(u32::from(byte))).unwrap_or(Value::Nil))
+}
+
+/// Split a string into a list of its characters, each as a string of its own.
+async fn chars(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let string = utf8_arg(&args)?;
+
+    Ok(string.chars().map(|c| c.to_string()).collect())
+}
+
+/// Get the character at an index in a string, or nil if the index is out of
+/// range. Indices count characters rather than bytes.
+async fn char_at(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let string = utf8_arg(&args)?;
+
+    let index = match args.get(1).and_then(coerce::to_number) {
+        Some(index) => index,
+        None => throw!("index must be a number"),
+    };
+
+    if index < 0.0 {
+        return Ok(Value::Nil);
+    }
+
+    Ok(string.chars().nth(index as usize).map(|c| Value::from(c.to_string())).unwrap_or(Value::Nil))
+}
+
+/// Get the first argument as a string of characters.
+fn utf8_arg(args: &[Value]) -> Result<String, Exception> {
+    let string = match args.first() {
+        Some(value) => coerce::to_string(value),
+        None => throw!("string required"),
+    };
+
+    match string.as_utf8() {
+        Some(string) => Ok(string.to_owned()),
+        None => throw!("string is not valid UTF-8"),
+    }
+}
@@ -0,0 +1,122 @@
+//! Functions for styling text written to a terminal.
+//!
+//! Styles are applied by wrapping a string in ANSI escape sequences. If
+//! standard output is not a terminal, strings are returned unchanged so that
+//! escape sequences do not end up in files or captured output.
+
+use riptide_runtime::{
+    coerce,
+    io::Output,
+    prelude::*,
+    table,
+    throw,
+    RipString,
+};
+
+pub fn load() -> Result<Value, Exception> {
+    Ok(table! {
+        "bold" => Value::foreign_fn(bold),
+        "color" => Value::foreign_fn(color),
+        "dim" => Value::foreign_fn(dim),
+        "strip-color" => Value::foreign_fn(strip_color),
+        "underline" => Value::foreign_fn(underline),
+    }
+    .into())
+}
+
+/// Names of the colors that can be used, in the order of their ANSI codes.
+const COLORS: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+/// Color a string, such as `color red 'error'`. The color can be any of the
+/// eight standard terminal colors, optionally prefixed with `bright-`.
+async fn color(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let name = match args.first() {
+        Some(name) => coerce::to_string(name),
+        None => throw!("color name required"),
+    };
+
+    let (name, base) = match name.as_utf8() {
+        Some(name) => match name.strip_prefix("bright-") {
+            Some(name) => (name, 90),
+            None => (name, 30),
+        },
+        None => throw!("unknown color: {}", name),
+    };
+
+    let code = match COLORS.iter().position(|color| *color == name) {
+        Some(index) => base + index,
+        None => throw!("unknown color: {}", name),
+    };
+
+    style(fiber, &args[1..], &code.to_string(), "39")
+}
+
+/// Make a string bold.
+async fn bold(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    style(fiber, &args, "1", "22")
+}
+
+/// Make a string dim.
+async fn dim(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    style(fiber, &args, "2", "22")
+}
+
+/// Underline a string.
+async fn underline(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    style(fiber, &args, "4", "24")
+}
+
+/// Remove all ANSI escape sequences from a string.
+async fn strip_color(_: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    match args.first() {
+        Some(string) => Ok(Value::from(strip(coerce::to_string(string).as_bytes()))),
+        None => throw!("string required"),
+    }
+}
+
+/// Wrap a string in escape sequences that turn a style on and off again, if
+/// standard output is a terminal.
+///
+/// Each style is turned off with its own code instead of resetting all styles,
+/// so that styles can be nested.
+fn style(fiber: &mut Fiber, args: &[Value], on: &str, off: &str) -> Result<Value, Exception> {
+    let string = match args {
+        [string] => coerce::to_string(string),
+        [] => throw!("string required"),
+        _ => throw!("too many arguments"),
+    };
+
+    if !fiber.stdout().is_terminal() {
+        return Ok(Value::from(string));
+    }
+
+    let mut bytes = Vec::with_capacity(string.as_bytes().len() + 10);
+    bytes.extend_from_slice(format!("\x1b[{}m", on).as_bytes());
+    bytes.extend_from_slice(string.as_bytes());
+    bytes.extend_from_slice(format!("\x1b[{}m", off).as_bytes());
+
+    Ok(Value::from(RipString::from(bytes)))
+}
+
+/// Remove escape sequences from text, which start with `ESC [` and end with a
+/// byte in the range `@` to `~`.
+fn strip(bytes: &[u8]) -> RipString {
+    let mut stripped = Vec::with_capacity(bytes.len());
+    let mut iter = bytes.iter().copied().peekable();
+
+    while let Some(byte) = iter.next() {
+        if byte == 0x1b && iter.peek() == Some(&b'[') {
+            iter.next();
+
+            for byte in iter.by_ref() {
+                if (0x40..=0x7e).contains(&byte) {
+                    break;
+                }
+            }
+        } else {
+            stripped.push(byte);
+        }
+    }
+
+    RipString::from(stripped)
+}
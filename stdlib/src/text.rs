@@ -0,0 +1,158 @@
+//! Functions for filtering lines of text passing through a pipeline.
+
+use riptide_runtime::{
+    coerce,
+    prelude::*,
+    table,
+    throw,
+};
+use regex::bytes::RegexBuilder;
+use std::collections::VecDeque;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+pub fn load() -> Result<Value, Exception> {
+    Ok(table! {
+        "grep" => Value::foreign_fn(grep),
+        "head" => Value::foreign_fn(head),
+        "tail" => Value::foreign_fn(tail),
+    }
+    .into())
+}
+
+/// Copy the lines of standard input that match a pattern to standard output.
+///
+/// The pattern may be a regex literal or a string holding a regular
+/// expression. Lines that do not match are copied instead if the `-v` flag is
+/// given, and the `-i` flag makes the pattern case-insensitive.
+async fn grep(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let mut invert = false;
+    let mut case_insensitive = false;
+    let mut pattern = None;
+
+    for arg in args.iter() {
+        if arg == "-v" {
+            invert = true;
+        } else if arg == "-i" {
+            case_insensitive = true;
+        } else if pattern.is_none() {
+            pattern = Some(match arg {
+                Value::Regex(regex) => regex.as_str().to_owned(),
+                Value::String(string) => match string.as_utf8() {
+                    Some(string) => string.to_owned(),
+                    None => throw!("pattern must be a UTF-8 string"),
+                },
+                _ => throw!("pattern must be a regex or a string, got {}", arg.type_name()),
+            });
+        } else {
+            throw!("too many arguments");
+        }
+    }
+
+    let pattern = match pattern {
+        Some(pattern) => pattern,
+        None => throw!("pattern required"),
+    };
+
+    let regex = match RegexBuilder::new(&pattern).case_insensitive(case_insensitive).build() {
+        Ok(regex) => regex,
+        Err(e) => throw!("invalid pattern: {}", e),
+    };
+
+    let mut stdin = BufReader::new(fiber.stdin().try_clone()?);
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+
+        if stdin.read_until(b'\n', &mut line).await? == 0 {
+            break;
+        }
+
+        let text = line.strip_suffix(b"\n").unwrap_or(&line);
+        let text = text.strip_suffix(b"\r").unwrap_or(text);
+
+        if regex.is_match(text) != invert {
+            fiber.stdout().write_all(&line).await?;
+        }
+    }
+
+    fiber.stdout().flush().await?;
+
+    Ok(Value::Nil)
+}
+
+/// Copy the first lines of standard input to standard output.
+///
+/// Ten lines are copied unless a different count is given with `-n`. Standard
+/// input is closed once enough lines have been read, so that the command
+/// writing to it can stop.
+async fn head(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let count = line_count(&args)?;
+    let mut stdin = BufReader::new(fiber.stdin().try_clone()?);
+    let mut line = Vec::new();
+
+    for _ in 0..count {
+        line.clear();
+
+        if stdin.read_until(b'\n', &mut line).await? == 0 {
+            break;
+        }
+
+        fiber.stdout().write_all(&line).await?;
+    }
+
+    fiber.stdout().flush().await?;
+
+    drop(stdin);
+    fiber.io().close_stdin().await?;
+
+    Ok(Value::Nil)
+}
+
+/// Copy the last lines of standard input to standard output, once all of the
+/// input has been read.
+///
+/// Ten lines are copied unless a different count is given with `-n`.
+async fn tail(fiber: &mut Fiber, args: Vec<Value>) -> Result<Value, Exception> {
+    let count = line_count(&args)?;
+    let mut stdin = BufReader::new(fiber.stdin().try_clone()?);
+    // The count can be far more lines than there are, so only preallocate
+    // room for a modest number of them.
+    let mut lines = VecDeque::with_capacity(count.min(1024));
+
+    loop {
+        let mut line = Vec::new();
+
+        if stdin.read_until(b'\n', &mut line).await? == 0 {
+            break;
+        }
+
+        if lines.len() == count {
+            lines.pop_front();
+        }
+
+        if count > 0 {
+            lines.push_back(line);
+        }
+    }
+
+    for line in lines {
+        fiber.stdout().write_all(&line).await?;
+    }
+
+    fiber.stdout().flush().await?;
+
+    Ok(Value::Nil)
+}
+
+/// Get the number of lines to read from the `-n` argument.
+fn line_count(args: &[Value]) -> Result<usize, Exception> {
+    match args {
+        [] => Ok(10),
+        [flag, count] if flag == "-n" => match coerce::to_number(count) {
+            Some(count) if count >= 0.0 && count.fract() == 0.0 => Ok(count as usize),
+            _ => throw!("line count must be a non-negative integer"),
+        },
+        _ => throw!("usage: -n <count>"),
+    }
+}
@@ -0,0 +1,11 @@
+//! Helpers shared by the standard library tests.
+
+use riptide_runtime::prelude::*;
+
+/// Create a new runtime with the standard library loaded, returning its root
+/// fiber.
+pub async fn fiber() -> Fiber {
+    let mut fiber = riptide_runtime::init().await.unwrap();
+    riptide_stdlib::init(&mut fiber).await.unwrap();
+    fiber
+}
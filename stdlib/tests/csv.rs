@@ -0,0 +1,58 @@
+mod common;
+
+use riptide_runtime::prelude::*;
+
+async fn csv(text: &str, command: &str) -> Result<Value, Exception> {
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("text", text);
+
+    fiber.execute(None, format!("
+        import 'std/csv' for *
+        {}
+    ", command)).await
+}
+
+#[tokio::test]
+async fn parse_quoted_fields() {
+    let rows = csv("a,\"b,c\",\"say \"\"hi\"\"\"\r\n1,\"two\nlines\",\n", "parse $text").await.unwrap();
+    let rows = rows.as_list().unwrap();
+
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0], Value::List(vec!["a".into(), "b,c".into(), "say \"hi\"".into()]));
+    assert_eq!(rows[1], Value::List(vec!["1".into(), "two\nlines".into(), "".into()]));
+}
+
+#[tokio::test]
+async fn parse_with_header() {
+    let rows = csv("name,age\nalice,30\nbob,25\n", "parse --header $text").await.unwrap();
+    let rows = rows.as_list().unwrap();
+
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[1].get("name"), "bob");
+    assert_eq!(rows[1].get("age"), "25");
+
+    let error = csv("name,age\nalice\n", "parse --header $text").await.unwrap_err();
+    assert_eq!(error.message().to_string(), "row 2 has 1 fields, but the header has 2");
+}
+
+#[tokio::test]
+async fn parse_malformed_input_throws() {
+    let error = csv("a,b\nc,\"d\n", "parse $text").await.unwrap_err();
+    assert_eq!(error.message().to_string(), "row 2, column 2: unterminated quoted field");
+
+    let error = csv("a,\"b\"c\n", "parse $text").await.unwrap_err();
+    assert_eq!(error.message().to_string(), "row 1, column 2: unexpected text after closing quote");
+}
+
+#[tokio::test]
+async fn stringify_round_trips() {
+    let text = "a,\"b,c\",\"say \"\"hi\"\"\"\r\n1,\"two\nlines\",\r\n";
+    let result = csv(text, "stringify (parse $text)").await.unwrap();
+
+    assert_eq!(result, text);
+
+    let text = "name,age\r\nalice,30\r\n";
+    let result = csv(text, "stringify (parse --header $text)").await.unwrap();
+
+    assert_eq!(result, text);
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use riptide_runtime::prelude::*;
+
+async fn encoding(text: &str, command: &str) -> Result<Value, Exception> {
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("text", text);
+
+    fiber.execute(None, format!("
+        import 'std/encoding' for *
+        {}
+    ", command)).await
+}
+
+#[tokio::test]
+async fn base64_round_trip() {
+    for (text, encoded) in [("", ""), ("f", "Zg=="), ("fo", "Zm8="), ("foo", "Zm9v"), ("foobar", "Zm9vYmFy")] {
+        assert_eq!(encoding(text, "base64-encode $text").await.unwrap(), encoded);
+        assert_eq!(encoding(encoded, "base64-decode $text").await.unwrap(), text);
+    }
+}
+
+#[tokio::test]
+async fn base64_url_safe() {
+    assert_eq!(encoding("??>", "base64-encode $text").await.unwrap(), "Pz8+");
+    assert_eq!(encoding("??>", "base64-encode --url $text").await.unwrap(), "Pz8-");
+    assert_eq!(encoding("Pz8-", "base64-decode --url $text").await.unwrap(), "??>");
+    assert_eq!(encoding("Zm8", "base64-decode --url $text").await.unwrap(), "fo");
+}
+
+#[tokio::test]
+async fn base64_decode_invalid_input_throws() {
+    let error = encoding("Zm9v!", "base64-decode $text").await.unwrap_err();
+    assert_eq!(error.message().to_string(), "invalid base64: wrong length");
+
+    let error = encoding("Zm-v", "base64-decode $text").await.unwrap_err();
+    assert_eq!(error.message().to_string(), "invalid base64: unexpected character '-'");
+}
+
+#[tokio::test]
+async fn hex_round_trip() {
+    assert_eq!(encoding("hi!\n", "hex-encode $text").await.unwrap(), "6869210a");
+    assert_eq!(encoding("6869210A", "hex-decode $text").await.unwrap(), "hi!\n");
+
+    let error = encoding("686", "hex-decode $text").await.unwrap_err();
+    assert_eq!(error.message().to_string(), "invalid hex: odd number of digits");
+
+    let error = encoding("6g", "hex-decode $text").await.unwrap_err();
+    assert_eq!(error.message().to_string(), "invalid hex: unexpected character 'g'");
+}
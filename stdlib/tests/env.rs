@@ -0,0 +1,96 @@
+mod common;
+
+use std::{env, fs, process};
+
+#[tokio::test]
+async fn load_dotenv_sets_variables() {
+    let path = env::temp_dir().join(format!("riptide-dotenv-{}", process::id()));
+    fs::write(&path, r#"
+# A comment
+RIPTIDE_DOTENV_PLAIN=plain value # trailing comment
+export RIPTIDE_DOTENV_DOUBLE="double # quoted\nvalue"
+RIPTIDE_DOTENV_SINGLE='single $quoted\n'
+"#).unwrap();
+
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("path", path.clone());
+
+    fiber.execute(None, r#"
+        import 'std/env' for load-dotenv
+        load-dotenv $path
+    "#).await.unwrap();
+
+    assert_eq!(env::var("RIPTIDE_DOTENV_PLAIN").unwrap(), "plain value");
+    assert_eq!(env::var("RIPTIDE_DOTENV_DOUBLE").unwrap(), "double # quoted\nvalue");
+    assert_eq!(env::var("RIPTIDE_DOTENV_SINGLE").unwrap(), "single $quoted\\n");
+
+    let result = fiber.execute(None, r#"
+        import 'std/env' for env-get
+        env-get RIPTIDE_DOTENV_PLAIN
+    "#).await.unwrap();
+
+    assert_eq!(result, "plain value");
+    assert_eq!(fiber.globals().get("env").get("RIPTIDE_DOTENV_PLAIN"), "plain value");
+
+    fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn load_dotenv_reports_invalid_line() {
+    let path = env::temp_dir().join(format!("riptide-dotenv-invalid-{}", process::id()));
+    fs::write(&path, "RIPTIDE_DOTENV_OK=1\nnot a variable\n").unwrap();
+
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("path", path.clone());
+
+    let error = fiber.execute(None, r#"
+        import 'std/env' for load-dotenv
+        load-dotenv $path
+    "#).await.unwrap_err();
+
+    assert!(error.message().to_string().contains("on line 2"));
+
+    fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn env_set_and_list() {
+    let mut fiber = common::fiber().await;
+
+    let result = fiber.execute(None, r#"
+        import 'std/env' for env-set env-list
+        env-set RIPTIDE_ENV_SET_TEST hello
+        env-list
+    "#).await.unwrap();
+
+    assert_eq!(result.get("RIPTIDE_ENV_SET_TEST"), "hello");
+
+    fiber.execute(None, r#"
+        import 'std/env' for env-set
+        env-set RIPTIDE_ENV_SET_TEST
+    "#).await.unwrap();
+
+    assert!(env::var_os("RIPTIDE_ENV_SET_TEST").is_none());
+}
+
+#[tokio::test]
+async fn path_helpers_deduplicate_entries() {
+    env::set_var("RIPTIDE_PATH_TEST", "/usr/bin::/bin:/usr/bin:/opt/bin");
+    let mut fiber = common::fiber().await;
+
+    fiber.execute(None, r#"
+        import 'std/env' for path-prepend
+        path-prepend --var RIPTIDE_PATH_TEST /opt/bin /home/bin
+    "#).await.unwrap();
+
+    assert_eq!(env::var("RIPTIDE_PATH_TEST").unwrap(), "/opt/bin:/home/bin:/usr/bin:/bin");
+
+    fiber.execute(None, r#"
+        import 'std/env' for path-append path-remove
+        path-append --var RIPTIDE_PATH_TEST /usr/bin
+        path-remove --var RIPTIDE_PATH_TEST /home/bin
+    "#).await.unwrap();
+
+    assert_eq!(env::var("RIPTIDE_PATH_TEST").unwrap(), "/opt/bin:/bin:/usr/bin");
+    assert_eq!(fiber.globals().get("env").get("RIPTIDE_PATH_TEST"), "/opt/bin:/bin:/usr/bin");
+}
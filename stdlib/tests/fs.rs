@@ -0,0 +1,220 @@
+mod common;
+
+use riptide_runtime::prelude::*;
+use std::{env, fs, path::PathBuf, process};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("riptide-fs-{}-{}", name, process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn write_append_and_read() {
+    let dir = temp_dir("write");
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("path", dir.join("file.txt"));
+
+    let result = fiber.execute(None, r#"
+        import 'std/fs' for read write append
+
+        write $path "hello"
+        append $path " world"
+        read $path
+    "#).await.unwrap();
+
+    assert_eq!(result, "hello world");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn read_missing_file_includes_path() {
+    let mut fiber = common::fiber().await;
+
+    let error = fiber.execute(None, r#"
+        import 'std/fs' for read
+
+        read 'does-not-exist.txt'
+    "#).await.unwrap_err();
+
+    assert!(error.message().to_string().contains("does-not-exist.txt"));
+}
+
+#[tokio::test]
+async fn list_dir_exists_and_remove() {
+    let dir = temp_dir("list");
+    fs::write(dir.join("b"), "").unwrap();
+    fs::write(dir.join("a"), "").unwrap();
+
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("dir", dir.clone());
+    fiber.globals().set("path", dir.join("a"));
+
+    let result = fiber.execute(None, r#"
+        import 'std/fs' for list-dir
+        list-dir $dir
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::from(vec![Value::from("a"), Value::from("b")]));
+
+    let result = fiber.execute(None, r#"
+        import 'std/fs' for exists remove
+
+        remove $path
+        exists $path
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::FALSE);
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn mkdir_copy_and_rename() {
+    let dir = temp_dir("copy");
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("nested", dir.join("a/b"));
+    fiber.globals().set("src", dir.join("a"));
+    fiber.globals().set("copied", dir.join("c"));
+    fiber.globals().set("moved", dir.join("d"));
+    fiber.globals().set("file", dir.join("a/b/file.txt"));
+
+    fiber.execute(None, r#"
+        import 'std/fs' for copy mkdir rename write
+
+        mkdir -p $nested
+        mkdir -p $nested
+        write $file "hello"
+        copy $src $copied
+        rename $copied $moved
+    "#).await.unwrap();
+
+    assert_eq!(fs::read_to_string(dir.join("d/b/file.txt")).unwrap(), "hello");
+    assert!(!dir.join("c").exists());
+
+    let error = fiber.execute(None, r#"
+        import 'std/fs' for mkdir
+        mkdir $nested
+    "#).await.unwrap_err();
+
+    assert!(error.message().to_string().contains("error creating directory"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn stat_follows_symlinks_unless_lstat() {
+    let dir = temp_dir("stat");
+    fs::write(dir.join("file"), "hello").unwrap();
+    std::os::unix::fs::symlink(dir.join("file"), dir.join("link")).unwrap();
+
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("link", dir.join("link"));
+    fiber.globals().set("missing", dir.join("missing"));
+
+    let result = fiber.execute(None, r#"
+        import 'std/fs' for stat
+        stat $link
+    "#).await.unwrap();
+
+    let stat = result.as_table().unwrap();
+    assert_eq!(stat.get("size"), Value::from(5u64));
+    assert_eq!(stat.get("is-file"), Value::TRUE);
+    assert_eq!(stat.get("is-symlink"), Value::TRUE);
+
+    let result = fiber.execute(None, r#"
+        import 'std/fs' for stat
+        stat --lstat $link
+    "#).await.unwrap();
+
+    assert_eq!(result.as_table().unwrap().get("is-file"), Value::FALSE);
+
+    let error = fiber.execute(None, r#"
+        import 'std/fs' for stat
+        stat $missing
+    "#).await.unwrap_err();
+
+    assert!(error.message().to_string().contains("missing"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn read_lines_strips_terminators() {
+    let dir = temp_dir("lines");
+    fs::write(dir.join("file.txt"), "one\r\ntwo\nthree\n").unwrap();
+
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("path", dir.join("file.txt"));
+
+    let result = fiber.execute(None, r#"
+        import 'std/fs' for read-lines
+        read-lines $path
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::from(vec![Value::from("one"), Value::from("two"), Value::from("three")]));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn tee_copies_input_to_file_and_output() {
+    let dir = temp_dir("tee");
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("path", dir.join("file.txt"));
+
+    let result = fiber.execute(None, r#"
+        import 'std/fs' for read tee
+
+        { print "hello" } | tee $path | { }
+        { print " world" } | tee --append $path
+
+        read $path
+    "#).await.unwrap();
+
+    assert_eq!(result, "hello world");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn dir_size_totals_files_without_following_symlinks() {
+    let dir = temp_dir("dir-size");
+    fs::create_dir_all(dir.join("sub/deeper")).unwrap();
+    fs::write(dir.join("a"), "12345").unwrap();
+    fs::write(dir.join("sub/b"), "123").unwrap();
+    fs::write(dir.join("sub/deeper/c"), "12").unwrap();
+    fs::hard_link(dir.join("sub/b"), dir.join("sub/b-again")).unwrap();
+    std::os::unix::fs::symlink(&dir, dir.join("sub/loop")).unwrap();
+
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("dir", dir.clone());
+    fiber.globals().set("file", dir.join("a"));
+
+    let result = fiber.execute(None, r#"
+        import 'std/fs' for dir-size
+        dir-size $dir
+    "#).await.unwrap();
+
+    assert_eq!(result, Value::from(10u64));
+
+    let result = fiber.execute(None, r#"
+        import 'std/fs' for dir-size
+        dir-size --by-dir $dir
+    "#).await.unwrap();
+
+    let totals = result.as_table().unwrap();
+    assert_eq!(totals.get(dir.to_str().unwrap()), Value::from(10u64));
+    assert_eq!(totals.get(dir.join("sub").to_str().unwrap()), Value::from(5u64));
+    assert_eq!(totals.get(dir.join("sub/deeper").to_str().unwrap()), Value::from(2u64));
+
+    let error = fiber.execute(None, r#"
+        import 'std/fs' for dir-size
+        dir-size $file
+    "#).await.unwrap_err();
+
+    assert!(error.message().to_string().contains("not a directory"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
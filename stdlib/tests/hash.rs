@@ -0,0 +1,41 @@
+mod common;
+
+use riptide_runtime::prelude::*;
+use std::{env, fs, process};
+
+async fn hash(command: &str) -> Result<Value, Exception> {
+    let mut fiber = common::fiber().await;
+
+    fiber.execute(None, format!("
+        import 'std/hash' for *
+        {}
+    ", command)).await
+}
+
+#[tokio::test]
+async fn hash_strings() {
+    assert_eq!(hash("sha256 abc").await.unwrap(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+    assert_eq!(hash("sha1 abc").await.unwrap(), "a9993e364706816aba3e25717850c26c9cd0d89d");
+    assert_eq!(hash("md5 abc").await.unwrap(), "900150983cd24fb0d6963f7d28e17f72");
+    assert_eq!(hash("md5 ''").await.unwrap(), "d41d8cd98f00b204e9800998ecf8427e");
+}
+
+#[tokio::test]
+async fn hash_file_in_pieces() {
+    // Larger than a single read, so the file is hashed in more than one piece.
+    let path = env::temp_dir().join(format!("riptide-hash-{}", process::id()));
+    fs::write(&path, "a".repeat(200_000)).unwrap();
+
+    let sha256 = hash(&format!("hash-file '{}'", path.display())).await.unwrap();
+    let md5 = hash(&format!("hash-file --md5 '{}'", path.display())).await.unwrap();
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(sha256, "2287d207f24a941ff3b56c04c8a25ad56b63e3023207b3bb5b4ac0c9869d74be");
+    assert_eq!(md5, "561b1994f6baacd6e5eaf4baaa12849f");
+}
+
+#[tokio::test]
+async fn hash_missing_file_throws() {
+    let error = hash("hash-file /does/not/exist").await.unwrap_err();
+    assert!(error.message().to_string().starts_with("error reading '/does/not/exist'"));
+}
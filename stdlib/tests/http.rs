@@ -0,0 +1,89 @@
+mod common;
+
+use riptide_runtime::prelude::*;
+use std::{
+    io::{Read, Write},
+    net::TcpListener,
+    thread,
+};
+
+/// Serve a single request with a canned response, returning the server's URL
+/// and a handle that yields the raw request that was received.
+fn serve_once(response: &'static str) -> (String, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/path", listener.local_addr().unwrap());
+
+    let handle = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0; 4096];
+
+        // Read the headers, followed by the body if one was sent.
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+            let len = stream.read(&mut buffer).unwrap();
+            request.extend_from_slice(&buffer[..len]);
+        }
+
+        let text = String::from_utf8_lossy(&request).to_lowercase();
+        let head_len = text.find("\r\n\r\n").unwrap() + 4;
+        let content_length = text.lines()
+            .find_map(|line| line.strip_prefix("content-length: "))
+            .map_or(0, |len| len.trim().parse::<usize>().unwrap());
+
+        while request.len() < head_len + content_length {
+            let len = stream.read(&mut buffer).unwrap();
+            request.extend_from_slice(&buffer[..len]);
+        }
+
+        stream.write_all(response.as_bytes()).unwrap();
+        String::from_utf8(request).unwrap()
+    });
+
+    (url, handle)
+}
+
+async fn http(url: &str, command: &str) -> Result<Value, Exception> {
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("url", url);
+
+    fiber.execute(None, format!("
+        import 'std/http' for *
+        {}
+    ", command)).await
+}
+
+#[tokio::test]
+async fn get_returns_response() {
+    let (url, server) = serve_once("HTTP/1.1 404 Not Found\r\nContent-Length: 4\r\nX-Test: a\r\nX-Test: b\r\n\r\nnope");
+
+    let response = http(&url, r#"get $url --headers [Accept: "text/plain"]"#).await.unwrap();
+    let request = server.join().unwrap();
+
+    assert!(request.starts_with("GET /path HTTP/1.1\r\n"));
+    assert!(request.to_lowercase().contains("accept: text/plain\r\n"));
+    assert_eq!(response.get("status"), Value::from(404u32));
+    assert_eq!(response.get("headers").get("x-test"), "a, b");
+    assert_eq!(response.get("body"), "nope");
+}
+
+#[tokio::test]
+async fn post_sends_body() {
+    let (url, server) = serve_once("HTTP/1.1 201 Created\r\nContent-Length: 0\r\n\r\n");
+
+    let response = http(&url, "post $url 'hello'").await.unwrap();
+    let request = server.join().unwrap();
+
+    assert!(request.starts_with("POST /path HTTP/1.1\r\n"));
+    assert!(request.ends_with("\r\n\r\nhello"));
+    assert_eq!(response.get("status"), Value::from(201u32));
+}
+
+#[tokio::test]
+async fn strict_throws_on_error_status() {
+    let (url, server) = serve_once("HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\n\r\n");
+
+    let error = http(&url, "get --strict $url").await.unwrap_err();
+    server.join().unwrap();
+
+    assert_eq!(error.message().to_string(), format!("request to '{}' failed with status 500 Internal Server Error", url));
+}
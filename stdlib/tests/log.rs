@@ -0,0 +1,45 @@
+mod common;
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Mutex;
+
+static RECORDS: Mutex<Vec<(Level, String)>> = Mutex::new(Vec::new());
+
+struct CaptureLogger;
+
+impl Log for CaptureLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.target() == "riptide::script"
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            RECORDS.lock().unwrap().push((record.level(), record.args().to_string()));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[tokio::test]
+async fn log_functions_write_to_logger() {
+    log::set_logger(&CaptureLogger).unwrap();
+    log::set_max_level(LevelFilter::Info);
+
+    let mut fiber = common::fiber().await;
+
+    fiber.execute(None, r#"
+        import 'std/log' for *
+
+        debug 'not shown'
+        info hello world
+        warn 'count:' 3
+        error [a b]
+    "#).await.unwrap();
+
+    assert_eq!(*RECORDS.lock().unwrap(), [
+        (Level::Info, "hello world".to_owned()),
+        (Level::Warn, "count: 3".to_owned()),
+        (Level::Error, "[a,b]".to_owned()),
+    ]);
+}
@@ -0,0 +1,47 @@
+mod common;
+
+use std::env;
+
+#[tokio::test]
+async fn exec_missing_command_throws() {
+    let mut fiber = common::fiber().await;
+
+    let error = fiber.execute(None, "exec riptide-no-such-command").await.unwrap_err();
+    assert_eq!(error.message().to_string(), "no such command or file");
+
+    // The process carries on as before after a failed exec.
+    let error = fiber.execute(None, "exec").await.unwrap_err();
+    assert_eq!(error.message().to_string(), "command to execute is required");
+
+    assert_eq!(fiber.execute(None, "return ok").await.unwrap(), "ok");
+}
+
+#[tokio::test]
+async fn exec_failure_restores_working_directory() {
+    let mut fiber = common::fiber().await;
+    let original = env::current_dir().unwrap();
+
+    fiber.globals().set("dir", env::temp_dir());
+
+    let error = fiber.execute(None, r#"
+        let @cwd = $dir {
+            exec riptide-no-such-command
+        }
+    "#).await.unwrap_err();
+
+    assert_eq!(error.message().to_string(), "no such command or file");
+    assert_eq!(env::current_dir().unwrap(), original);
+}
+
+#[tokio::test]
+async fn exec_in_missing_directory_throws() {
+    let mut fiber = common::fiber().await;
+
+    let error = fiber.execute(None, r#"
+        let @cwd = /riptide-no-such-dir {
+            exec true
+        }
+    "#).await.unwrap_err();
+
+    assert!(error.message().to_string().starts_with("failed to change directory to '/riptide-no-such-dir': "));
+}
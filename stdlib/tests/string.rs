@@ -0,0 +1,136 @@
+mod common;
+
+use riptide_runtime::prelude::*;
+
+async fn string_fn(name: &str, args: &str) -> Result<Value, Exception> {
+    let mut fiber = common::fiber().await;
+
+    fiber.execute(None, format!("
+        import 'std/string' for {0}
+        {0} {1}
+    ", name, args)).await
+}
+
+async fn format(args: &str) -> Result<Value, Exception> {
+    string_fn("format", args).await
+}
+
+#[tokio::test]
+async fn format_placeholders() {
+    assert_eq!(format("'%s has %d items' box 3.7").await.unwrap(), "box has 3 items");
+    assert_eq!(format("'%.2f%%' 12.345").await.unwrap(), "12.35%");
+    assert_eq!(format("'%f' 1").await.unwrap(), "1.000000");
+    assert_eq!(format("'%x %x' 255 -16").await.unwrap(), "ff -10");
+}
+
+#[tokio::test]
+async fn format_width_and_padding() {
+    assert_eq!(format("'[%5s]' ab").await.unwrap(), "[   ab]");
+    assert_eq!(format("'[%-5s]' ab").await.unwrap(), "[ab   ]");
+    assert_eq!(format("'[%05d]' -42").await.unwrap(), "[-0042]");
+    assert_eq!(format("'[%8.3f]' 3.14159").await.unwrap(), "[   3.142]");
+    assert_eq!(format("'[%.3s]' abcdef").await.unwrap(), "[abc]");
+}
+
+#[tokio::test]
+async fn format_argument_count_mismatch_throws() {
+    assert!(format("'%s %s' a").await.is_err());
+    assert!(format("'%s' a b").await.is_err());
+    assert!(format("'%d' abc").await.is_err());
+}
+
+async fn split_lines(args: &str) -> Result<Value, Exception> {
+    string_fn("split-lines", args).await
+}
+
+#[tokio::test]
+async fn split_lines_strips_terminators() {
+    assert_eq!(split_lines("\"a\\nb\\r\\nc\"").await.unwrap(), Value::from(vec![Value::from("a"), Value::from("b"), Value::from("c")]));
+    assert_eq!(split_lines("\"a\\n\"").await.unwrap(), Value::from(vec![Value::from("a")]));
+    assert_eq!(split_lines("--keep-trailing \"a\\n\"").await.unwrap(), Value::from(vec![Value::from("a"), Value::from("")]));
+    assert_eq!(split_lines("''").await.unwrap(), Value::from(Vec::<Value>::new()));
+}
+
+#[tokio::test]
+async fn trim_prefix_and_suffix() {
+    assert_eq!(string_fn("trim-prefix", "v1.2.3 v").await.unwrap(), "1.2.3");
+    assert_eq!(string_fn("trim-prefix", "1.2.3 v").await.unwrap(), "1.2.3");
+    assert_eq!(string_fn("trim-suffix", "file.tar.gz .gz").await.unwrap(), "file.tar");
+    assert_eq!(string_fn("trim-suffix", "file.tar .gz").await.unwrap(), "file.tar");
+}
+
+#[tokio::test]
+async fn pad_to_display_width() {
+    assert_eq!(string_fn("pad-left", "42 5").await.unwrap(), "   42");
+    assert_eq!(string_fn("pad-left", "42 5 0").await.unwrap(), "00042");
+    assert_eq!(string_fn("pad-right", "ab 4 .").await.unwrap(), "ab..");
+    assert_eq!(string_fn("pad-right", "abcdef 3").await.unwrap(), "abcdef");
+    assert_eq!(string_fn("pad-right", "日本 6").await.unwrap(), "日本  ");
+    assert!(string_fn("pad-left", "a 3 xy").await.is_err());
+}
+
+#[tokio::test]
+async fn format_bytes_in_binary_and_decimal_units() {
+    assert_eq!(string_fn("format-bytes", "0").await.unwrap(), "0 B");
+    assert_eq!(string_fn("format-bytes", "512").await.unwrap(), "512 B");
+    assert_eq!(string_fn("format-bytes", "1536").await.unwrap(), "1.5 KiB");
+    assert_eq!(string_fn("format-bytes", "1048576").await.unwrap(), "1 MiB");
+    assert_eq!(string_fn("format-bytes", "--decimal 1500000").await.unwrap(), "1.5 MB");
+    assert_eq!(string_fn("format-bytes", "--precision 3 1234567").await.unwrap(), "1.177 MiB");
+    assert_eq!(string_fn("format-bytes", "-2048").await.unwrap(), "-2 KiB");
+}
+
+#[tokio::test]
+async fn format_bytes_rounds_into_next_unit() {
+    assert_eq!(string_fn("format-bytes", "1048575").await.unwrap(), "1 MiB");
+    assert_eq!(string_fn("format-bytes", "1e30").await.unwrap(), "827180.6 YiB");
+    assert!(string_fn("format-bytes", "lots").await.is_err());
+    assert!(string_fn("format-bytes", "--precision -1 10").await.is_err());
+}
+
+#[tokio::test]
+async fn parse_bytes_reads_units() {
+    assert_eq!(string_fn("parse-bytes", "'1.5 KiB'").await.unwrap(), 1536.0);
+    assert_eq!(string_fn("parse-bytes", "20MB").await.unwrap(), 20000000.0);
+    assert_eq!(string_fn("parse-bytes", "2k").await.unwrap(), 2048.0);
+    assert_eq!(string_fn("parse-bytes", "'100 b'").await.unwrap(), 100.0);
+    assert_eq!(string_fn("parse-bytes", "42").await.unwrap(), 42.0);
+    assert!(string_fn("parse-bytes", "'12 parsecs'").await.is_err());
+    assert!(string_fn("parse-bytes", "KiB").await.is_err());
+}
+
+#[tokio::test]
+async fn bytes_of_strings() {
+    assert_eq!(string_fn("bytes", "'hé'").await.unwrap(), Value::from(vec![Value::from(104.0), Value::from(195.0), Value::from(169.0)]));
+    assert_eq!(string_fn("bytes", "''").await.unwrap(), Value::from(vec![]));
+    assert!(string_fn("bytes", "[1 2]").await.is_err());
+}
+
+#[tokio::test]
+async fn from_bytes_builds_any_string() {
+    assert_eq!(string_fn("from-bytes", "[104 105]").await.unwrap(), "hi");
+    assert_eq!(string_fn("from-bytes", "[255 0]").await.unwrap(), b"\xff\0"[..]);
+    assert!(string_fn("from-bytes", "[256]").await.is_err());
+    assert!(string_fn("from-bytes", "[1.5]").await.is_err());
+    assert!(string_fn("from-bytes", "[a]").await.is_err());
+}
+
+#[tokio::test]
+async fn byte_at_index() {
+    assert_eq!(string_fn("byte-at", "abc 1").await.unwrap(), 98.0);
+    assert_eq!(string_fn("byte-at", "abc 3").await.unwrap(), Value::Nil);
+    assert_eq!(string_fn("byte-at", "abc -1").await.unwrap(), Value::Nil);
+}
+
+#[tokio::test]
+async fn chars_of_strings() {
+    assert_eq!(string_fn("chars", "'hé!'").await.unwrap(), Value::from(vec![Value::from("h"), Value::from("é"), Value::from("!")]));
+    assert_eq!(string_fn("chars", "''").await.unwrap(), Value::from(vec![]));
+}
+
+#[tokio::test]
+async fn char_at_counts_characters() {
+    assert_eq!(string_fn("char-at", "日本語 1").await.unwrap(), "本");
+    assert_eq!(string_fn("char-at", "日本語 3").await.unwrap(), Value::Nil);
+    assert_eq!(string_fn("char-at", "abc -1").await.unwrap(), Value::Nil);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+#[tokio::test]
+async fn strip_color_removes_escape_sequences() {
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("styled", "\x1b[1m\x1b[31mhello\x1b[39m\x1b[22m world");
+
+    let result = fiber.execute(None, r#"
+        import 'std/term' for strip-color
+        strip-color $styled
+    "#).await.unwrap();
+
+    assert_eq!(result, "hello world");
+}
+
+#[tokio::test]
+async fn styles_can_be_stripped() {
+    let mut fiber = common::fiber().await;
+
+    let result = fiber.execute(None, r#"
+        import 'std/term' for *
+        strip-color (bold (color bright-red (underline (dim 'hello'))))
+    "#).await.unwrap();
+
+    assert_eq!(result, "hello");
+}
+
+#[tokio::test]
+async fn unknown_color_throws() {
+    let mut fiber = common::fiber().await;
+
+    let error = fiber.execute(None, r#"
+        import 'std/term' for color
+        color purple 'hello'
+    "#).await.unwrap_err();
+
+    assert!(error.message().to_string().contains("unknown color"));
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use riptide_runtime::prelude::*;
+use std::{env, fs, path::PathBuf, process};
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("riptide-text-{}-{}", name, process::id()))
+}
+
+async fn filter(name: &str, command: &str) -> Value {
+    filter_input(name, "1 2 3 4 5", command).await
+}
+
+async fn filter_input(name: &str, input: &str, command: &str) -> Value {
+    let path = temp_file(name);
+    let mut fiber = common::fiber().await;
+    fiber.globals().set("path", path.clone());
+
+    let result = fiber.execute(None, format!(r#"
+        import 'std/fs' for read write
+        import 'std/text' for *
+
+        {{ println {} }} | {} | write $path
+        read $path
+    "#, input, command)).await.unwrap();
+
+    fs::remove_file(path).unwrap();
+    result
+}
+
+#[tokio::test]
+async fn head_copies_first_lines() {
+    assert_eq!(filter("head", "head -n 2").await, "1\n2\n");
+    assert_eq!(filter("head-all", "head").await, "1\n2\n3\n4\n5\n");
+}
+
+#[tokio::test]
+async fn tail_copies_last_lines() {
+    assert_eq!(filter("tail", "tail -n 2").await, "4\n5\n");
+    assert_eq!(filter("tail-none", "tail -n 0").await, "");
+}
+
+#[tokio::test]
+async fn grep_copies_matching_lines() {
+    assert_eq!(filter("grep", "grep `[24]`").await, "2\n4\n");
+    assert_eq!(filter("grep-invert", "grep -v `[24]`").await, "1\n3\n5\n");
+    assert_eq!(filter_input("grep-case", "Foo bar FOO", "grep -i '^foo$'").await, "Foo\nFOO\n");
+}
+
+#[tokio::test]
+async fn grep_invalid_pattern_throws() {
+    let mut fiber = common::fiber().await;
+
+    let error = fiber.execute(None, r#"
+        import 'std/text' for grep
+        grep '('
+    "#).await.unwrap_err();
+
+    assert!(error.message().to_string().contains("invalid pattern"));
+}
@@ -0,0 +1,69 @@
+//! Parser throughput benchmarks.
+//!
+//! Run with `cargo bench -p riptide-syntax`.
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use riptide_syntax::parse_str;
+
+/// The scripts run by every runtime when it starts.
+const INIT_SCRIPTS: &[(&str, &str)] = &[
+    ("runtime", include_str!("../../runtime/src/init.rt")),
+    ("stdlib", include_str!("../../stdlib/src/init.rt")),
+    ("interactive", include_str!("../../shell/src/interactive.rt")),
+];
+
+/// A chunk of script using most kinds of syntax, repeated to make larger
+/// inputs.
+const CHUNK: &str = r#"
+# Find the largest files in a directory.
+$files = [:]
+$limit = $config->limit ?? 10
+
+ls -l $dir | grep -v '^total' | foreach <line> {
+    $fields = $(split $line)
+    $files->last = $fields[-1]
+}
+
+match $mode {
+    'json' => { to-json $files }
+    _ => { println "found ${count} files in $dir" }
+}
+
+let @strict = true {
+    sub report <name, [verbose false]> {
+        "Prints a report line."
+        println "$name: $(wc -c $name)" `\d+`
+    }
+}
+"#;
+
+fn init_scripts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("init");
+
+    for (name, source) in INIT_SCRIPTS {
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), source, |b, source| {
+            b.iter(|| parse_str(black_box(source)).unwrap());
+        });
+    }
+
+    group.finish();
+}
+
+fn large_scripts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("large");
+
+    for repeat in [1, 10, 100] {
+        let source = CHUNK.repeat(repeat);
+
+        group.throughput(Throughput::Bytes(source.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(source.len()), &source, |b, source| {
+            b.iter(|| parse_str(black_box(source)).unwrap());
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, init_scripts, large_scripts);
+criterion_main!(benches);
@@ -4,6 +4,8 @@ use crate::source::Span;
 use regex::bytes::Regex;
 use std::fmt;
 
+pub mod visit;
+
 macro_rules! derive_debug_enum_transparent {
     (
         $(#[$meta:meta])*
@@ -33,14 +35,74 @@ macro_rules! derive_debug_enum_transparent {
     };
 }
 
+/// Implements `Debug` for an AST node without printing its span.
+///
+/// Only blocks include their span in their debug output. Printing the span of
+/// every node would make the output of a whole tree difficult to read.
+macro_rules! derive_debug_without_span {
+    ($name:ident { $($field:ident),* }) => {
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_struct(stringify!($name))
+                    $(.field(stringify!($field), &self.$field))*
+                    .finish()
+            }
+        }
+    };
+
+    ($name:ident ( $($field:ident),* )) => {
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                f.debug_tuple(stringify!($name))
+                    $(.field(&self.$field))*
+                    .finish()
+            }
+        }
+    };
+
+    ($name:ident => $field:ident) => {
+        impl ::std::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                ::std::fmt::Debug::fmt(&self.$field, f)
+            }
+        }
+    };
+}
+
+/// Implements `PartialEq` for an AST node by comparing every field except its
+/// span.
+///
+/// Two nodes parsed from different places in the source are equal if they
+/// have the same structure.
+macro_rules! derive_partial_eq_without_span {
+    ($name:ident { $($field:ident),* }) => {
+        impl ::std::cmp::PartialEq for $name {
+            fn eq(&self, other: &Self) -> bool {
+                true $(&& self.$field == other.$field)*
+            }
+        }
+    };
+}
+
+/// An AST node that may know where in the source it was parsed from.
+///
+/// Nodes produced by the parser have a span, while nodes built by hand will
+/// usually not have one. Spans are not considered when comparing nodes for
+/// equality.
+pub trait Spanned {
+    /// Get the span in the source that this node was parsed from, if known.
+    fn span(&self) -> Option<&Span>;
+}
+
 /// A function block, containing a list of pipelines to execute.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Block {
     /// Where in the source the block is defined.
     pub span: Option<Span>,
 
     /// A list of named parameters.
-    pub named_params: Option<Vec<String>>,
+    pub named_params: Option<Vec<NamedParam>>,
 
     /// An optional, final named parameter that receives unbound arguments as a
     /// list.
@@ -50,78 +112,203 @@ pub struct Block {
     pub statements: Vec<Statement>,
 }
 
+/// A named block parameter.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NamedParam {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    /// The name of the variable the argument is bound to.
+    pub name: String,
+
+    /// An expression to evaluate for the parameter value if no argument is
+    /// given for it.
+    pub default: Option<Expr>,
+}
+
 /// A subroutine is a named block.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Subroutine {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
     pub name: String,
+
+    /// Documentation for the subroutine, given as a quoted string at the start
+    /// of its body. The doc string is not part of the block.
+    pub doc: Option<String>,
+
     pub block: Block,
 }
 
 derive_debug_enum_transparent! {
     #[derive(Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
     pub enum Statement {
         Assignment(AssignmentStatement),
+        Background(Background),
+        Condition(Condition),
         Import(ImportStatement),
         Pipeline(Pipeline),
-        Return(Option<Expr>),
+        Return(ReturnStatement),
     }
 }
 
+/// A pipeline to be run in the background, such as `foo | bar &`.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Background(pub Pipeline);
+
+/// Pipelines joined by the `&&` and `||` operators, such as
+/// `make && make install`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Condition {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    /// The pipeline that is always run.
+    pub first: Pipeline,
+
+    /// The pipelines after the first, each run or skipped depending on whether
+    /// the pipeline run before it succeeded.
+    pub rest: Vec<(ConditionOperator, Pipeline)>,
+}
+
+/// An operator joining the pipelines of a [`Condition`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ConditionOperator {
+    /// `&&`, which runs the next pipeline if the one before it succeeded.
+    And,
+
+    /// `||`, which runs the next pipeline if the one before it failed.
+    Or,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct AssignmentStatement {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
     pub target: AssignmentTarget,
     pub value: Expr,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// A return statement, such as `return $value`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ReturnStatement {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    /// The value to return, if any.
+    pub value: Option<Expr>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ImportStatement {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
     pub path: String,
     pub clause: ImportClause,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum ImportClause {
     Items(Vec<String>),
     Wildcard,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum AssignmentTarget {
     /// Assign a value to the member of an object.
     MemberAccess(MemberAccess),
 
     /// Assign a variable.
-    Variable(String),
+    Variable(VariableReference),
 }
 
 /// A pipeline of function calls.
-#[derive(Clone, Debug, PartialEq)]
-pub struct Pipeline(pub Vec<Call>);
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Pipeline {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    /// The calls in the pipeline, in order.
+    pub calls: Vec<Call>,
+}
 
 /// A function call.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Call {
     /// A function call for a named function.
     Named {
+        span: Option<Span>,
         function: String,
         args: Vec<CallArg>,
     },
 
     /// A function call on a callable object.
     Unnamed {
+        span: Option<Span>,
         function: Box<Expr>,
-        args: Vec<CallArg>
+        args: Vec<CallArg>,
     },
 }
 
+impl fmt::Debug for Call {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Call::Named { function, args, .. } => f.debug_struct("Named")
+                .field("function", function)
+                .field("args", args)
+                .finish(),
+            Call::Unnamed { function, args, .. } => f.debug_struct("Unnamed")
+                .field("function", function)
+                .field("args", args)
+                .finish(),
+        }
+    }
+}
+
+impl PartialEq for Call {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Call::Named { function, args, .. },
+                Call::Named { function: other_function, args: other_args, .. },
+            ) => function == other_function && args == other_args,
+            (
+                Call::Unnamed { function, args, .. },
+                Call::Unnamed { function: other_function, args: other_args, .. },
+            ) => function == other_function && args == other_args,
+            _ => false,
+        }
+    }
+}
+
 /// An argument to a function call.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum CallArg {
     /// A single expression.
     Expr(Expr),
 
     /// A splat, expanding the expression as a list into multiple args.
     Splat(Expr),
+
+    /// A named argument with an optional value, such as `--name value`.
+    Named(String, Option<Expr>),
 }
 
 derive_debug_enum_transparent! {
@@ -129,43 +316,211 @@ derive_debug_enum_transparent! {
     ///
     /// Contains a variant for each different expression type.
     #[derive(Clone, PartialEq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize))]
     pub enum Expr {
         Block(Block),
         Subroutine(Subroutine),
         Pipeline(Pipeline),
         MemberAccess(MemberAccess),
+        Subscript(Subscript),
+        Coalesce(Coalesce),
         CvarReference(CvarReference),
         CvarScope(CvarScope),
+        Match(Match),
+        Try(Try),
         Regex(RegexLiteral),
         Substitution(Substitution),
         Table(TableLiteral),
         List(ListLiteral),
-        Number(f64),
+        Number(NumberLiteral),
         InterpolatedString(InterpolatedString),
-        String(String),
+        Tilde(TildeLiteral),
+        String(StringLiteral),
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct MemberAccess(pub Box<Expr>, pub String);
+/// A member access expression, such as `$table->key`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MemberAccess {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct CvarReference(pub String);
+    /// The table to access a member of.
+    pub target: Box<Expr>,
 
-#[derive(Clone, Debug, PartialEq)]
+    /// The name of the member.
+    pub name: String,
+}
+
+/// A subscript expression with a computed index, such as `$list[-1]` or
+/// `$table[$key]`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Subscript {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    /// The list or table to index.
+    pub target: Box<Expr>,
+
+    pub index: Box<Expr>,
+}
+
+/// A nil-coalescing expression, such as `$name ?? default`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Coalesce {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    pub lhs: Box<Expr>,
+
+    /// The expression to evaluate if the left-hand side is nil.
+    pub rhs: Box<Expr>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct CvarReference {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    pub name: String,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct CvarScope {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
     pub name: CvarReference,
     pub value: Box<Expr>,
     pub scope: Block,
 }
 
-/// Value substitution.
+/// A match expression, such as `match $x { 'a' => { ... } _ => { ... } }`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Match {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    /// The value to compare against each pattern.
+    pub subject: Box<Expr>,
+
+    /// The arms to try, in order.
+    pub arms: Vec<MatchArm>,
+}
+
+/// One arm of a match expression.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MatchArm {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    pub pattern: MatchPattern,
+
+    /// The block to run if the pattern matches.
+    pub body: Block,
+}
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum MatchPattern {
+    /// Matches a subject equal to the value of a literal expression.
+    Value(Box<Expr>),
+
+    /// Matches any subject, written as `_`.
+    Wildcard,
+}
+
+/// A try expression, such as `try { ... } catch e { ... } finally { ... }`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Try {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    /// The block to run.
+    pub body: Box<Block>,
+
+    /// The clause to run if the body throws an exception.
+    pub catch: Option<Box<Catch>>,
+
+    /// A block to run after the body and catch clause, whether or not an
+    /// exception was thrown.
+    pub finally: Option<Box<Block>>,
+}
+
+/// The catch clause of a try expression.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Catch {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    /// The name of the variable to bind the exception to, if any.
+    pub name: Option<String>,
+
+    pub body: Block,
+}
+
+/// An unquoted string starting with a tilde, such as `~/src`.
+///
+/// Holds the full text of the string, including the tilde. Unlike other string
+/// literals, the value depends on the environment the program is run in, since
+/// a leading `~` or `~user` is expanded to a home directory.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TildeLiteral {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    pub text: String,
+}
+
+/// A quoted or unquoted string literal, such as `'hello'` or `echo`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct StringLiteral {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    /// The value of the string, with any escapes translated.
+    pub value: String,
+}
+
+/// A number literal, such as `42` or `0.5`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct NumberLiteral {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    pub value: f64,
+}
+
+/// A reference to a variable by name, such as `$foo`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct VariableReference {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    pub name: String,
+}
+
+/// Value substitution.
+#[derive(Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Substitution {
     /// A format substitution with a variable and parameters, such as `${foo:.2}`.
     ///
     /// This always evaluates to a string, unless the referenced variable is not defined.
-    Format(String, Option<String>),
+    Format(FormatSubstitution),
 
     /// A pipeline substitution, such as `$(add 1 2 3)`.
     ///
@@ -175,29 +530,79 @@ pub enum Substitution {
     /// A simple variable substitution, such as `$foo`.
     ///
     /// This gets evaluated to the current value of the variable identified.
-    Variable(String),
+    Variable(VariableReference),
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct TableLiteral(pub Vec<TableEntry>);
+impl fmt::Debug for Substitution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Substitution::Format(format) => f.debug_tuple("Format")
+                .field(&format.name)
+                .field(&format.flags)
+                .finish(),
+            Substitution::Pipeline(pipeline) => f.debug_tuple("Pipeline").field(pipeline).finish(),
+            Substitution::Variable(variable) => f.debug_tuple("Variable").field(variable).finish(),
+        }
+    }
+}
 
-#[derive(Clone, Debug, PartialEq)]
+/// The variable and parameters of a format substitution, such as `${foo:.2}`.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct FormatSubstitution {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    /// The name of the variable to format.
+    pub name: String,
+
+    /// The format parameters following the colon, if any.
+    pub flags: Option<String>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TableLiteral {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    pub entries: Vec<TableEntry>,
+}
+
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct TableEntry {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
     pub key: Expr,
-    pub value: Expr
+    pub value: Expr,
 }
 
-#[derive(Clone, Debug, PartialEq)]
-pub struct ListLiteral(pub Vec<Expr>);
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ListLiteral {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    pub items: Vec<Expr>,
+}
 
 /// An interpolated string literal.
 ///
 /// An interpolated string is made up of a sequence of parts that, when stringified and concatenated in order, form the
 /// desired string value.
-#[derive(Clone, Debug, PartialEq)]
-pub struct InterpolatedString(pub Vec<InterpolatedStringPart>);
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct InterpolatedString {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    pub parts: Vec<InterpolatedStringPart>,
+}
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum InterpolatedStringPart {
     String(String),
     Substitution(Substitution),
@@ -212,17 +617,326 @@ pub enum InterpolatedStringPart {
 /// This also can be used as a runtime optimizations, as regex literals do not
 /// have to be re-parsed every time they are used without any effort from the
 /// user. They can be executed directly from AST memory.
-#[derive(Clone, Debug)]
-pub struct RegexLiteral(pub Regex);
+#[derive(Clone)]
+pub struct RegexLiteral {
+    /// Where in the source the node is defined.
+    pub span: Option<Span>,
+
+    pub regex: Regex,
+}
 
 impl fmt::Display for RegexLiteral {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        self.0.fmt(f)
+        self.regex.fmt(f)
     }
 }
 
 impl PartialEq for RegexLiteral {
     fn eq(&self, other: &Self) -> bool {
-        self.0.as_str() == other.0.as_str()
+        self.regex.as_str() == other.regex.as_str()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RegexLiteral {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.regex.as_str())
+    }
+}
+
+derive_debug_without_span!(NamedParam { name, default });
+derive_debug_without_span!(Subroutine { name, doc, block });
+derive_debug_without_span!(Condition { first, rest });
+derive_debug_without_span!(AssignmentStatement { target, value });
+derive_debug_without_span!(ReturnStatement { value });
+derive_debug_without_span!(ImportStatement { path, clause });
+derive_debug_without_span!(Pipeline(calls));
+derive_debug_without_span!(MemberAccess(target, name));
+derive_debug_without_span!(Subscript { target, index });
+derive_debug_without_span!(Coalesce { lhs, rhs });
+derive_debug_without_span!(CvarReference(name));
+derive_debug_without_span!(CvarScope { name, value, scope });
+derive_debug_without_span!(Match { subject, arms });
+derive_debug_without_span!(MatchArm { pattern, body });
+derive_debug_without_span!(Try { body, catch, finally });
+derive_debug_without_span!(Catch { name, body });
+derive_debug_without_span!(TildeLiteral(text));
+derive_debug_without_span!(StringLiteral => value);
+derive_debug_without_span!(NumberLiteral => value);
+derive_debug_without_span!(VariableReference => name);
+derive_debug_without_span!(TableLiteral(entries));
+derive_debug_without_span!(TableEntry { key, value });
+derive_debug_without_span!(ListLiteral(items));
+derive_debug_without_span!(InterpolatedString(parts));
+derive_debug_without_span!(RegexLiteral(regex));
+
+derive_partial_eq_without_span!(Block { named_params, vararg_param, statements });
+derive_partial_eq_without_span!(NamedParam { name, default });
+derive_partial_eq_without_span!(Subroutine { name, doc, block });
+derive_partial_eq_without_span!(Condition { first, rest });
+derive_partial_eq_without_span!(AssignmentStatement { target, value });
+derive_partial_eq_without_span!(ReturnStatement { value });
+derive_partial_eq_without_span!(ImportStatement { path, clause });
+derive_partial_eq_without_span!(Pipeline { calls });
+derive_partial_eq_without_span!(MemberAccess { target, name });
+derive_partial_eq_without_span!(Subscript { target, index });
+derive_partial_eq_without_span!(Coalesce { lhs, rhs });
+derive_partial_eq_without_span!(CvarReference { name });
+derive_partial_eq_without_span!(CvarScope { name, value, scope });
+derive_partial_eq_without_span!(Match { subject, arms });
+derive_partial_eq_without_span!(MatchArm { pattern, body });
+derive_partial_eq_without_span!(Try { body, catch, finally });
+derive_partial_eq_without_span!(Catch { name, body });
+derive_partial_eq_without_span!(TildeLiteral { text });
+derive_partial_eq_without_span!(StringLiteral { value });
+derive_partial_eq_without_span!(NumberLiteral { value });
+derive_partial_eq_without_span!(VariableReference { name });
+derive_partial_eq_without_span!(FormatSubstitution { name, flags });
+derive_partial_eq_without_span!(TableLiteral { entries });
+derive_partial_eq_without_span!(TableEntry { key, value });
+derive_partial_eq_without_span!(ListLiteral { items });
+derive_partial_eq_without_span!(InterpolatedString { parts });
+
+impl Spanned for Block {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for NamedParam {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for Subroutine {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for Statement {
+    fn span(&self) -> Option<&Span> {
+        match self {
+            Statement::Assignment(statement) => statement.span(),
+            Statement::Background(background) => background.span(),
+            Statement::Condition(condition) => condition.span(),
+            Statement::Import(statement) => statement.span(),
+            Statement::Pipeline(pipeline) => pipeline.span(),
+            Statement::Return(statement) => statement.span(),
+        }
+    }
+}
+
+impl Spanned for Condition {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for Background {
+    fn span(&self) -> Option<&Span> {
+        self.0.span()
+    }
+}
+
+impl Spanned for AssignmentStatement {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for ReturnStatement {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for ImportStatement {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for AssignmentTarget {
+    fn span(&self) -> Option<&Span> {
+        match self {
+            AssignmentTarget::MemberAccess(member_access) => member_access.span(),
+            AssignmentTarget::Variable(variable) => variable.span(),
+        }
+    }
+}
+
+impl Spanned for Pipeline {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for Call {
+    fn span(&self) -> Option<&Span> {
+        match self {
+            Call::Named { span, .. } => span.as_ref(),
+            Call::Unnamed { span, .. } => span.as_ref(),
+        }
+    }
+}
+
+impl Spanned for CallArg {
+    fn span(&self) -> Option<&Span> {
+        match self {
+            CallArg::Expr(expr) => expr.span(),
+            CallArg::Splat(expr) => expr.span(),
+            CallArg::Named(_, value) => value.as_ref().and_then(Spanned::span),
+        }
+    }
+}
+
+impl Spanned for Expr {
+    fn span(&self) -> Option<&Span> {
+        match self {
+            Expr::Block(block) => block.span(),
+            Expr::Subroutine(subroutine) => subroutine.span(),
+            Expr::Pipeline(pipeline) => pipeline.span(),
+            Expr::MemberAccess(member_access) => member_access.span(),
+            Expr::Subscript(subscript) => subscript.span(),
+            Expr::Coalesce(coalesce) => coalesce.span(),
+            Expr::CvarReference(cvar) => cvar.span(),
+            Expr::CvarScope(cvar_scope) => cvar_scope.span(),
+            Expr::Match(match_expr) => match_expr.span(),
+            Expr::Try(try_expr) => try_expr.span(),
+            Expr::Regex(regex) => regex.span(),
+            Expr::Substitution(substitution) => substitution.span(),
+            Expr::Table(table) => table.span(),
+            Expr::List(list) => list.span(),
+            Expr::Number(number) => number.span(),
+            Expr::InterpolatedString(string) => string.span(),
+            Expr::Tilde(tilde) => tilde.span(),
+            Expr::String(string) => string.span(),
+        }
+    }
+}
+
+impl Spanned for MemberAccess {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for Subscript {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for Coalesce {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for CvarReference {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for CvarScope {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for Match {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for MatchArm {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for Try {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for Catch {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for TildeLiteral {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for StringLiteral {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for NumberLiteral {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for VariableReference {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for Substitution {
+    fn span(&self) -> Option<&Span> {
+        match self {
+            Substitution::Format(format) => format.span(),
+            Substitution::Pipeline(pipeline) => pipeline.span(),
+            Substitution::Variable(variable) => variable.span(),
+        }
+    }
+}
+
+impl Spanned for FormatSubstitution {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for TableLiteral {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for TableEntry {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for ListLiteral {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for InterpolatedString {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
+    }
+}
+
+impl Spanned for RegexLiteral {
+    fn span(&self) -> Option<&Span> {
+        self.span.as_ref()
     }
 }
@@ -0,0 +1,338 @@
+//! Traversal of an abstract syntax tree.
+//!
+//! The [`Visitor`] trait has a method for each type of node, which by default
+//! calls the matching `walk_*` function to visit the children of the node. An
+//! implementation only needs to override the methods for the nodes it is
+//! interested in, and can call the `walk_*` function from its override to keep
+//! walking into the children of the node.
+//!
+//! # Examples
+//!
+//! Finding the names of all the commands called in a script:
+//!
+//! ```
+//! use riptide_syntax::{ast::{*, visit::*}, parse, source::SourceFile};
+//!
+//! #[derive(Default)]
+//! struct CommandNames(Vec<String>);
+//!
+//! impl Visitor for CommandNames {
+//!     fn visit_call(&mut self, call: &Call) {
+//!         if let Call::Named { function, .. } = call {
+//!             self.0.push(function.clone());
+//!         }
+//!
+//!         walk_call(self, call);
+//!     }
+//! }
+//!
+//! let block = parse(SourceFile::r#virtual("example", "ls | grep { echo $(pwd) }")).unwrap();
+//! let mut names = CommandNames::default();
+//! names.visit_block(&block);
+//!
+//! assert_eq!(names.0, ["ls", "grep", "echo", "pwd"]);
+//! ```
+
+use super::*;
+
+/// A read-only visitor over the nodes of an abstract syntax tree.
+pub trait Visitor {
+    fn visit_block(&mut self, block: &Block) {
+        walk_block(self, block);
+    }
+
+    fn visit_named_param(&mut self, param: &NamedParam) {
+        walk_named_param(self, param);
+    }
+
+    fn visit_subroutine(&mut self, subroutine: &Subroutine) {
+        walk_subroutine(self, subroutine);
+    }
+
+    fn visit_statement(&mut self, statement: &Statement) {
+        walk_statement(self, statement);
+    }
+
+    fn visit_condition(&mut self, condition: &Condition) {
+        walk_condition(self, condition);
+    }
+
+    fn visit_assignment_statement(&mut self, statement: &AssignmentStatement) {
+        walk_assignment_statement(self, statement);
+    }
+
+    fn visit_return_statement(&mut self, statement: &ReturnStatement) {
+        walk_return_statement(self, statement);
+    }
+
+    fn visit_import_statement(&mut self, _statement: &ImportStatement) {}
+
+    fn visit_assignment_target(&mut self, target: &AssignmentTarget) {
+        walk_assignment_target(self, target);
+    }
+
+    fn visit_pipeline(&mut self, pipeline: &Pipeline) {
+        walk_pipeline(self, pipeline);
+    }
+
+    fn visit_call(&mut self, call: &Call) {
+        walk_call(self, call);
+    }
+
+    fn visit_call_arg(&mut self, arg: &CallArg) {
+        walk_call_arg(self, arg);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+
+    fn visit_member_access(&mut self, member_access: &MemberAccess) {
+        walk_member_access(self, member_access);
+    }
+
+    fn visit_subscript(&mut self, subscript: &Subscript) {
+        walk_subscript(self, subscript);
+    }
+
+    fn visit_coalesce(&mut self, coalesce: &Coalesce) {
+        walk_coalesce(self, coalesce);
+    }
+
+    fn visit_cvar_reference(&mut self, _cvar: &CvarReference) {}
+
+    fn visit_cvar_scope(&mut self, cvar_scope: &CvarScope) {
+        walk_cvar_scope(self, cvar_scope);
+    }
+
+    fn visit_match(&mut self, match_expr: &Match) {
+        walk_match(self, match_expr);
+    }
+
+    fn visit_match_arm(&mut self, arm: &MatchArm) {
+        walk_match_arm(self, arm);
+    }
+
+    fn visit_try(&mut self, try_expr: &Try) {
+        walk_try(self, try_expr);
+    }
+
+    fn visit_catch(&mut self, catch: &Catch) {
+        walk_catch(self, catch);
+    }
+
+    fn visit_regex_literal(&mut self, _regex: &RegexLiteral) {}
+
+    fn visit_substitution(&mut self, substitution: &Substitution) {
+        walk_substitution(self, substitution);
+    }
+
+    fn visit_table_literal(&mut self, table: &TableLiteral) {
+        walk_table_literal(self, table);
+    }
+
+    fn visit_table_entry(&mut self, entry: &TableEntry) {
+        walk_table_entry(self, entry);
+    }
+
+    fn visit_list_literal(&mut self, list: &ListLiteral) {
+        walk_list_literal(self, list);
+    }
+
+    fn visit_interpolated_string(&mut self, string: &InterpolatedString) {
+        walk_interpolated_string(self, string);
+    }
+
+    fn visit_tilde_literal(&mut self, _tilde: &TildeLiteral) {}
+}
+
+pub fn walk_block<V: Visitor + ?Sized>(visitor: &mut V, block: &Block) {
+    for param in block.named_params.iter().flatten() {
+        visitor.visit_named_param(param);
+    }
+
+    for statement in &block.statements {
+        visitor.visit_statement(statement);
+    }
+}
+
+pub fn walk_named_param<V: Visitor + ?Sized>(visitor: &mut V, param: &NamedParam) {
+    if let Some(default) = &param.default {
+        visitor.visit_expr(default);
+    }
+}
+
+pub fn walk_subroutine<V: Visitor + ?Sized>(visitor: &mut V, subroutine: &Subroutine) {
+    visitor.visit_block(&subroutine.block);
+}
+
+pub fn walk_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &Statement) {
+    match statement {
+        Statement::Assignment(statement) => visitor.visit_assignment_statement(statement),
+        Statement::Background(Background(pipeline)) => visitor.visit_pipeline(pipeline),
+        Statement::Condition(condition) => visitor.visit_condition(condition),
+        Statement::Import(statement) => visitor.visit_import_statement(statement),
+        Statement::Pipeline(pipeline) => visitor.visit_pipeline(pipeline),
+        Statement::Return(statement) => visitor.visit_return_statement(statement),
+    }
+}
+
+pub fn walk_condition<V: Visitor + ?Sized>(visitor: &mut V, condition: &Condition) {
+    visitor.visit_pipeline(&condition.first);
+
+    for (_, pipeline) in &condition.rest {
+        visitor.visit_pipeline(pipeline);
+    }
+}
+
+pub fn walk_assignment_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &AssignmentStatement) {
+    visitor.visit_assignment_target(&statement.target);
+    visitor.visit_expr(&statement.value);
+}
+
+pub fn walk_return_statement<V: Visitor + ?Sized>(visitor: &mut V, statement: &ReturnStatement) {
+    if let Some(value) = &statement.value {
+        visitor.visit_expr(value);
+    }
+}
+
+pub fn walk_assignment_target<V: Visitor + ?Sized>(visitor: &mut V, target: &AssignmentTarget) {
+    match target {
+        AssignmentTarget::MemberAccess(member_access) => visitor.visit_member_access(member_access),
+        AssignmentTarget::Variable(_) => {}
+    }
+}
+
+pub fn walk_pipeline<V: Visitor + ?Sized>(visitor: &mut V, pipeline: &Pipeline) {
+    for call in &pipeline.calls {
+        visitor.visit_call(call);
+    }
+}
+
+pub fn walk_call<V: Visitor + ?Sized>(visitor: &mut V, call: &Call) {
+    let args = match call {
+        Call::Named { args, .. } => args,
+        Call::Unnamed { function, args, .. } => {
+            visitor.visit_expr(function);
+            args
+        }
+    };
+
+    for arg in args {
+        visitor.visit_call_arg(arg);
+    }
+}
+
+pub fn walk_call_arg<V: Visitor + ?Sized>(visitor: &mut V, arg: &CallArg) {
+    match arg {
+        CallArg::Expr(expr) | CallArg::Splat(expr) => visitor.visit_expr(expr),
+        CallArg::Named(_, Some(expr)) => visitor.visit_expr(expr),
+        CallArg::Named(_, None) => {}
+    }
+}
+
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match expr {
+        Expr::Block(block) => visitor.visit_block(block),
+        Expr::Subroutine(subroutine) => visitor.visit_subroutine(subroutine),
+        Expr::Pipeline(pipeline) => visitor.visit_pipeline(pipeline),
+        Expr::MemberAccess(member_access) => visitor.visit_member_access(member_access),
+        Expr::Subscript(subscript) => visitor.visit_subscript(subscript),
+        Expr::Coalesce(coalesce) => visitor.visit_coalesce(coalesce),
+        Expr::CvarReference(cvar) => visitor.visit_cvar_reference(cvar),
+        Expr::CvarScope(cvar_scope) => visitor.visit_cvar_scope(cvar_scope),
+        Expr::Match(match_expr) => visitor.visit_match(match_expr),
+        Expr::Try(try_expr) => visitor.visit_try(try_expr),
+        Expr::Regex(regex) => visitor.visit_regex_literal(regex),
+        Expr::Substitution(substitution) => visitor.visit_substitution(substitution),
+        Expr::Table(table) => visitor.visit_table_literal(table),
+        Expr::List(list) => visitor.visit_list_literal(list),
+        Expr::InterpolatedString(string) => visitor.visit_interpolated_string(string),
+        Expr::Tilde(tilde) => visitor.visit_tilde_literal(tilde),
+        Expr::Number(_) | Expr::String(_) => {}
+    }
+}
+
+pub fn walk_member_access<V: Visitor + ?Sized>(visitor: &mut V, member_access: &MemberAccess) {
+    visitor.visit_expr(&member_access.target);
+}
+
+pub fn walk_subscript<V: Visitor + ?Sized>(visitor: &mut V, subscript: &Subscript) {
+    visitor.visit_expr(&subscript.target);
+    visitor.visit_expr(&subscript.index);
+}
+
+pub fn walk_coalesce<V: Visitor + ?Sized>(visitor: &mut V, coalesce: &Coalesce) {
+    visitor.visit_expr(&coalesce.lhs);
+    visitor.visit_expr(&coalesce.rhs);
+}
+
+pub fn walk_cvar_scope<V: Visitor + ?Sized>(visitor: &mut V, cvar_scope: &CvarScope) {
+    visitor.visit_cvar_reference(&cvar_scope.name);
+    visitor.visit_expr(&cvar_scope.value);
+    visitor.visit_block(&cvar_scope.scope);
+}
+
+pub fn walk_match<V: Visitor + ?Sized>(visitor: &mut V, match_expr: &Match) {
+    visitor.visit_expr(&match_expr.subject);
+
+    for arm in &match_expr.arms {
+        visitor.visit_match_arm(arm);
+    }
+}
+
+pub fn walk_match_arm<V: Visitor + ?Sized>(visitor: &mut V, arm: &MatchArm) {
+    if let MatchPattern::Value(pattern) = &arm.pattern {
+        visitor.visit_expr(pattern);
+    }
+
+    visitor.visit_block(&arm.body);
+}
+
+pub fn walk_try<V: Visitor + ?Sized>(visitor: &mut V, try_expr: &Try) {
+    visitor.visit_block(&try_expr.body);
+
+    if let Some(catch) = &try_expr.catch {
+        visitor.visit_catch(catch);
+    }
+
+    if let Some(finally) = &try_expr.finally {
+        visitor.visit_block(finally);
+    }
+}
+
+pub fn walk_catch<V: Visitor + ?Sized>(visitor: &mut V, catch: &Catch) {
+    visitor.visit_block(&catch.body);
+}
+
+pub fn walk_substitution<V: Visitor + ?Sized>(visitor: &mut V, substitution: &Substitution) {
+    match substitution {
+        Substitution::Pipeline(pipeline) => visitor.visit_pipeline(pipeline),
+        Substitution::Format(..) | Substitution::Variable(_) => {}
+    }
+}
+
+pub fn walk_table_literal<V: Visitor + ?Sized>(visitor: &mut V, table: &TableLiteral) {
+    for entry in &table.entries {
+        visitor.visit_table_entry(entry);
+    }
+}
+
+pub fn walk_table_entry<V: Visitor + ?Sized>(visitor: &mut V, entry: &TableEntry) {
+    visitor.visit_expr(&entry.key);
+    visitor.visit_expr(&entry.value);
+}
+
+pub fn walk_list_literal<V: Visitor + ?Sized>(visitor: &mut V, list: &ListLiteral) {
+    for expr in &list.items {
+        visitor.visit_expr(expr);
+    }
+}
+
+pub fn walk_interpolated_string<V: Visitor + ?Sized>(visitor: &mut V, string: &InterpolatedString) {
+    for part in &string.parts {
+        if let InterpolatedStringPart::Substitution(substitution) = part {
+            visitor.visit_substitution(substitution);
+        }
+    }
+}
@@ -2,10 +2,16 @@ use crate::{
     grammar::Rule,
     source::{SourceFile, Span},
 };
-use std::fmt;
+use std::{
+    error::Error,
+    fmt::{self, Write},
+};
 
 /// Describes an error that occurred in parsing.
 pub struct ParseError {
+    /// The general category of the error.
+    kind: ParseErrorKind,
+
     /// The error message details.
     variant: Variant,
 
@@ -13,6 +19,18 @@ pub struct ParseError {
     span: Span,
 }
 
+/// The general category of a parse error.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    /// The source contains invalid syntax.
+    Syntax,
+
+    /// The source ended while a construct was still open, such as an unclosed
+    /// brace, parenthesis, or string. The source might become valid if more
+    /// input were appended to it.
+    Incomplete,
+}
+
 enum Variant {
     Pest(Box<pest::error::Error<Rule>>),
     Message(String),
@@ -21,23 +39,63 @@ enum Variant {
 impl ParseError {
     pub(crate) fn new(span: Span, message: String) -> Self {
         Self {
+            kind: ParseErrorKind::Syntax,
+            variant: Variant::Message(message),
+            span,
+        }
+    }
+
+    /// Create an error for a construct that was still open at the end of the
+    /// source.
+    pub(crate) fn incomplete(span: Span, message: String) -> Self {
+        Self {
+            kind: ParseErrorKind::Incomplete,
             variant: Variant::Message(message),
             span,
         }
     }
 
     pub(crate) fn from_pest(span: Span, error: pest::error::Error<Rule>) -> Self {
+        // If the parser gave up at the end of the input, then it was still
+        // expecting more.
+        let kind = match error.location {
+            pest::error::InputLocation::Pos(offset)
+                if span.source_file().source_text()[offset..].trim().is_empty() => ParseErrorKind::Incomplete,
+            _ => ParseErrorKind::Syntax,
+        };
+
         Self {
+            kind,
             variant: Variant::Pest(Box::new(error.with_path(span.source_file().name().as_ref()))),
             span,
         }
     }
 
+    /// Get the general category of this error.
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Get the location in the source file where the error occurred.
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
     /// Get the source file the error occurred in.
     pub fn file(&self) -> &SourceFile {
         self.span.source_file()
     }
 
+    /// Render the error as a multi-line diagnostic, showing the lines of the
+    /// given source where the error occurred with the offending text
+    /// underlined.
+    pub fn render(&self, source: &SourceFile) -> String {
+        match &self.variant {
+            Variant::Pest(e) => render(&e.variant.message(), &self.span, source),
+            Variant::Message(e) => render(e, &self.span, source),
+        }
+    }
+
     // / Get the position in the file the error occurred.
     // pub fn position(&self) -> (usize, usize) {
     //     match self.inner.location {
@@ -56,6 +114,8 @@ impl fmt::Display for ParseError {
     }
 }
 
+impl Error for ParseError {}
+
 impl fmt::Debug for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match &self.variant {
@@ -64,3 +124,126 @@ impl fmt::Debug for ParseError {
         }
     }
 }
+
+/// An error paired with the location in source code it applies to.
+///
+/// This allows errors that do not carry their own location, such as runtime
+/// exceptions, to be reported in the same way as parse errors.
+#[derive(Clone, Debug)]
+pub struct Spanned<E> {
+    error: E,
+    span: Span,
+}
+
+impl<E> Spanned<E> {
+    pub fn new(error: E, span: Span) -> Self {
+        Self {
+            error,
+            span,
+        }
+    }
+
+    /// Get the wrapped error.
+    pub fn error(&self) -> &E {
+        &self.error
+    }
+
+    /// Get the location in the source file the error applies to.
+    pub fn span(&self) -> &Span {
+        &self.span
+    }
+
+    /// Unwrap the error, discarding its location.
+    pub fn into_inner(self) -> E {
+        self.error
+    }
+}
+
+impl<E: fmt::Display> Spanned<E> {
+    /// Render the error as a multi-line diagnostic, showing the lines of the
+    /// given source where the error occurred with the offending text
+    /// underlined.
+    pub fn render(&self, source: &SourceFile) -> String {
+        render(&self.error, &self.span, source)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for Spanned<E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.span, self.error)
+    }
+}
+
+impl<E: Error + 'static> Error for Spanned<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Render a message followed by the source lines covered by a span, in the
+/// style of rustc:
+///
+/// ```text
+/// error: unexpected token
+///  --> script.rt:2:5
+///   |
+/// 2 | foo ) bar
+///   |     ^
+/// ```
+///
+/// A span that covers more than one line is shown with its first and last
+/// lines, with the start and end of the span marked.
+fn render(message: &dyn fmt::Display, span: &Span, source: &SourceFile) -> String {
+    let lines = source.source_text()
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<_>>();
+    let line = |number: usize| lines.get(number - 1).copied().unwrap_or_default();
+
+    let (start, end) = (span.start(), span.end());
+    let gutter = end.line().to_string().len();
+    let mut output = String::new();
+
+    writeln!(output, "error: {}", message).unwrap();
+    writeln!(output, "{:gutter$}--> {}:{}", "", source.name(), start).unwrap();
+    writeln!(output, "{:gutter$} |", "").unwrap();
+
+    if start.line() == end.line() {
+        let text = line(start.line());
+        writeln!(output, "{:>gutter$} | {}", start.line(), text).unwrap();
+        writeln!(output, "{:gutter$} | {}", "", underline(text, start.column() - 1, end.column() - 1)).unwrap();
+    } else {
+        let text = line(start.line());
+        writeln!(output, "{:>gutter$} | {}", start.line(), text).unwrap();
+        writeln!(output, "{:gutter$} | {}", "", underline(text, start.column() - 1, text.len())).unwrap();
+
+        if end.line() > start.line() + 1 {
+            writeln!(output, "...").unwrap();
+        }
+
+        let text = line(end.line());
+        writeln!(output, "{:>gutter$} | {}", end.line(), text).unwrap();
+        writeln!(output, "{:gutter$} | {}", "", underline(text, 0, end.column() - 1)).unwrap();
+    }
+
+    output
+}
+
+/// Underline the text of a line between two byte offsets, always marking at
+/// least one character so that empty spans are still visible.
+fn underline(line: &str, start: usize, end: usize) -> String {
+    let mut output = String::new();
+    let mut carets = 0;
+
+    for (offset, c) in line.char_indices() {
+        if offset < start {
+            // Keep tabs so that the underline lines up with the text above.
+            output.push(if c == '\t' { '\t' } else { ' ' });
+        } else if offset < end {
+            carets += 1;
+        }
+    }
+
+    output.push_str(&"^".repeat(carets.max(1)));
+    output
+}
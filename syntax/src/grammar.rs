@@ -8,6 +8,8 @@ use pest::{
 #[grammar = "grammar.pest"]
 struct Grammar;
 
+// The error is boxed up by the caller, so the size here doesn't matter.
+#[allow(clippy::result_large_err)]
 pub(crate) fn parse(input: &str, rule: Rule) -> Result<Pairs<'_, Rule>, Error<Rule>> {
     Grammar::parse(rule, input)
 }
@@ -7,7 +7,8 @@
 pub mod ast;
 pub mod error;
 mod grammar;
+pub mod lint;
 mod parser;
 pub mod source;
 
-pub use parser::parse;
+pub use parser::{parse, parse_range, parse_str};
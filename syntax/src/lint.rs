@@ -0,0 +1,64 @@
+//! Checks for likely mistakes in a program that are not syntax errors.
+//!
+//! Lints work on a parsed syntax tree and return their findings as a list of
+//! diagnostics, leaving it up to the caller whether to report them as warnings
+//! or to ignore them.
+
+use crate::{
+    ast::{visit::*, *},
+    source::Span,
+};
+use std::fmt;
+
+/// A problem found in a program by a lint.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    /// Where in the source the problem is, if known.
+    pub span: Option<Span>,
+
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.span {
+            Some(span) => write!(f, "{}: {}", span, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Find statements that can never run because they follow a `return`
+/// statement in the same block.
+///
+/// One diagnostic is returned for each block containing unreachable
+/// statements, pointing at the first statement that is unreachable.
+pub fn unreachable_statements(block: &Block) -> Vec<Diagnostic> {
+    let mut lint = UnreachableStatements::default();
+    lint.visit_block(block);
+    lint.diagnostics
+}
+
+#[derive(Default)]
+struct UnreachableStatements {
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Visitor for UnreachableStatements {
+    fn visit_block(&mut self, block: &Block) {
+        let unreachable = block.statements
+            .iter()
+            .skip_while(|statement| !matches!(statement, Statement::Return(_)))
+            .nth(1);
+
+        if let Some(statement) = unreachable {
+            self.diagnostics.push(Diagnostic {
+                span: statement.span().cloned(),
+                message: String::from("unreachable statement after return"),
+            });
+        }
+
+        walk_block(self, block);
+    }
+}
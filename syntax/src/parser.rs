@@ -2,7 +2,7 @@ use crate::{
     ast::*,
     error::ParseError,
     grammar::{self, Rule},
-    source::{SourceFile, Span},
+    source::{byte_char, SourceFile, Span},
 };
 use pest::iterators::Pair;
 use regex::bytes::Regex;
@@ -15,13 +15,64 @@ use regex::bytes::Regex;
 pub fn parse(source_file: impl Into<SourceFile>) -> Result<Block, ParseError> {
     let source_file = source_file.into();
 
+    parse_program(&source_file, source_file.source_text())
+}
+
+/// Parse a string of source code into an abstract syntax tree.
+///
+/// This is a shorthand for [`parse`] with a virtual source file holding the
+/// string, for when the source does not come from a file.
+pub fn parse_str(source: &str) -> Result<Block, ParseError> {
+    parse(SourceFile::from(source))
+}
+
+/// Parse a range of a source file as a list of statements.
+///
+/// This is a cheaper way of updating the syntax tree of a file after part of it
+/// has changed, such as when editing it, as only the given range is parsed
+/// again. Spans in the returned statements refer to positions in the full file.
+///
+/// The range is given as byte offsets, and must start and end on statement
+/// boundaries: either at the start or end of the file, or next to a newline or
+/// `;` that separates statements in the same block. Separators at the start or
+/// end of the range are allowed. If the range starts or ends partway through a
+/// statement, the statements returned may differ from how the full file would
+/// be parsed, or a syntax error may be returned.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the file or does not start and end
+/// on character boundaries.
+pub fn parse_range(source_file: &SourceFile, start: usize, end: usize) -> Result<Vec<Statement>, ParseError> {
+    let text = &source_file.source_text()[..end];
+    let range = &text[start..];
+
+    // Blank out the text before the range while keeping its line breaks, so
+    // that offsets and line numbers in the input are the same as in the file.
+    let mut input = String::with_capacity(end);
+    input.extend(text[..start].bytes().map(|b| if b == b'\n' { '\n' } else { ' ' }));
+    input.push_str(range);
+
+    parse_program(source_file, &input).map(|block| block.statements)
+}
+
+fn parse_program(source_file: &SourceFile, input: &str) -> Result<Block, ParseError> {
     let mut ctx = ParsingContext {
         source_file: source_file.clone(),
     };
 
-    let mut pair = match grammar::parse(source_file.source_text(), Rule::program) {
+    let mut pair = match grammar::parse(input, Rule::program) {
         Ok(pair) => pair,
         Err(e) => {
+            // A block comment that is never closed would otherwise be reported
+            // at the end of the input, far from where the comment begins.
+            if let Some(start) = unterminated_block_comment(input) {
+                let span = source_file.slice(start, start + 2).unwrap();
+                let message = format!("{}: unterminated block comment", span);
+
+                return Err(ParseError::incomplete(span, message));
+            }
+
             let span = match e.location {
                 pest::error::InputLocation::Pos(offset) => source_file.slice(offset, offset).unwrap(),
                 pest::error::InputLocation::Span((start, end)) => source_file.slice(start, end).unwrap(),
@@ -34,6 +85,64 @@ pub fn parse(source_file: impl Into<SourceFile>) -> Result<Block, ParseError> {
     from_pair(pair.next().unwrap(), &mut ctx)
 }
 
+/// Find the start of the outermost block comment that is never closed, if
+/// there is one.
+///
+/// Comment markers inside of string and regex literals do not count, so those
+/// are skipped over the same way as the grammar does.
+fn unterminated_block_comment(input: &str) -> Option<usize> {
+    let bytes = input.as_bytes();
+    let mut openings = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+
+        if !openings.is_empty() {
+            if rest.starts_with(b"#{") {
+                openings.push(i);
+                i += 2;
+            } else if rest.starts_with(b"}#") {
+                openings.pop();
+                i += 2;
+            } else {
+                i += 1;
+            }
+
+            continue;
+        }
+
+        i = match rest {
+            [b'#', b'{', ..] => {
+                openings.push(i);
+                i + 2
+            }
+            [b'#', ..] => rest.iter().position(|&b| b == b'\n' || b == b'\r').map_or(bytes.len(), |end| i + end),
+            [quote @ (b'\'' | b'"'), ..] => skip_quoted(bytes, i + 1, *quote, *quote),
+            [b'`', ..] => skip_quoted(bytes, i + 1, b'`', b'\\'),
+            _ => i + 1,
+        };
+    }
+
+    openings.first().copied()
+}
+
+/// Get the offset just past the closing quote of a literal starting at the
+/// given offset, where a backslash followed by `escaped` is skipped over.
+fn skip_quoted(bytes: &[u8], mut i: usize, quote: u8, escaped: u8) -> usize {
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && bytes.get(i + 1) == Some(&escaped) {
+            i += 2;
+        } else if bytes[i] == quote {
+            return i + 1;
+        } else {
+            i += 1;
+        }
+    }
+
+    i
+}
+
 /// An AST node that can be parsed.
 ///
 /// Which is all of them...
@@ -89,7 +198,7 @@ impl ParsableNode for Block {
             for param in block_params.into_inner() {
                 match param.as_rule() {
                     Rule::param_decl => {
-                        named_params.get_or_insert_with(Vec::new).push(param.as_str().to_owned());
+                        named_params.get_or_insert_with(Vec::new).push(from_pair(param, ctx)?);
                     }
                     Rule::vararg_param_decl => {
                         vararg_param = Some(param.into_inner().next().unwrap().as_str().to_owned());
@@ -108,37 +217,127 @@ impl ParsableNode for Block {
     }
 }
 
+impl ParsableNode for NamedParam {
+    fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
+        assert_eq!(pair.as_rule(), Rule::param_decl);
+
+        let span = ctx.span(&pair);
+        let mut pairs = pair.into_inner();
+
+        Ok(NamedParam {
+            span: Some(span),
+            name: pairs.next().unwrap().as_str().to_owned(),
+            default: pairs.next().map(|p| from_pair(p, ctx)).transpose()?,
+        })
+    }
+}
+
 impl ParsableNode for Subroutine {
     fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
         assert!(pair.as_rule() == Rule::subroutine);
 
+        let span = ctx.span(&pair);
         let mut pairs = pair.into_inner();
+        let name = pairs.next().unwrap().as_str().to_owned();
+        let mut block = from_pair(pairs.next().unwrap(), ctx)?;
 
         Ok(Subroutine {
-            name: pairs.next().unwrap().as_str().to_owned(),
-            block: from_pair(pairs.next().unwrap(), ctx)?,
+            span: Some(span),
+            name,
+            doc: take_doc_string(&mut block),
+            block,
         })
     }
 }
 
+/// Remove the doc string from the start of a subroutine body, if it has one.
+///
+/// A doc string is a quoted string on its own as the first statement, followed
+/// by at least one other statement. A string that is the only statement is left
+/// alone, since it is also a call to the command of that name.
+fn take_doc_string(block: &mut Block) -> Option<String> {
+    if block.statements.len() < 2 {
+        return None;
+    }
+
+    let doc = match &block.statements[0] {
+        Statement::Pipeline(Pipeline { calls, .. }) => match calls.as_slice() {
+            // Only quoted strings count, as a bare word is a command.
+            [call @ Call::Named { function, args, .. }]
+                if args.is_empty() && call.span()?.source_text().starts_with('\'') => function.clone(),
+
+            [Call::Unnamed { function, args, .. }] if args.is_empty() => match &**function {
+                Expr::InterpolatedString(InterpolatedString { parts, .. }) => parts
+                    .iter()
+                    .map(|part| match part {
+                        InterpolatedStringPart::String(string) => Some(string.as_str()),
+                        InterpolatedStringPart::Substitution(_) => None,
+                    })
+                    .collect::<Option<String>>()?,
+                _ => return None,
+            },
+
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    block.statements.remove(0);
+
+    Some(doc)
+}
+
 impl ParsableNode for Statement {
     fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
         match pair.as_rule() {
             Rule::import_statement => Ok(Statement::Import(from_pair(pair, ctx)?)),
-            Rule::return_statement => Ok(Statement::Return({
+            Rule::return_statement => {
+                let span = ctx.span(&pair);
                 let mut pairs = pair.into_inner();
 
-                if let Some(pair) = pairs.next() {
-                    Some(from_pair(pair, ctx)?)
-                } else {
-                    None
+                Ok(Statement::Return(ReturnStatement {
+                    span: Some(span),
+                    value: pairs.next().map(|p| from_pair(p, ctx)).transpose()?,
+                }))
+            }
+            Rule::pipeline_statement => {
+                let span = ctx.span(&pair);
+                let mut pairs = pair.into_inner();
+                let pipeline = from_pair(pairs.next().unwrap(), ctx)?;
+                let mut rest = Vec::new();
+                let mut background = false;
+
+                while let Some(pair) = pairs.next() {
+                    match pair.as_rule() {
+                        Rule::condition_operator => {
+                            let operator = match pair.as_str() {
+                                "&&" => ConditionOperator::And,
+                                _ => ConditionOperator::Or,
+                            };
+
+                            rest.push((operator, from_pair(pairs.next().unwrap(), ctx)?));
+                        }
+                        _ => background = true,
+                    }
                 }
-            })),
-            Rule::pipeline_statement => Ok(Statement::Pipeline(from_pair(pair.into_inner().next().unwrap(), ctx)?)),
+
+                Ok(match (rest.is_empty(), background) {
+                    (true, true) => Statement::Background(Background(pipeline)),
+                    (true, false) => Statement::Pipeline(pipeline),
+                    (false, true) => return Err(ParseError::new(span, "a chain of pipelines joined by && or || cannot be run in the background".into())),
+                    (false, false) => Statement::Condition(Condition {
+                        span: Some(span),
+                        first: pipeline,
+                        rest,
+                    }),
+                })
+            }
             Rule::assignment_statement => {
+                let span = ctx.span(&pair);
                 let mut pairs = pair.into_inner();
 
                 Ok(Statement::Assignment(AssignmentStatement {
+                    span: Some(span),
                     target: from_pair(pairs.next().unwrap(), ctx)?,
                     value: from_pair(pairs.next().unwrap(), ctx)?,
                 }))
@@ -152,9 +351,11 @@ impl ParsableNode for ImportStatement {
     fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
         assert_eq!(pair.as_rule(), Rule::import_statement);
 
+        let span = ctx.span(&pair);
         let mut pairs = pair.into_inner();
 
         Ok(ImportStatement {
+            span: Some(span),
             path: string_literal(pairs.next().unwrap()),
             clause: from_pair(pairs.next().unwrap(), ctx)?,
         })
@@ -193,7 +394,7 @@ impl ParsableNode for AssignmentTarget {
 
         match pair.as_rule() {
             Rule::member_access_expr => Ok(AssignmentTarget::MemberAccess(from_pair(pair, ctx)?)),
-            Rule::variable_substitution => Ok(AssignmentTarget::Variable(string_literal(pair.into_inner().next().unwrap()))),
+            Rule::variable_substitution => Ok(AssignmentTarget::Variable(from_pair(pair, ctx)?)),
             rule => panic!("unexpected rule: {:?}", rule),
         }
     }
@@ -203,7 +404,12 @@ impl ParsableNode for Pipeline {
     fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
         assert_eq!(pair.as_rule(), Rule::pipeline);
 
-        Ok(Pipeline(pair.into_inner().map(|p| from_pair(p, ctx)).collect::<Result<_, _>>()?))
+        let span = ctx.span(&pair);
+
+        Ok(Pipeline {
+            span: Some(span),
+            calls: pair.into_inner().map(|p| from_pair(p, ctx)).collect::<Result<_, _>>()?,
+        })
     }
 }
 
@@ -211,6 +417,7 @@ impl ParsableNode for Call {
     fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
         assert_eq!(pair.as_rule(), Rule::call);
 
+        let span = ctx.span(&pair);
         let pair = pair.into_inner().next().unwrap();
 
         match pair.as_rule() {
@@ -218,16 +425,29 @@ impl ParsableNode for Call {
                 let mut pairs = pair.into_inner();
 
                 Ok(Call::Named {
+                    span: Some(span),
                     function: string_literal(pairs.next().unwrap()),
                     args: pairs.map(|p| from_pair(p, ctx)).collect::<Result<_, _>>()?,
                 })
             }
             Rule::unnamed_call => {
                 let mut pairs = pair.into_inner();
-
-                Ok(Call::Unnamed {
-                    function: Box::new(pairs.next().map(|p| from_pair(p, ctx)).unwrap()?),
-                    args: pairs.map(|p| from_pair(p, ctx)).collect::<Result<_, _>>()?,
+                let function = pairs.next().map(|p| from_pair(p, ctx)).unwrap()?;
+                let args = pairs.map(|p| from_pair(p, ctx)).collect::<Result<_, _>>()?;
+
+                Ok(match function {
+                    // A bare "try" that is not followed by a catch or finally
+                    // clause calls the command of that name.
+                    Expr::String(StringLiteral { value, .. }) if value == "try" => Call::Named {
+                        span: Some(span),
+                        function: value,
+                        args,
+                    },
+                    function => Call::Unnamed {
+                        span: Some(span),
+                        function: Box::new(function),
+                        args,
+                    },
                 })
             }
             rule => panic!("unexpected rule: {:?}", rule),
@@ -243,6 +463,14 @@ impl ParsableNode for CallArg {
 
         match pair.as_rule() {
             Rule::splat_arg => Ok(CallArg::Splat(from_pair(pair.into_inner().next().unwrap(), ctx)?)),
+            Rule::named_arg => {
+                let mut pairs = pair.into_inner();
+
+                Ok(CallArg::Named(
+                    pairs.next().unwrap().into_inner().next().unwrap().as_str().to_owned(),
+                    pairs.next().map(|p| from_pair(p, ctx)).transpose()?,
+                ))
+            }
             Rule::expr => Ok(CallArg::Expr(from_pair(pair, ctx)?)),
             rule => panic!("unexpected rule: {:?}", rule),
         }
@@ -256,16 +484,28 @@ impl Expr {
             Rule::block => Expr::Block(from_pair(pair, ctx)?),
             Rule::subroutine => Expr::Subroutine(from_pair(pair, ctx)?),
             Rule::pipeline => Expr::Pipeline(from_pair(pair, ctx)?),
-            Rule::member_access_expr => from_pair(pair, ctx).map(Expr::MemberAccess)?,
+            Rule::postfix_expr => postfix_expr(pair, ctx)?,
             Rule::cvar => Expr::CvarReference(from_pair(pair, ctx)?),
             Rule::cvar_scope => Expr::CvarScope(from_pair(pair, ctx)?),
+            Rule::match_expr => Expr::Match(from_pair(pair, ctx)?),
+            Rule::try_expr => Expr::Try(from_pair(pair, ctx)?),
             Rule::regex_literal => Expr::Regex(from_pair(pair, ctx)?),
             Rule::substitution => Expr::Substitution(from_pair(pair, ctx)?),
             Rule::table_literal => Expr::Table(from_pair(pair, ctx)?),
             Rule::list_literal => Expr::List(from_pair(pair, ctx)?),
             Rule::interpolated_string => Expr::InterpolatedString(from_pair(pair, ctx)?),
-            Rule::string_literal => Expr::String(string_literal(pair)),
-            Rule::number_literal => Expr::Number(pair.as_str().parse().unwrap()),
+            Rule::tilde_literal => Expr::Tilde(TildeLiteral {
+                span: Some(ctx.span(&pair)),
+                text: pair.as_str().to_owned(),
+            }),
+            Rule::string_literal => Expr::String(StringLiteral {
+                span: Some(ctx.span(&pair)),
+                value: string_literal(pair),
+            }),
+            Rule::number_literal => Expr::Number(NumberLiteral {
+                span: Some(ctx.span(&pair)),
+                value: pair.as_str().parse().unwrap(),
+            }),
             rule => panic!("unexpected rule: {:?}", rule),
         })
     }
@@ -275,7 +515,22 @@ impl ParsableNode for Expr {
     fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
         assert!(matches!(pair.as_rule(), Rule::expr));
 
-        Self::from_pair_inner(pair.into_inner().next().unwrap(), ctx)
+        let mut pairs = pair.into_inner().map(|operand| operand.into_inner().next().unwrap());
+        let lhs = pairs.next().unwrap();
+        let start = lhs.as_span().start();
+        let mut expr = Self::from_pair_inner(lhs, ctx)?;
+
+        // A chain such as `a ?? b ?? c` is grouped from the left, with each
+        // operation spanning from the start of the chain to its own operand.
+        for rhs in pairs {
+            expr = Expr::Coalesce(Coalesce {
+                span: ctx.source_file.slice(start, rhs.as_span().end()),
+                lhs: Box::new(expr),
+                rhs: Box::new(Self::from_pair_inner(rhs, ctx)?),
+            });
+        }
+
+        Ok(expr)
     }
 }
 
@@ -284,30 +539,78 @@ impl ParsableNode for MemberAccess {
         assert_eq!(pair.as_rule(), Rule::member_access_expr);
 
         let mut pairs = pair.into_inner();
-        let mut member_access = MemberAccess(
-            Box::new(Expr::from_pair_inner(pairs.next().unwrap(), ctx)?),
-            string_literal(pairs.next().unwrap()),
-        );
+        let lhs = pairs.next().unwrap();
+        let start = lhs.as_span().start();
+        let member_name = pairs.next().unwrap();
+
+        // Each access in a chain such as `$a->b->c` spans from the start of
+        // the expression to the end of its own member name.
+        let mut member_access = MemberAccess {
+            span: ctx.source_file.slice(start, member_name.as_span().end()),
+            target: Box::new(Expr::from_pair_inner(lhs, ctx)?),
+            name: string_literal(member_name),
+        };
 
         for member_name in pairs {
-            member_access = MemberAccess(Box::new(Expr::MemberAccess(member_access)), string_literal(member_name));
+            member_access = MemberAccess {
+                span: ctx.source_file.slice(start, member_name.as_span().end()),
+                target: Box::new(Expr::MemberAccess(member_access)),
+                name: string_literal(member_name),
+            };
         }
 
         Ok(member_access)
     }
 }
 
+/// Parse a chain of member accesses and subscripts, each of which spans from
+/// the start of the chain to the end of its own member name or subscript.
+fn postfix_expr(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Expr, ParseError> {
+    assert_eq!(pair.as_rule(), Rule::postfix_expr);
+
+    let mut pairs = pair.into_inner();
+    let operand = pairs.next().unwrap().into_inner().next().unwrap();
+    let start = operand.as_span().start();
+    let mut expr = Expr::from_pair_inner(operand, ctx)?;
+
+    for pair in pairs {
+        let span = ctx.source_file.slice(start, pair.as_span().end());
+
+        expr = match pair.as_rule() {
+            Rule::subscript => Expr::Subscript(Subscript {
+                span,
+                target: Box::new(expr),
+                index: Box::new(from_pair(pair.into_inner().next().unwrap(), ctx)?),
+            }),
+            _ => Expr::MemberAccess(MemberAccess {
+                span,
+                target: Box::new(expr),
+                name: string_literal(pair),
+            }),
+        };
+    }
+
+    Ok(expr)
+}
+
 impl ParsableNode for CvarReference {
-    fn from_pair(pair: Pair<'_, Rule>, _ctx: &mut ParsingContext) -> Result<Self, ParseError> {
-        Ok(CvarReference(string_literal(pair)))
+    fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
+        let span = ctx.span(&pair);
+
+        Ok(CvarReference {
+            span: Some(span),
+            name: string_literal(pair),
+        })
     }
 }
 
 impl ParsableNode for CvarScope {
     fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
+        let span = ctx.span(&pair);
         let mut pairs = pair.into_inner();
 
         Ok(CvarScope {
+            span: Some(span),
             name: from_pair(pairs.next().unwrap(), ctx)?,
             value: Box::new(from_pair(pairs.next().unwrap(), ctx)?),
             scope: from_pair(pairs.next().unwrap(), ctx)?,
@@ -315,6 +618,95 @@ impl ParsableNode for CvarScope {
     }
 }
 
+impl ParsableNode for Match {
+    fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
+        assert_eq!(pair.as_rule(), Rule::match_expr);
+
+        let span = ctx.span(&pair);
+
+        // Skip over the "match" keyword.
+        let mut pairs = pair.into_inner().skip(1);
+
+        Ok(Match {
+            span: Some(span),
+            subject: Box::new(from_pair(pairs.next().unwrap(), ctx)?),
+            arms: pairs.map(|pair| from_pair(pair, ctx)).collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl ParsableNode for MatchArm {
+    fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
+        assert_eq!(pair.as_rule(), Rule::match_arm);
+
+        let span = ctx.span(&pair);
+        let mut pairs = pair.into_inner();
+        let pattern = pairs.next().unwrap().into_inner().next().unwrap();
+
+        Ok(MatchArm {
+            span: Some(span),
+            pattern: match pattern.as_rule() {
+                Rule::match_wildcard => MatchPattern::Wildcard,
+                _ => MatchPattern::Value(Box::new(Expr::from_pair_inner(pattern, ctx)?)),
+            },
+            body: from_pair(pairs.next().unwrap(), ctx)?,
+        })
+    }
+}
+
+impl ParsableNode for Try {
+    fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
+        assert_eq!(pair.as_rule(), Rule::try_expr);
+
+        let span = ctx.span(&pair);
+
+        // Skip over the "try" keyword.
+        let mut pairs = pair.into_inner().skip(1);
+        let mut try_expr = Try {
+            span: Some(span),
+            body: Box::new(from_pair(pairs.next().unwrap(), ctx)?),
+            catch: None,
+            finally: None,
+        };
+
+        for clause in pairs {
+            match clause.as_rule() {
+                Rule::catch_clause => try_expr.catch = Some(Box::new(from_pair(clause, ctx)?)),
+                Rule::finally_clause => {
+                    try_expr.finally = Some(Box::new(from_pair(clause.into_inner().nth(1).unwrap(), ctx)?));
+                }
+                rule => panic!("unexpected rule: {:?}", rule),
+            }
+        }
+
+        Ok(try_expr)
+    }
+}
+
+impl ParsableNode for Catch {
+    fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
+        assert_eq!(pair.as_rule(), Rule::catch_clause);
+
+        let span = ctx.span(&pair);
+        let mut name = None;
+        let mut body = None;
+
+        // Skip over the "catch" keyword.
+        for pair in pair.into_inner().skip(1) {
+            match pair.as_rule() {
+                Rule::symbol => name = Some(pair.as_str().to_owned()),
+                _ => body = Some(from_pair(pair, ctx)?),
+            }
+        }
+
+        Ok(Catch {
+            span: Some(span),
+            name,
+            body: body.unwrap(),
+        })
+    }
+}
+
 impl ParsableNode for Substitution {
     fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
         assert_eq!(pair.as_rule(), Rule::substitution);
@@ -323,28 +715,47 @@ impl ParsableNode for Substitution {
 
         match pair.as_rule() {
             Rule::format_substitution => {
+                let span = ctx.span(&pair);
                 let mut pairs = pair.into_inner();
-                let variable = pairs.next().map(string_literal).unwrap();
-                let flags = pairs.next().map(|pair| pair.as_str().to_owned());
 
-                Ok(Substitution::Format(variable, flags))
+                Ok(Substitution::Format(FormatSubstitution {
+                    span: Some(span),
+                    name: pairs.next().map(string_literal).unwrap(),
+                    flags: pairs.next().map(|pair| pair.as_str().to_owned()),
+                }))
             }
             Rule::pipeline_substitution => {
                 Ok(Substitution::Pipeline(from_pair(pair.into_inner().next().unwrap(), ctx)?))
             }
-            Rule::variable_substitution => {
-                Ok(Substitution::Variable(string_literal(pair.into_inner().next().unwrap())))
-            }
+            Rule::variable_substitution => Ok(Substitution::Variable(from_pair(pair, ctx)?)),
             rule => panic!("unexpected rule: {:?}", rule),
         }
     }
 }
 
+impl ParsableNode for VariableReference {
+    fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
+        assert_eq!(pair.as_rule(), Rule::variable_substitution);
+
+        let span = ctx.span(&pair);
+
+        Ok(VariableReference {
+            span: Some(span),
+            name: string_literal(pair.into_inner().next().unwrap()),
+        })
+    }
+}
+
 impl ParsableNode for TableLiteral {
     fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
         assert_eq!(pair.as_rule(), Rule::table_literal);
 
-        Ok(TableLiteral(pair.into_inner().map(|p| from_pair(p, ctx)).collect::<Result<_, _>>()?))
+        let span = ctx.span(&pair);
+
+        Ok(TableLiteral {
+            span: Some(span),
+            entries: pair.into_inner().map(|p| from_pair(p, ctx)).collect::<Result<_, _>>()?,
+        })
     }
 }
 
@@ -352,9 +763,11 @@ impl ParsableNode for TableEntry {
     fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
         assert_eq!(pair.as_rule(), Rule::table_literal_entry);
 
+        let span = ctx.span(&pair);
         let mut pairs = pair.into_inner();
 
         Ok(TableEntry {
+            span: Some(span),
             key: from_pair(pairs.next().unwrap(), ctx)?,
             value: from_pair(pairs.next().unwrap(), ctx)?,
         })
@@ -365,7 +778,12 @@ impl ParsableNode for ListLiteral {
     fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
         assert_eq!(pair.as_rule(), Rule::list_literal);
 
-        Ok(ListLiteral(pair.into_inner().map(|p| from_pair(p, ctx)).collect::<Result<_, _>>()?))
+        let span = ctx.span(&pair);
+
+        Ok(ListLiteral {
+            span: Some(span),
+            items: pair.into_inner().map(|p| from_pair(p, ctx)).collect::<Result<_, _>>()?,
+        })
     }
 }
 
@@ -373,7 +791,12 @@ impl ParsableNode for InterpolatedString {
     fn from_pair(pair: Pair<'_, Rule>, ctx: &mut ParsingContext) -> Result<Self, ParseError> {
         assert_eq!(pair.as_rule(), Rule::interpolated_string);
 
-        Ok(InterpolatedString(pair.into_inner().map(|p| from_pair(p, ctx)).collect::<Result<_, _>>()?))
+        let span = ctx.span(&pair);
+
+        Ok(InterpolatedString {
+            span: Some(span),
+            parts: pair.into_inner().map(|p| from_pair(p, ctx)).collect::<Result<_, _>>()?,
+        })
     }
 }
 
@@ -397,7 +820,10 @@ impl ParsableNode for RegexLiteral {
         let regex_str = &regex_str[1..regex_str.len()-1];
 
         match Regex::new(regex_str) {
-            Ok(regex) => Ok(RegexLiteral(regex)),
+            Ok(regex) => Ok(RegexLiteral {
+                span: Some(ctx.span(&pair)),
+                regex,
+            }),
             Err(e) => Err(ParseError::new(ctx.span(&pair), e.to_string()))
         }
     }
@@ -408,6 +834,12 @@ fn string_literal(pair: Pair<'_, Rule>) -> String {
 }
 
 fn translate_escapes(source: &str) -> String {
+    // Most strings, and every unquoted string, have no escapes at all, so they
+    // can be copied as they are.
+    if !source.contains('\\') {
+        return source.to_owned();
+    }
+
     let mut string = String::with_capacity(source.len());
     let mut chars = source.chars();
 
@@ -417,6 +849,7 @@ fn translate_escapes(source: &str) -> String {
                 'n' => '\n',
                 'r' => '\r',
                 't' => '\t',
+                'x' => hex_escape(&mut chars).unwrap_or('x'),
                 c => c, // interpret all other chars as their literal
             }),
             c => string.push(c),
@@ -425,3 +858,18 @@ fn translate_escapes(source: &str) -> String {
 
     string
 }
+
+/// Read the two hex digits of a `\xHH` byte escape. Bytes that are not ASCII
+/// are stored the same way as invalid UTF-8 in a source file, so that the
+/// string can hold arbitrary bytes.
+fn hex_escape(chars: &mut std::str::Chars<'_>) -> Option<char> {
+    let digits = chars.as_str().get(..2)?;
+
+    if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    chars.nth(1);
+
+    Some(byte_char(u8::from_str_radix(digits, 16).unwrap()))
+}
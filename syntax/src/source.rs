@@ -2,13 +2,174 @@
 
 use std::{
     borrow::Cow,
+    cell::RefCell,
     cmp::Ordering,
+    collections::HashMap,
     fmt, fs, io,
     ops::Range,
     path::{Path, PathBuf},
     rc::Rc,
+    str,
+    time::{Instant, SystemTime},
 };
 
+/// The most files that are kept in the cache of opened files. Once it is full,
+/// the file that was opened least recently is dropped to make room.
+const MAX_OPEN_FILES: usize = 64;
+
+thread_local! {
+    /// Files opened with [`SourceFile::open`] by canonical path.
+    static OPEN_FILES: RefCell<HashMap<PathBuf, OpenFile>> = RefCell::default();
+}
+
+/// A file in the cache of opened files.
+struct OpenFile {
+    /// The state of the file on disk when it was read.
+    stamp: FileStamp,
+
+    /// When the file was last opened.
+    opened: Instant,
+
+    file: SourceFile,
+}
+
+/// The state of a file on disk, used to tell whether a cached copy of it is
+/// stale.
+///
+/// The modification time alone is not enough, since a file may be written more
+/// than once within the resolution of the file system's timestamps.
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+
+    /// Changes when the file is replaced by another, such as by an editor that
+    /// saves by renaming a new file into place.
+    #[cfg(unix)]
+    inode: u64,
+}
+
+impl FileStamp {
+    /// Get the stamp of a file from its metadata, if the platform can tell us
+    /// when the file was modified.
+    fn new(metadata: &fs::Metadata) -> Option<Self> {
+        Some(Self {
+            modified: metadata.modified().ok()?,
+            len: metadata.len(),
+            #[cfg(unix)]
+            inode: std::os::unix::fs::MetadataExt::ino(metadata),
+        })
+    }
+}
+
+/// The character that a zero byte would be mapped to by [`decode`]. Only bytes
+/// from `0x80` up are ever mapped, so the characters used are in the range
+/// `U+10FF80` to `U+10FFFF`, which lies in a private use area. Source text that
+/// contains these characters literally has them mapped too, byte by byte, so
+/// that they are not mistaken for a single raw byte.
+const RAW_BYTE_BASE: u32 = 0x10FF00;
+
+/// Get the key that a file is stored under in the cache of opened files, so
+/// that different paths to the same file share a single entry.
+fn cache_key(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+/// Decode the contents of a source file into text.
+///
+/// Source files are parsed as UTF-8, but strings in Riptide are arbitrary bytes
+/// and may need to hold text that is not, such as file names on Unix. Each
+/// byte of an invalid UTF-8 sequence is decoded as a character in a private
+/// use area instead of failing, so that [`raw_bytes`] can recover it later.
+fn decode(bytes: Vec<u8>) -> String {
+    let bytes = match String::from_utf8(bytes) {
+        Ok(text) => return escape(text),
+        Err(e) => e.into_bytes(),
+    };
+
+    let mut text = String::with_capacity(bytes.len());
+    let mut rest = &bytes[..];
+
+    loop {
+        match str::from_utf8(rest) {
+            Ok(valid) => {
+                push_escaped(&mut text, valid);
+                return text;
+            }
+            Err(e) => {
+                let (valid, invalid) = rest.split_at(e.valid_up_to());
+                let len = e.error_len().unwrap_or(invalid.len());
+
+                push_escaped(&mut text, str::from_utf8(valid).unwrap());
+                text.extend(invalid[..len].iter().copied().map(byte_char));
+
+                rest = &invalid[len..];
+            }
+        }
+    }
+}
+
+/// Map any characters in valid source text that [`raw_bytes`] would otherwise
+/// mistake for raw bytes to the raw bytes of their UTF-8 encoding instead.
+fn escape(text: String) -> String {
+    if !text.chars().any(|c| raw_byte(c).is_some()) {
+        return text;
+    }
+
+    let mut escaped = String::with_capacity(text.len());
+    push_escaped(&mut escaped, &text);
+    escaped
+}
+
+fn push_escaped(text: &mut String, valid: &str) {
+    for c in valid.chars() {
+        match raw_byte(c) {
+            Some(_) => text.extend(c.encode_utf8(&mut [0; 4]).bytes().map(byte_char)),
+            None => text.push(c),
+        }
+    }
+}
+
+/// Get the bytes of a string parsed from source code.
+///
+/// Source files may contain bytes that are not valid UTF-8, which are kept as
+/// characters in the range `U+10FF80` to `U+10FFFF` while parsing. This
+/// converts those characters back into the original bytes. Characters in that
+/// range that a source file contains literally are kept as the bytes of their
+/// UTF-8 encoding, and so come back out unchanged.
+pub fn raw_bytes(text: &str) -> Cow<'_, [u8]> {
+    if !text.chars().any(|c| raw_byte(c).is_some()) {
+        return Cow::Borrowed(text.as_bytes());
+    }
+
+    let mut bytes = Vec::with_capacity(text.len());
+
+    for c in text.chars() {
+        match raw_byte(c) {
+            Some(byte) => bytes.push(byte),
+            None => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+        }
+    }
+
+    Cow::Owned(bytes)
+}
+
+/// Get the character that represents a byte in text parsed from source code.
+/// ASCII bytes are represented by themselves.
+pub(crate) fn byte_char(byte: u8) -> char {
+    match byte {
+        0..=0x7f => byte as char,
+        _ => char::from_u32(RAW_BYTE_BASE + byte as u32).unwrap(),
+    }
+}
+
+fn raw_byte(c: char) -> Option<u8> {
+    match (c as u32).checked_sub(RAW_BYTE_BASE) {
+        Some(byte @ 0x80..=0xff) => Some(byte as u8),
+        _ => None,
+    }
+}
+
 /// Holds information about a source file being parsed in memory.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct SourceFile(Rc<Inner>);
@@ -32,15 +193,94 @@ struct Inner {
 
 impl SourceFile {
     /// Open a file as a file map.
+    ///
+    /// Bytes that are not valid UTF-8 are preserved as described in
+    /// [`raw_bytes`].
+    ///
+    /// Opened files are cached by their canonical path, so opening the same
+    /// file again, even by a different path, shares the contents read the first
+    /// time instead of reading the file again. The file is only read again if
+    /// its modification time, size or inode has changed, if it was removed from
+    /// the cache
+    /// using [`SourceFile::invalidate`], or if enough other files have been
+    /// opened since that it was dropped from the cache.
     pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
         let path = path.as_ref();
+        let key = cache_key(path);
+        let stamp = FileStamp::new(&fs::metadata(path)?);
+
+        if let Some(stamp) = stamp {
+            let cached = OPEN_FILES.with(|files| match files.borrow_mut().get_mut(&key) {
+                Some(cached) if cached.stamp == stamp => {
+                    cached.opened = Instant::now();
+                    Some(cached.file.clone())
+                }
+                _ => None,
+            });
+
+            if let Some(file) = cached {
+                return Ok(file);
+            }
+        }
+
+        let file = Self::new(path, false, decode(fs::read(path)?));
+
+        // If the platform can't tell us when the file was modified, then we
+        // have no way of knowing if a cached copy is stale.
+        if let Some(stamp) = stamp {
+            OPEN_FILES.with(|files| {
+                let mut files = files.borrow_mut();
+
+                if files.len() >= MAX_OPEN_FILES && !files.contains_key(&key) {
+                    let oldest = files.iter()
+                        .min_by_key(|(_, cached)| cached.opened)
+                        .map(|(key, _)| key.clone());
+
+                    if let Some(oldest) = oldest {
+                        files.remove(&oldest);
+                    }
+                }
+
+                files.insert(key, OpenFile {
+                    stamp,
+                    opened: Instant::now(),
+                    file: file.clone(),
+                });
+            });
+        }
+
+        Ok(file)
+    }
+
+    /// Remove a file from the cache of opened files, so that the next call to
+    /// [`SourceFile::open`] with the same path reads the file again.
+    ///
+    /// Existing handles to the file are not affected.
+    pub fn invalidate(path: impl AsRef<Path>) {
+        let key = cache_key(path.as_ref());
+
+        OPEN_FILES.with(|files| {
+            files.borrow_mut().remove(&key);
+        });
+    }
 
-        fs::read_to_string(path).map(|buffer| Self::new(path, false, buffer))
+    /// Remove all files from the cache of opened files.
+    pub fn clear_cache() {
+        OPEN_FILES.with(|files| files.borrow_mut().clear());
     }
 
     /// Create a "virtual" file using a provided path and in-memory buffer.
     pub fn r#virtual(path: impl Into<PathBuf>, contents: impl Into<String>) -> Self {
-        Self::new(path, true, contents)
+        Self::new(path, true, escape(contents.into()))
+    }
+
+    /// Create a "virtual" file using a provided path and an in-memory buffer
+    /// of raw bytes.
+    ///
+    /// Bytes that are not valid UTF-8 are preserved as described in
+    /// [`raw_bytes`].
+    pub fn virtual_from_bytes(path: impl Into<PathBuf>, contents: impl Into<Vec<u8>>) -> Self {
+        Self::new(path, true, decode(contents.into()))
     }
 
     fn new(path: impl Into<PathBuf>, r#virtual: bool, contents: impl Into<String>) -> Self {
@@ -49,7 +289,7 @@ impl SourceFile {
 
         for offset in 0..=buffer.len() {
             if let Some(b'\n') | None = buffer.as_bytes().get(offset) {
-                let start = line_offsets.last().map(|range| range.start).unwrap_or(0);
+                let start = line_offsets.last().map(|range| range.end).unwrap_or(0);
 
                 line_offsets.push(start..(offset + 1));
             }
@@ -262,6 +502,21 @@ impl fmt::Display for Span {
     }
 }
 
+/// Spans are serialized as their file name and start and end positions, with
+/// positions in `line:column` form.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Span {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("Span", 3)?;
+        state.serialize_field("file", &self.file.name())?;
+        state.serialize_field("start", &self.start.to_string())?;
+        state.serialize_field("end", &self.end.to_string())?;
+        state.end()
+    }
+}
+
 impl AsRef<str> for Span {
     fn as_ref(&self) -> &str {
         self.source_text()
@@ -287,4 +542,92 @@ mod tests {
         assert_eq!(file.get_position(0).unwrap().line(), 1);
         assert_eq!(file.get_position(0).unwrap().column(), 1);
     }
+
+    #[test]
+    fn invalid_utf8_is_preserved() {
+        let file = SourceFile::virtual_from_bytes("test", b"println 'a'\nprintln '\xff\xc3'".to_vec());
+
+        assert_eq!(file.lines().count(), 2);
+        assert_eq!(&*raw_bytes(file.source_text()), b"println 'a'\nprintln '\xff\xc3'");
+        assert_eq!(&*raw_bytes("caf\u{e9}"), "caf\u{e9}".as_bytes());
+    }
+
+    #[test]
+    fn private_use_characters_are_preserved() {
+        let source = "println '\u{10ff80}\u{10ffff}'";
+
+        let file = SourceFile::r#virtual("test", source);
+        assert_eq!(&*raw_bytes(file.source_text()), source.as_bytes());
+
+        let file = SourceFile::virtual_from_bytes("test", b"'\xff' ".iter().chain(source.as_bytes()).copied().collect::<Vec<_>>());
+        assert_eq!(&*raw_bytes(file.source_text()), [b"'\xff' ", source.as_bytes()].concat());
+    }
+
+    #[test]
+    fn opened_files_are_shared() {
+        let path = std::env::temp_dir().join(format!("riptide-source-test-{}.rt", std::process::id()));
+        fs::write(&path, "println hello").unwrap();
+
+        let first = SourceFile::open(&path).unwrap();
+        let second = SourceFile::open(&path).unwrap();
+        assert!(Rc::ptr_eq(&first.0, &second.0));
+
+        SourceFile::invalidate(&path);
+        let third = SourceFile::open(&path).unwrap();
+        assert!(!Rc::ptr_eq(&first.0, &third.0));
+        assert_eq!(third.source_text(), "println hello");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn rewritten_files_are_read_again() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("riptide-source-rewrite-test-{}.rt", std::process::id()));
+        fs::write(&path, "println hello").unwrap();
+        assert_eq!(SourceFile::open(&path).unwrap().source_text(), "println hello");
+
+        // Likely within the resolution of the modification time.
+        fs::write(&path, "println goodbye").unwrap();
+        assert_eq!(SourceFile::open(&path).unwrap().source_text(), "println goodbye");
+
+        // Replaced by a file of the same size.
+        let replacement = dir.join(format!("riptide-source-rewrite-test-{}.rt.new", std::process::id()));
+        fs::write(&replacement, "println welcome").unwrap();
+        fs::rename(&replacement, &path).unwrap();
+        assert_eq!(SourceFile::open(&path).unwrap().source_text(), "println welcome");
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn opened_files_are_shared_by_canonical_path() {
+        let dir = std::env::temp_dir();
+        let name = format!("riptide-source-canonical-test-{}.rt", std::process::id());
+        let path = dir.join(&name);
+        fs::write(&path, "println hello").unwrap();
+
+        let first = SourceFile::open(&path).unwrap();
+        let second = SourceFile::open(dir.join(".").join(&name)).unwrap();
+        assert!(Rc::ptr_eq(&first.0, &second.0));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn open_files_cache_is_bounded() {
+        let dir = std::env::temp_dir().join(format!("riptide-source-bounded-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let paths = (0..=MAX_OPEN_FILES).map(|i| dir.join(format!("{}.rt", i))).collect::<Vec<_>>();
+
+        for path in paths.iter() {
+            fs::write(path, "println hello").unwrap();
+            SourceFile::open(path).unwrap();
+        }
+
+        OPEN_FILES.with(|files| assert_eq!(files.borrow().len(), MAX_OPEN_FILES));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
 }
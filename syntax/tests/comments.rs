@@ -0,0 +1,43 @@
+use riptide_syntax::{error::ParseErrorKind, parse, source::*};
+
+/// Parse a program and format its statements, leaving out spans so that
+/// programs laid out differently can be compared.
+fn statements(source: &str) -> String {
+    format!("{:?}", parse(SourceFile::r#virtual("test", source)).unwrap().statements)
+}
+
+#[test]
+fn line_comments() {
+    assert_eq!(statements("foo # comment\nbar"), statements("foo\nbar"));
+    assert_eq!(statements("foo #comment {\nbar"), statements("foo\nbar"));
+    assert_eq!(statements("#\nfoo"), statements("foo"));
+}
+
+#[test]
+fn block_comments() {
+    assert_eq!(statements("foo #{ comment }# bar"), statements("foo bar"));
+    assert_eq!(statements("#{\n  several\n  lines\n}#\nfoo"), statements("foo"));
+    assert_eq!(statements("foo #{ a\nb }#\nbar"), statements("foo\nbar"));
+}
+
+#[test]
+fn nested_block_comments() {
+    assert_eq!(statements("foo #{ outer #{ inner }# still outer }# bar"), statements("foo bar"));
+    assert_eq!(statements("#{ #{ #{ }# }# }#"), statements(""));
+}
+
+#[test]
+fn comment_markers_in_strings() {
+    assert_eq!(statements("echo '#{' \"}#\""), statements("echo '#{' \"}#\" #{ comment }#"));
+}
+
+#[test]
+fn unterminated_block_comment() {
+    for (source, position) in [("foo #{ comment", "1:5"), ("foo\n  #{ outer #{ inner }#\n", "2:3"), ("echo '}#' #{ it's", "1:11")] {
+        let error = parse(SourceFile::r#virtual("test", source)).unwrap_err();
+
+        assert_eq!(error.kind(), ParseErrorKind::Incomplete, "source: {:?}", source);
+        assert_eq!(error.span().start().to_string(), position, "source: {:?}", source);
+        assert_eq!(error.to_string(), format!("test:{}: unterminated block comment", position));
+    }
+}
@@ -0,0 +1,31 @@
+use riptide_syntax::{parse, source::*};
+
+fn statement_count(source: &str) -> usize {
+    parse(SourceFile::r#virtual("test", source)).unwrap().statements.len()
+}
+
+#[test]
+fn empty_program() {
+    assert_eq!(statement_count(""), 0);
+}
+
+#[test]
+fn whitespace_only_program() {
+    for source in [" ", "\n", "\t \n\n  ", "\r\n\r\n", ";;\n;"] {
+        assert_eq!(statement_count(source), 0, "source: {:?}", source);
+    }
+}
+
+#[test]
+fn comment_only_program() {
+    for source in ["# comment", "# comment\n", "\n  # one\n\n# two\n", "#!/usr/bin/env ripsh\n"] {
+        assert_eq!(statement_count(source), 0, "source: {:?}", source);
+    }
+}
+
+#[test]
+fn byte_order_mark_is_ignored() {
+    assert_eq!(statement_count("\u{feff}"), 0);
+    assert_eq!(statement_count("\u{feff}# comment\n"), 0);
+    assert_eq!(statement_count("\u{feff}println hello\n"), 1);
+}
@@ -0,0 +1,72 @@
+use riptide_syntax::{
+    error::{ParseErrorKind, Spanned},
+    parse,
+    source::*,
+};
+
+fn error_kind(source: &str) -> ParseErrorKind {
+    parse(SourceFile::r#virtual("test", source)).unwrap_err().kind()
+}
+
+#[test]
+fn unclosed_constructs_are_incomplete() {
+    for source in ["{ foo", "foo (bar", "foo <a> {\n    bar\n", "println \"abc", "println 'abc", "[a b", "make &&"] {
+        assert_eq!(error_kind(source), ParseErrorKind::Incomplete, "source: {:?}", source);
+    }
+}
+
+#[test]
+fn stray_tokens_are_syntax_errors() {
+    for source in ["foo )", "foo }", "{ foo ) bar }", "a && b &"] {
+        assert_eq!(error_kind(source), ParseErrorKind::Syntax, "source: {:?}", source);
+    }
+}
+
+#[test]
+fn render_underlines_error_on_line() {
+    let source = SourceFile::r#virtual("test.rt", "println hello\nfoo ) bar\n");
+    let error = parse(source.clone()).unwrap_err();
+
+    let rendered = error.render(&source);
+
+    assert!(rendered.starts_with("error: expected "), "{}", rendered);
+    assert!(rendered.ends_with(concat!(
+        " --> test.rt:2:5\n",
+        "  |\n",
+        "2 | foo ) bar\n",
+        "  |     ^\n",
+    )), "{}", rendered);
+}
+
+#[test]
+fn render_marks_start_and_end_of_multi_line_span() {
+    let source = SourceFile::r#virtual("test.rt", "a {\n    b\n} c\n");
+    let span = source.slice(2, 11).unwrap();
+    let error = Spanned::new("block here", span);
+
+    assert_eq!(error.to_string(), "test.rt:1:3: block here");
+    assert_eq!(error.render(&source), concat!(
+        "error: block here\n",
+        " --> test.rt:1:3\n",
+        "  |\n",
+        "1 | a {\n",
+        "  |   ^\n",
+        "...\n",
+        "3 | } c\n",
+        "  | ^\n",
+    ));
+}
+
+#[test]
+fn render_aligns_underline_with_tabs() {
+    let source = SourceFile::r#virtual("test.rt", "\tfoo bar");
+    let error = Spanned::new("unknown", source.slice(5, 8).unwrap());
+
+    assert_eq!(error.render(&source), concat!(
+        "error: unknown\n",
+        " --> test.rt:1:6\n",
+        "  |\n",
+        "1 | \tfoo bar\n",
+        "  | \t    ^^^\n",
+    ));
+}
@@ -0,0 +1,34 @@
+use riptide_syntax::{parse, source::*};
+
+/// Parse a program and format its statements, leaving out spans so that
+/// programs laid out over different lines can be compared.
+fn statements(source: &str) -> String {
+    format!("{:?}", parse(SourceFile::r#virtual("test", source)).unwrap().statements)
+}
+
+fn statement_count(source: &str) -> usize {
+    parse(SourceFile::r#virtual("test", source)).unwrap().statements.len()
+}
+
+#[test]
+fn backslash_before_newline_continues_line() {
+    let expected = statements("echo a b --flag value");
+
+    for source in ["echo a \\\nb --flag value", "echo a \\\r\nb --flag value", "echo a \\\n  \\\n  b --flag \\\n  value"] {
+        assert_eq!(statements(source), expected, "source: {:?}", source);
+    }
+}
+
+#[test]
+fn pipeline_can_be_continued() {
+    let expected = statements("a | b | c");
+
+    assert_eq!(statements("a \\\n  | b \\\n  | c"), expected);
+    assert_eq!(statements("a | \\\n  b | \\\n  c"), expected);
+}
+
+#[test]
+fn backslash_not_before_newline_does_not_continue_line() {
+    assert_eq!(statement_count("a \\\n\n b"), 2);
+    assert!(parse(SourceFile::r#virtual("test", "a \\ \n b")).is_err());
+}
@@ -0,0 +1,31 @@
+use riptide_syntax::{
+    lint::unreachable_statements,
+    parse,
+    source::*,
+};
+
+fn unreachable(source: &str) -> Vec<String> {
+    let block = parse(SourceFile::r#virtual("test", source)).unwrap();
+
+    unreachable_statements(&block)
+        .into_iter()
+        .map(|diagnostic| diagnostic.span.unwrap().source_text().to_owned())
+        .collect()
+}
+
+#[test]
+fn statements_after_return_are_unreachable() {
+    assert_eq!(unreachable("println a\nreturn 1\nprintln b\nprintln c"), ["println b"]);
+    assert_eq!(unreachable("return; return"), ["return"]);
+}
+
+#[test]
+fn return_only_affects_its_own_block() {
+    assert_eq!(unreachable("$f = { return 1 }\nprintln $f"), Vec::<String>::new());
+    assert_eq!(unreachable("foo {\n    return\n    bar\n}\nbaz"), ["bar"]);
+}
+
+#[test]
+fn final_return_is_fine() {
+    assert!(unreachable("println a\nreturn").is_empty());
+}
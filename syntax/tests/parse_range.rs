@@ -0,0 +1,39 @@
+use riptide_syntax::{
+    ast::*,
+    parse,
+    parse_range,
+    source::*,
+};
+
+const SOURCE: &str = "println a\n$x = {\n    foo; bar\n}\nprintln b\n";
+
+#[test]
+fn range_parses_the_same_as_whole_file() {
+    let file = SourceFile::r#virtual("test", SOURCE);
+    let start = SOURCE.find("$x").unwrap();
+    let end = SOURCE.find("println b").unwrap();
+
+    let statements = parse_range(&file, start, end).unwrap();
+
+    assert_eq!(statements, parse(file.clone()).unwrap().statements[1..2]);
+    assert_eq!(statements[0].span().unwrap().start().line(), 2);
+}
+
+#[test]
+fn range_within_a_block() {
+    let file = SourceFile::r#virtual("test", SOURCE);
+    let start = SOURCE.find("bar").unwrap();
+
+    let statements = parse_range(&file, start, start + 3).unwrap();
+
+    assert_eq!(statements.len(), 1);
+    assert_eq!(statements[0].span().unwrap().source_text(), "bar");
+}
+
+#[test]
+fn range_cutting_a_block_is_an_error() {
+    let file = SourceFile::r#virtual("test", SOURCE);
+    let start = SOURCE.find("$x").unwrap();
+
+    assert!(parse_range(&file, start, SOURCE.find("bar").unwrap()).is_err());
+}
@@ -0,0 +1,101 @@
+use riptide_syntax::{
+    ast::*,
+    parse,
+    source::*,
+};
+
+fn parse_str(source: &str) -> Block {
+    parse(SourceFile::r#virtual("test", source)).unwrap()
+}
+
+fn text(node: &impl Spanned) -> &str {
+    node.span().unwrap().source_text()
+}
+
+fn calls(statement: &Statement) -> &[Call] {
+    match statement {
+        Statement::Pipeline(Pipeline { calls, .. }) => calls,
+        statement => panic!("not a pipeline: {:?}", statement),
+    }
+}
+
+fn args(call: &Call) -> &[CallArg] {
+    match call {
+        Call::Named { args, .. } | Call::Unnamed { args, .. } => args,
+    }
+}
+
+#[test]
+fn statements_and_calls_have_spans() {
+    let block = parse_str("$x = [1 2]\nfoo $x | bar --baz {\n    qux\n}\n");
+
+    assert_eq!(text(&block.statements[0]), "$x = [1 2]");
+    assert_eq!(text(&block.statements[1]), "foo $x | bar --baz {\n    qux\n}");
+
+    let calls = calls(&block.statements[1]);
+
+    assert_eq!(text(&calls[0]), "foo $x");
+    assert_eq!(text(&calls[1]), "bar --baz {\n    qux\n}");
+    assert_eq!(text(&args(&calls[1])[0]), "{\n    qux\n}");
+}
+
+#[test]
+fn expressions_have_spans() {
+    let block = parse_str("print $a->b->c @foo `re` [x y] 42 'hi'");
+    let args = args(&calls(&block.statements[0])[0]);
+
+    assert_eq!(text(&args[0]), "$a->b->c");
+    assert_eq!(text(&args[1]), "@foo");
+    assert_eq!(text(&args[2]), "`re`");
+    assert_eq!(text(&args[3]), "[x y]");
+    assert_eq!(text(&args[4]), "42");
+    assert_eq!(text(&args[5]), "'hi'");
+
+    match &args[0] {
+        CallArg::Expr(Expr::MemberAccess(MemberAccess { target, .. })) => assert_eq!(text(&**target), "$a->b"),
+        arg => panic!("not a member access: {:?}", arg),
+    }
+}
+
+#[test]
+fn coalesce_chain_has_spans() {
+    let block = parse_str("print $a ?? $b ?? c next");
+    let args = args(&calls(&block.statements[0])[0]);
+
+    assert_eq!(text(&args[0]), "$a ?? $b ?? c");
+
+    match &args[0] {
+        CallArg::Expr(Expr::Coalesce(Coalesce { lhs, .. })) => assert_eq!(text(&**lhs), "$a ?? $b"),
+        arg => panic!("not a coalesce: {:?}", arg),
+    }
+}
+
+#[test]
+fn variables_have_spans() {
+    let block = parse_str("$x = 1");
+
+    match &block.statements[0] {
+        Statement::Assignment(AssignmentStatement { target, .. }) => assert_eq!(text(target), "$x"),
+        statement => panic!("not an assignment: {:?}", statement),
+    }
+
+    let block = parse_str("print $z \"${y:.2}\"");
+    let calls = calls(&block.statements[0]);
+    let args = args(&calls[0]);
+
+    assert_eq!(text(&args[0]), "$z");
+
+    match &args[1] {
+        CallArg::Expr(Expr::InterpolatedString(InterpolatedString { parts, .. })) => match &parts[0] {
+            InterpolatedStringPart::Substitution(substitution) => assert_eq!(text(substitution), "${y:.2}"),
+            part => panic!("not a substitution: {:?}", part),
+        },
+        arg => panic!("not an interpolated string: {:?}", arg),
+    }
+}
+
+#[test]
+fn equality_ignores_spans() {
+    assert_eq!(parse_str("foo 'a' $b `c`"), parse_str("\n    foo  'a'   $b `c`"));
+    assert_ne!(parse_str("foo 'a'"), parse_str("foo 'b'"));
+}
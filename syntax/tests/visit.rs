@@ -0,0 +1,53 @@
+use riptide_syntax::{
+    ast::{*, visit::*},
+    parse,
+    source::*,
+};
+
+#[derive(Default)]
+struct Variables(Vec<String>);
+
+impl Visitor for Variables {
+    fn visit_substitution(&mut self, substitution: &Substitution) {
+        match substitution {
+            Substitution::Variable(variable) => self.0.push(variable.name.clone()),
+            Substitution::Format(format) => self.0.push(format.name.clone()),
+            Substitution::Pipeline(_) => {}
+        }
+
+        walk_substitution(self, substitution);
+    }
+}
+
+#[test]
+fn visits_nested_nodes() {
+    let block = parse(SourceFile::r#virtual("test", r#"
+        $x = [$a [b: $b]]
+        println "${c}" | { return $(get $d) }
+        sub f <[e $e]> {
+            $f->g
+        }
+    "#)).unwrap();
+
+    let mut variables = Variables::default();
+    variables.visit_block(&block);
+
+    assert_eq!(variables.0, ["a", "b", "c", "d", "e", "f"]);
+}
+
+#[test]
+fn overriding_a_method_stops_the_walk() {
+    struct TopLevelCalls(usize);
+
+    impl Visitor for TopLevelCalls {
+        fn visit_call(&mut self, _call: &Call) {
+            self.0 += 1;
+        }
+    }
+
+    let block = parse(SourceFile::r#virtual("test", "foo { bar } | baz\nqux")).unwrap();
+    let mut calls = TopLevelCalls(0);
+    calls.visit_block(&block);
+
+    assert_eq!(calls.0, 3);
+}